@@ -0,0 +1,32 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use petgraph::graph::UnGraph;
+use std::hint::black_box;
+use wl_isomorphism::GraphWrapper;
+
+// Copies of stars with 1 to 500 leaves: every size gives a class for the centre and one for the leaves, about 1k classes in all.
+// 80 copies make 10M nodes
+fn star_forest(copies: u32) -> UnGraph<(), ()> {
+    let mut edges = Vec::new();
+    let mut next = 0;
+    for _ in 0..copies {
+        for leaves in 1..=500 {
+            let centre = next;
+            edges.extend((1..=leaves).map(|leaf| (centre, centre + leaf)));
+            next += leaves + 1;
+        }
+    }
+    UnGraph::from_edges(edges)
+}
+
+fn digest(c: &mut Criterion) {
+    let mut wrap = GraphWrapper::new(star_forest(80), 42, 0, true, false);
+    wrap.run();
+    let mut group = c.benchmark_group("digest of 10M nodes in 1k classes");
+    group.sample_size(10);
+    group.bench_function("sorted", |b| b.iter(|| black_box(wrap.digest())));
+    group.bench_function("counted", |b| b.iter(|| black_box(wrap.counted_digest())));
+    group.finish();
+}
+
+criterion_group!(benches, digest);
+criterion_main!(benches);
@@ -0,0 +1,76 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use petgraph::graph::UnGraph;
+use std::hint::black_box;
+use wl_isomorphism::generators::{gnp, rewire_edges};
+use wl_isomorphism::{AggregationMode, WlConfig};
+
+// 10,000 different graphs on 20 nodes: a cycle with chords, randomly rewired
+fn small_graphs() -> Vec<UnGraph<(), ()>> {
+    let mut edges: Vec<(u32, u32)> = (0..20).map(|i| (i, (i + 1) % 20)).collect();
+    edges.extend((0..10).map(|i| (i, i + 7)));
+    let base = UnGraph::<(), ()>::from_edges(edges);
+    (0..10_000)
+        .map(|seed| rewire_edges(base.clone(), 10, seed))
+        .collect()
+}
+
+fn engine(c: &mut Criterion) {
+    let graphs = small_graphs();
+    let mut group = c.benchmark_group("10k graphs of 20 nodes");
+    group.sample_size(10);
+    group.bench_function("invariant", |b| {
+        b.iter(|| {
+            for graph in &graphs {
+                black_box(wl_isomorphism::invariant(graph.clone()));
+            }
+        })
+    });
+    group.bench_function("WlEngine", |b| {
+        let mut engine = wl_isomorphism::WlEngine::new();
+        b.iter(|| {
+            for graph in &graphs {
+                black_box(engine.compute(graph.clone()));
+            }
+        })
+    });
+    group.finish();
+}
+
+// Sorting the neighbour labels dominates on dense graphs: 100 graphs on 400 nodes with average degree about 32
+fn aggregation(c: &mut Criterion) {
+    let graphs: Vec<UnGraph<(), ()>> = (0..100).map(|seed| gnp(400, 0.08, seed)).collect();
+    let mut group = c.benchmark_group("100 graphs of 400 nodes, average degree 32");
+    group.sample_size(10);
+    group.bench_function("invariant", |b| {
+        b.iter(|| {
+            for graph in &graphs {
+                black_box(wl_isomorphism::invariant(graph.clone()));
+            }
+        })
+    });
+    group.bench_function("invariant_sum_agg", |b| {
+        b.iter(|| {
+            for graph in &graphs {
+                black_box(wl_isomorphism::invariant_sum_agg(graph.clone()));
+            }
+        })
+    });
+    for mode in [
+        AggregationMode::SortedHash,
+        AggregationMode::XorFold,
+        AggregationMode::SumFold,
+    ] {
+        let config = WlConfig::new().aggregation(mode);
+        group.bench_function(format!("{:?}", mode), |b| {
+            b.iter(|| {
+                for graph in &graphs {
+                    black_box(config.invariant(graph.clone()).unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, engine, aggregation);
+criterion_main!(benches);
@@ -0,0 +1,32 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use petgraph::graph::UnGraph;
+use std::hint::black_box;
+
+// A path takes n / 2 iterations to stabilise, so the hash-based refinement does quadratic work
+fn path(n: u32) -> UnGraph<(), ()> {
+    UnGraph::from_edges((1..n).map(|i| (i - 1, i)))
+}
+
+fn partition(c: &mut Criterion) {
+    let short = path(2_000);
+    let mut group = c.benchmark_group("path of 2k nodes");
+    group.sample_size(10);
+    group.bench_function("invariant", |b| {
+        b.iter(|| black_box(wl_isomorphism::invariant(short.clone())))
+    });
+    group.bench_function("invariant_pt", |b| {
+        b.iter(|| black_box(wl_isomorphism::invariant_pt(short.clone())))
+    });
+    group.finish();
+
+    let long = path(1_000_000);
+    let mut group = c.benchmark_group("path of 1M nodes");
+    group.sample_size(10);
+    group.bench_function("invariant_pt", |b| {
+        b.iter(|| black_box(wl_isomorphism::invariant_pt(long.clone())))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, partition);
+criterion_main!(benches);
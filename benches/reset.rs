@@ -0,0 +1,71 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use petgraph::graph::UnGraph;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use wl_isomorphism::GraphWrapper;
+
+// Counts the allocations, to report how many reusing the wrapper saves next to the timings
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+// Hash every graph with a new wrapper
+fn fresh(graphs: &[UnGraph<(), ()>]) -> u64 {
+    graphs.iter().fold(0, |acc, graph| {
+        let mut wrap = GraphWrapper::new(graph.clone(), 42, 0, true, false);
+        wrap.run();
+        acc ^ wrap.digest()
+    })
+}
+
+// Hash every graph with one wrapper, reset for each
+fn reused(graphs: &[UnGraph<(), ()>]) -> u64 {
+    let mut wrap = GraphWrapper::new(graphs[0].clone(), 42, 0, true, false);
+    let mut acc = 0;
+    for graph in graphs {
+        wrap = wrap.reset(graph.clone());
+        wrap.run();
+        acc ^= wrap.digest();
+    }
+    acc
+}
+
+fn reset(c: &mut Criterion) {
+    // 10k small random graphs, as in a stream of molecules
+    let graphs: Vec<_> = (0..10_000)
+        .map(|seed| wl_isomorphism::generators::gnp(30, 0.1, seed))
+        .collect();
+    for (name, stream) in [("fresh", fresh as fn(&[_]) -> u64), ("reset", reused)] {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        black_box(stream(&graphs));
+        println!(
+            "{}: {} allocations for 10k graphs",
+            name,
+            ALLOCATIONS.load(Ordering::Relaxed) - before
+        );
+    }
+
+    let mut group = c.benchmark_group("stream of 10k graphs");
+    group.sample_size(10);
+    group.bench_function("fresh", |b| b.iter(|| fresh(black_box(&graphs))));
+    group.bench_function("reset", |b| b.iter(|| reused(black_box(&graphs))));
+    group.finish();
+}
+
+criterion_group!(benches, reset);
+criterion_main!(benches);
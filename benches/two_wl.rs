@@ -0,0 +1,17 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+use wl_isomorphism::generators::random_regular;
+
+// One iteration of 2-dimensional WL, which dominates its runtime: every one of the n (n + 1) / 2 pairs looks at all n alternatives
+fn two_wl(c: &mut Criterion) {
+    let graph = random_regular(300, 3, 42);
+    let mut group = c.benchmark_group("2-WL on 300 nodes");
+    group.sample_size(10);
+    group.bench_function("one iteration", |b| {
+        b.iter(|| black_box(wl_isomorphism::iter_2wl(graph.clone(), 2)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, two_wl);
+criterion_main!(benches);
@@ -0,0 +1,15 @@
+//! A `#![no_std]` crate that uses `wl_isomorphism` with `default-features = false`. CI builds it for a target without the standard
+//! library, so that the build fails as soon as the CSR functions, or one of their dependencies, start to need `std`.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec;
+use wl_isomorphism::{invariant_from_adj_list, refine_csr, WlOptions};
+
+/// The invariant of the path 0 - 1 - 2, once from adjacency lists and once from CSR arrays. Both are the same hash.
+pub fn path_invariants() -> (u64, u64) {
+    let from_lists = invariant_from_adj_list(vec![vec![1], vec![0, 2], vec![1]]);
+    let (_, from_csr) = refine_csr(&[0, 1, 3, 4], &[1, 0, 2, 1], None, WlOptions::default());
+    (from_lists, from_csr)
+}
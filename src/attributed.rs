@@ -0,0 +1,97 @@
+use crate::csr::Stability;
+use crate::edge_hash::weight_hash;
+use petgraph::graph::IndexType;
+use petgraph::visit::EdgeRef;
+use petgraph::Direction::{Incoming, Outgoing};
+use petgraph::{EdgeType, Graph};
+use std::hash::Hash;
+use twox_hash::XxHash64;
+
+/// Calculate the graph invariant using 1-dimensional WL on both the node and the edge weights. A node starts with the hash of its degree and its weight, and in every iteration it sees each neighbour's label paired with the weight of the edge to it, so that e.g. the atoms of a molecule are told apart by their element as well as by the order of their bonds. Directed graphs keep the incoming and outgoing edges apart, as in [`invariant`](fn.invariant.html). Automatically stabilises.
+///
+/// The weights are hashed with their `Hash` implementation, so floating point weights have to be mapped to something hashable first, such as their bits.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// // Ethanol's C-O bond against a C=O bond, with the same atoms
+/// let single = UnGraph::<&str, u8>::from_edges([(0, 1, 1), (1, 2, 1)]).map(|n, _| ["C", "C", "O"][n.index()], |_, &e| e);
+/// let double = UnGraph::<&str, u8>::from_edges([(0, 1, 1), (1, 2, 2)]).map(|n, _| ["C", "C", "O"][n.index()], |_, &e| e);
+/// assert_ne!(wl_isomorphism::invariant_full_attr(single), wl_isomorphism::invariant_full_attr(double));
+/// ```
+pub fn invariant_full_attr<N: Ord + Hash, E: Hash, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+) -> u64 {
+    let seed = 42;
+    let n = graph.node_count();
+    let directed = graph.is_directed();
+    // For every node the neighbours in each direction, with the hashed weight of the edge to them. For undirected graphs all neighbours
+    // are outgoing
+    let directions: &[_] = if directed {
+        &[Incoming, Outgoing]
+    } else {
+        &[Outgoing]
+    };
+    let neighbours: Vec<Vec<Vec<(usize, u64)>>> = graph
+        .node_indices()
+        .map(|node| {
+            directions
+                .iter()
+                .map(|&direction| {
+                    graph
+                        .edges_directed(node, direction)
+                        .map(|edge| {
+                            let neighbour = match direction {
+                                Incoming => edge.source(),
+                                Outgoing => edge.target(),
+                            };
+                            (neighbour.index(), weight_hash(seed, edge.weight()))
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut labels: Vec<u64> = graph
+        .node_indices()
+        .map(|node| {
+            let mut input = vec![weight_hash(seed, &graph[node])];
+            input.extend(
+                neighbours[node.index()]
+                    .iter()
+                    .map(|edges| edges.len() as u64),
+            );
+            XxHash64::oneshot(seed, bytemuck::cast_slice(&input))
+        })
+        .collect();
+    let mut new_labels = vec![0; n];
+    let mut input_hashes = Vec::new();
+    let mut edge_hashes = Vec::new();
+    let mut stability = Stability::default();
+    // n - 1 iterations are always enough to stabilise, and one more shows that it is
+    for _ in 0..n {
+        for (node, new_label) in new_labels.iter_mut().enumerate() {
+            input_hashes.clear();
+            for edges in &neighbours[node] {
+                edge_hashes.clear();
+                edge_hashes.extend(edges.iter().map(|&(neighbour, weight)| {
+                    XxHash64::oneshot(seed, bytemuck::cast_slice(&[weight, labels[neighbour]]))
+                }));
+                edge_hashes.sort_unstable();
+                input_hashes.push(XxHash64::oneshot(seed, bytemuck::cast_slice(&edge_hashes)));
+            }
+            input_hashes.push(labels[node]); // The node's own label comes last, as in the normal refinement
+            *new_label = XxHash64::oneshot(seed, bytemuck::cast_slice(&input_hashes));
+        }
+        // Unlike in the normal refinement, the new labels are kept even when they split nothing: the edge weights only enter the labels
+        // in the first iteration, so keeping the initial labels would ignore them in a graph whose nodes all differ from the start
+        let stable = stability.check(&labels, &new_labels);
+        std::mem::swap(&mut labels, &mut new_labels);
+        if stable {
+            break;
+        }
+    }
+
+    labels.sort_unstable();
+    XxHash64::oneshot(seed, bytemuck::cast_slice(&labels))
+}
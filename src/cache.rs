@@ -0,0 +1,76 @@
+use crate::invariant;
+use lru::LruCache;
+use petgraph::graph::IndexType;
+use petgraph::{EdgeType, Graph};
+use std::num::NonZeroUsize;
+use twox_hash::XxHash64;
+
+/// A cache of invariants for graphs that are queried repeatedly, evicting the least recently used entry when full.
+///
+/// Entries are keyed by `(node_count, edge_count, edge_list_hash)`, which is cheap to compute (linear in the size of the graph). Note that the key identifies a graph with the same numbering, not an isomorphic one: keying on something weaker, like the degree sequence, would hand out the cached invariant of a different graph that happens to share it.
+pub struct WlCache {
+    cache: LruCache<(usize, usize, u64), u64>,
+    hits: usize,
+    misses: usize,
+}
+
+impl WlCache {
+    /// Create a cache that holds at most `capacity` invariants.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        WlCache {
+            cache: LruCache::new(capacity),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Get the [`invariant`](fn.invariant.html) of the graph, from the cache if the same graph was seen before.
+    pub fn get_or_compute<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+        &mut self,
+        graph: Graph<N, E, Ty, Ix>,
+    ) -> u64 {
+        let key = (
+            graph.node_count(),
+            graph.edge_count(),
+            edge_list_hash(&graph),
+        );
+        if let Some(&hash) = self.cache.get(&key) {
+            self.hits += 1;
+            return hash;
+        }
+        self.misses += 1;
+        let hash = invariant(graph);
+        self.cache.put(key, hash);
+        hash
+    }
+
+    /// The number of graphs currently cached.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// How many queries were answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// How many queries required running WL.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+fn edge_list_hash<N, E, Ty: EdgeType, Ix: IndexType>(graph: &Graph<N, E, Ty, Ix>) -> u64 {
+    let mut endpoints = Vec::with_capacity(2 * graph.edge_count() + 1);
+    endpoints.push(graph.is_directed() as u64);
+    for edge in graph.raw_edges() {
+        endpoints.push(edge.source().index() as u64);
+        endpoints.push(edge.target().index() as u64);
+    }
+    XxHash64::oneshot(42, bytemuck::cast_slice(&endpoints))
+}
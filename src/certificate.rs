@@ -0,0 +1,147 @@
+use crate::context::HASH_VERSION;
+use crate::error::WlError;
+use crate::graphwrapper::GraphWrapper;
+use petgraph::graph::IndexType;
+use petgraph::{EdgeType, Graph};
+use twox_hash::XxHash64;
+
+/// The stable colouring of a graph as the number of nodes of every colour, for storing next to a graph and checking new graphs against it later without refining the stored graph again. Two graphs have matching certificates exactly when 1-WL cannot tell them apart, as with [`invariant`](fn.invariant.html), but unlike a hash the certificate can be inspected, and its [`digest`](struct.Certificate.html#method.digest) can serve as an index key. It carries the version of the labelling scheme, see [`HASH_VERSION`](constant.HASH_VERSION.html), so that certificates from different versions never match.
+///
+/// [`to_bytes`](struct.Certificate.html#method.to_bytes) gives a compact encoding that is the same on every platform, and with the `serde` feature it can be serialised too.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use wl_isomorphism::Certificate;
+///
+/// let stored = wl_isomorphism::certificate(UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]));
+/// let bytes = stored.to_bytes();
+///
+/// let query = wl_isomorphism::certificate(UnGraph::<(), ()>::from_edges([(3, 1), (1, 2), (2, 3), (3, 0)]));
+/// assert!(Certificate::from_bytes(&bytes).unwrap().matches(&query));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Certificate {
+    scheme: u32,
+    colours: Vec<[u64; 2]>,
+}
+
+/// Calculate the [`Certificate`](struct.Certificate.html) of a graph with 1-dimensional WL. Automatically stabilises.
+pub fn certificate<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+) -> Certificate {
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false);
+    wrap.run();
+    Certificate {
+        scheme: HASH_VERSION,
+        colours: wrap.colour_counts(),
+    }
+}
+
+impl Certificate {
+    /// The version of the labelling scheme that computed the certificate.
+    pub fn scheme(&self) -> u32 {
+        self.scheme
+    }
+
+    /// Every colour with the number of nodes that have it, sorted by colour.
+    pub fn colours(&self) -> &[[u64; 2]] {
+        &self.colours
+    }
+
+    /// Whether 1-WL cannot tell the two graphs apart. Certificates from different labelling schemes never match.
+    pub fn matches(&self, other: &Certificate) -> bool {
+        self == other
+    }
+
+    /// A hash of the certificate, equal to [`invariant_counted`](fn.invariant_counted.html) of the graph.
+    pub fn digest(&self) -> u64 {
+        XxHash64::oneshot(42, bytemuck::cast_slice(&self.colours))
+    }
+
+    /// Encode the certificate as bytes: the scheme and the number of colours, then every colour as 8 little-endian bytes followed by its count. The scheme and the counts are written as LEB128 varints, so that the usual small counts take a single byte.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(10 + self.colours.len() * 10);
+        write_varint(&mut bytes, self.scheme as u64);
+        write_varint(&mut bytes, self.colours.len() as u64);
+        for &[colour, count] in &self.colours {
+            bytes.extend_from_slice(&colour.to_le_bytes());
+            write_varint(&mut bytes, count);
+        }
+        bytes
+    }
+
+    /// Decode a certificate written by [`to_bytes`](struct.Certificate.html#method.to_bytes). Fails if the bytes are cut short or run on, or if the colours are not sorted, repeated or have no nodes, which no certificate has.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WlError> {
+        let mut reader = Reader { bytes, offset: 0 };
+        let scheme = reader.varint()?;
+        let scheme = u32::try_from(scheme).map_err(|_| reader.error("scheme out of range"))?;
+        let len = reader.varint()?;
+        // Every colour takes at least 9 bytes, which bounds the allocation for corrupt lengths
+        let mut colours: Vec<[u64; 2]> = Vec::with_capacity((len as usize).min(bytes.len() / 9));
+        for _ in 0..len {
+            let colour = u64::from_le_bytes(reader.take::<8>()?);
+            if colours
+                .last()
+                .is_some_and(|&[previous, _]| previous >= colour)
+            {
+                return Err(reader.error("colours are not sorted"));
+            }
+            let count = reader.varint()?;
+            if count == 0 {
+                return Err(reader.error("colour without nodes"));
+            }
+            colours.push([colour, count]);
+        }
+        if reader.offset != bytes.len() {
+            return Err(reader.error("trailing bytes"));
+        }
+        Ok(Certificate { scheme, colours })
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn error(&self, message: &str) -> WlError {
+        WlError::Decode {
+            offset: self.offset,
+            message: message.to_string(),
+        }
+    }
+
+    fn take<const K: usize>(&mut self) -> Result<[u8; K], WlError> {
+        let taken = self
+            .bytes
+            .get(self.offset..self.offset + K)
+            .ok_or_else(|| self.error("unexpected end"))?;
+        self.offset += K;
+        Ok(taken.try_into().unwrap())
+    }
+
+    fn varint(&mut self) -> Result<u64, WlError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let [byte] = self.take::<1>()?;
+            // The tenth byte holds only the highest bit
+            if shift == 63 && byte > 1 {
+                return Err(self.error("varint out of range"));
+            }
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.error("varint out of range"))
+    }
+}
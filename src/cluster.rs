@@ -0,0 +1,84 @@
+use crate::graphwrapper::GraphWrapper;
+use crate::transform::structure;
+use petgraph::graph::IndexType;
+use petgraph::{EdgeType, Graph};
+use std::collections::HashMap;
+use twox_hash::XxHash64;
+
+/// The partitions of a set of graphs at increasing WL depths, as built by [`wl_hierarchical_cluster`](fn.wl_hierarchical_cluster.html).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dendrogram {
+    /// `levels[k]` groups the indices of the graphs whose labels agree after `k + 1` labellings, i.e. whose [`invariant_iters`](fn.invariant_iters.html) with `n_iters = k + 1` are equal. Every group is sorted, and the groups are ordered by their first graph. Each level refines the one before it, and the last level groups the graphs by their stable invariant.
+    pub levels: Vec<Vec<Vec<usize>>>,
+}
+
+/// Cluster graphs hierarchically by WL: the first level groups them by their degree sequences, and every further level splits the groups by one more iteration, up to the depth at which the last of the graphs stabilises. Graphs that are still together at the last level are possibly isomorphic.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// // A 6-cycle and two triangles have the same degrees, and WL never tells them apart; a path of 6 nodes splits off at once
+/// let cycle = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+/// let triangles = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+/// let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)]);
+/// let dendrogram = wl_isomorphism::wl_hierarchical_cluster(&[cycle, path, triangles]);
+/// assert_eq!(dendrogram.levels[0], vec![vec![0, 2], vec![1]]);
+/// assert_eq!(dendrogram.levels.last().unwrap(), &vec![vec![0, 2], vec![1]]);
+/// ```
+pub fn wl_hierarchical_cluster<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graphs: &[Graph<N, E, Ty, Ix>],
+) -> Dendrogram {
+    let seed = 42;
+    let structures: Vec<Graph<(), (), Ty, Ix>> = graphs.iter().map(structure).collect();
+    // The number of labellings every graph keeps before it is stable
+    let depth = structures
+        .iter()
+        .map(|graph| {
+            let mut wrap = GraphWrapper::new(graph.clone(), seed, 0, true, false);
+            let mut labellings = 0;
+            wrap.run_streaming(|_| labellings += 1);
+            labellings
+        })
+        .max()
+        .unwrap_or(0);
+
+    // The hash of every graph at every depth, from the columns of its subgraph hashes
+    let hashes: Vec<Vec<u64>> = structures
+        .into_iter()
+        .map(|graph| {
+            let n = graph.node_count();
+            let mut wrap = GraphWrapper::new(graph, seed, depth, false, true);
+            wrap.run();
+            let rows = wrap.subgraphs.unwrap();
+            let mut column = Vec::with_capacity(n);
+            (0..depth)
+                .map(|iteration| {
+                    column.clear();
+                    // Graphs with fewer nodes than the depth stop early, so their last labels stand in for the missing ones, as
+                    // with invariant_iters
+                    column.extend(
+                        rows.iter()
+                            .map(|row| *row.get(iteration).unwrap_or(row.last().unwrap())),
+                    );
+                    column.sort_unstable();
+                    XxHash64::oneshot(seed, bytemuck::cast_slice(&column))
+                })
+                .collect()
+        })
+        .collect();
+
+    let levels = (0..depth)
+        .map(|iteration| {
+            let mut groups: Vec<Vec<usize>> = Vec::new();
+            let mut group_of = HashMap::new();
+            for (graph, hashes) in hashes.iter().enumerate() {
+                let group = *group_of.entry(hashes[iteration]).or_insert_with(|| {
+                    groups.push(Vec::new());
+                    groups.len() - 1
+                });
+                groups[group].push(graph);
+            }
+            groups
+        })
+        .collect();
+    Dendrogram { levels }
+}
@@ -0,0 +1,73 @@
+use palette::{Hsv, IntoColor, Srgb};
+use std::collections::HashMap;
+use twox_hash::XxHash64;
+
+/// An sRGB colour with 8 bits per channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb8 {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Rgb8 {
+    /// The colour as a hex string like `#FF0000`, as used in dot files and on the web.
+    pub fn hex(&self) -> String {
+        format!("#{:02X}{:02X}{:02X}", self.red, self.green, self.blue)
+    }
+}
+
+impl From<Srgb<u8>> for Rgb8 {
+    fn from(colour: Srgb<u8>) -> Self {
+        Rgb8 {
+            red: colour.red,
+            green: colour.green,
+            blue: colour.blue,
+        }
+    }
+}
+
+/// Assign every distinct label a colour, with the hues spread as far apart as possible. The labels are ordered by value first, so the same labels always get the same colours. This is the colouring of the dot output (see [`invariant_dot`](fn.invariant_dot.html)), for use in other visualisations.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let graph = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+/// let labels = wl_isomorphism::neighbourhood_stable(graph)
+///     .iter()
+///     .map(|hashes| *hashes.last().unwrap())
+///     .collect::<Vec<_>>();
+/// let colours = wl_isomorphism::colour_assignment(&labels);
+/// assert_eq!(colours.len(), 2);
+/// assert_eq!(colours[&labels[0]], colours[&labels[2]]);
+/// ```
+pub fn colour_assignment(labels: &[u64]) -> HashMap<u64, Rgb8> {
+    let mut unique = labels.to_vec();
+    unique.sort_unstable();
+    unique.dedup();
+    let colours = generate_contrasting_colors(unique.len());
+    unique.into_iter().zip(colours).collect()
+}
+
+/// A colour for a colour class that only depends on its label, so the same class gets the same colour in every graph and run, unlike [`colour_assignment`](fn.colour_assignment.html) which depends on the other labels. Different labels get different colours with high probability, but the colours are not as far apart.
+pub fn stable_colour_for_class(label: u64) -> Rgb8 {
+    let bits = XxHash64::oneshot(0, &label.to_le_bytes());
+    let part = |shift: u32| (bits >> shift & 0xFFFF) as f32 / 65535.0;
+    // Keep the colours bright and saturated enough to tell apart
+    let hsv = Hsv::new(
+        360.0 * part(0),
+        0.55 + 0.45 * part(16),
+        0.75 + 0.25 * part(32),
+    );
+    let srgb: Srgb = hsv.into_color();
+    srgb.into_format().into()
+}
+
+// Get colours that are as opposing as possible
+pub(crate) fn generate_contrasting_colors(n: usize) -> impl Iterator<Item = Rgb8> {
+    (0..n).map(move |i| {
+        let contrast = (360.0 / n as f32) * i as f32; // Spread hues (for colours) and lightness (for black and white) evenly lightness doesn't do what was hoped :(
+        let hsv = Hsv::new(contrast, 1.0, 1.0); // Full saturation
+        let srgb: Srgb = hsv.into_color();
+        srgb.into_format().into() // Convert to u8 format
+    })
+}
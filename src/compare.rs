@@ -0,0 +1,308 @@
+use crate::error::WlError;
+use crate::graphwrapper::{pair_count, GraphWrapper};
+use crate::possibly_isomorphic_early_exit;
+use crate::transform::{disjoint_union, structure};
+use petgraph::algo::is_isomorphic;
+use petgraph::graph::IndexType;
+use petgraph::{EdgeType, Graph, Undirected};
+use std::collections::HashMap;
+
+/// The policy of [`compare`](fn.compare.html): which checks to run after 1-dimensional WL fails to tell two graphs apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompareOptions {
+    /// Whether to try 2-dimensional WL next. This is only done for undirected graphs, and is much slower than 1-dimensional WL.
+    pub escalate_2wl: bool,
+    /// Run an exact isomorphism test (VF2) on graphs with at most this many nodes, so 0 disables it for all non-empty graphs.
+    pub exact_max_nodes: usize,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        CompareOptions {
+            escalate_2wl: true,
+            exact_max_nodes: 64,
+        }
+    }
+}
+
+/// The test that decided the outcome of a [`compare`](fn.compare.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decider {
+    OneWl,
+    TwoWl,
+    Exact,
+}
+
+/// The outcome of [`compare`](fn.compare.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// The exact test found an isomorphism.
+    Equal,
+    /// WL could not tell the graphs apart, but the exact test shows that they are not isomorphic.
+    WlEqualButNotIsomorphic,
+    /// WL proved that the graphs are not isomorphic.
+    NotIsomorphic { by: Decider },
+    /// WL could not tell the graphs apart and the exact test was not run, so the graphs are possibly isomorphic. `by` is the last test that was run.
+    PossiblyIsomorphic { by: Decider },
+}
+
+/// Compare two graphs for isomorphism, escalating from 1-dimensional WL to 2-dimensional WL and finally an exact test, as configured by `opts`. Every step only runs if the previous ones could not tell the graphs apart, and the result records which step decided.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use wl_isomorphism::{compare, CompareOptions, Comparison, Decider};
+///
+/// let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+/// let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+/// let outcome = compare(path, star, CompareOptions::default());
+/// assert_eq!(outcome, Comparison::NotIsomorphic { by: Decider::OneWl });
+/// ```
+pub fn compare<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    g1: Graph<N, E, Ty, Ix>,
+    g2: Graph<N, E, Ty, Ix>,
+    opts: CompareOptions,
+) -> Comparison {
+    // The later steps need the structure again, but not the weights
+    let s1 = structure(&g1);
+    let s2 = structure(&g2);
+    if !possibly_isomorphic_early_exit(g1, g2) {
+        return Comparison::NotIsomorphic { by: Decider::OneWl };
+    }
+    let mut last = Decider::OneWl;
+    if opts.escalate_2wl && !s1.is_directed() {
+        if invariant_2wl(s1.clone()) != invariant_2wl(s2.clone()) {
+            return Comparison::NotIsomorphic { by: Decider::TwoWl };
+        }
+        last = Decider::TwoWl;
+    }
+    if s1.node_count() > opts.exact_max_nodes {
+        return Comparison::PossiblyIsomorphic { by: last };
+    }
+    if is_isomorphic(&s1, &s2) {
+        Comparison::Equal
+    } else {
+        Comparison::WlEqualButNotIsomorphic
+    }
+}
+
+/// Find the smallest WL dimension, up to `max_k`, that tells two graphs apart: `Some(1)` if 1-dimensional WL does, `Some(2)` if only 2-dimensional WL does, and `None` if neither does. Every dimension only runs if the lower ones could not tell the graphs apart. 3-dimensional WL is not implemented, so a `max_k` above 2 behaves like 2.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let two_triangles = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+/// let hexagon = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+/// let k = wl_isomorphism::distinguishing_dimension(two_triangles, hexagon, 2).unwrap();
+/// assert_eq!(k, Some(2));
+/// ```
+///
+/// Returns [`WlError::TooLarge`](enum.WlError.html#variant.TooLarge) instead of running 2-dimensional WL if its labels (one per pair of nodes) cannot be allocated.
+pub fn distinguishing_dimension<N: Ord, E, Ix: IndexType>(
+    g1: Graph<N, E, Undirected, Ix>,
+    g2: Graph<N, E, Undirected, Ix>,
+    max_k: usize,
+) -> Result<Option<usize>, WlError> {
+    if max_k == 0 {
+        return Ok(None);
+    }
+    let s1 = structure(&g1);
+    let s2 = structure(&g2);
+    if !possibly_isomorphic_early_exit(g1, g2) {
+        return Ok(Some(1));
+    }
+    if max_k == 1 {
+        return Ok(None);
+    }
+    // Both graphs have the same number of nodes, as 1-dimensional WL did not tell them apart
+    let nodes = s1.node_count();
+    let feasible = pair_count(nodes).is_some_and(|pairs| {
+        // The current and the next labels of both graphs
+        Vec::<u64>::new()
+            .try_reserve_exact(pairs.saturating_mul(4))
+            .is_ok()
+    });
+    if !feasible {
+        return Err(WlError::TooLarge {
+            nodes,
+            dimension: 2,
+        });
+    }
+    if invariant_2wl(s1) != invariant_2wl(s2) {
+        return Ok(Some(2));
+    }
+    Ok(None)
+}
+
+/// The colour classes that two graphs have in common: a map from every final WL label that occurs in both graphs to how often it occurs in the first and in the second graph. Labels that occur in only one graph are left out. The labels are those of WL on the disjoint union of the two graphs, so that equal labels mean the same class in both, and two nodes only share a class if WL cannot tell them apart at all.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// // A triangle with a tail, and a triangle with a longer tail
+/// let short = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+/// let long = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3), (3, 4)]);
+/// let shared = wl_isomorphism::wl_color_intersection(short.clone(), short.clone());
+/// assert_eq!(shared.len(), 3);
+/// assert!(wl_isomorphism::wl_color_intersection(short, long).len() < 3);
+/// ```
+pub fn wl_color_intersection<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    g1: Graph<N, E, Ty, Ix>,
+    g2: Graph<N, E, Ty, Ix>,
+) -> HashMap<u64, (usize, usize)> {
+    let offset = g1.node_count();
+    let mut wrap = GraphWrapper::new(disjoint_union(&g1, &g2), 42, 0, true, false);
+    wrap.run();
+    // Per colour: (number of nodes in g1, number of nodes in g2)
+    let mut counts: HashMap<u64, (usize, usize)> = HashMap::new();
+    for (index, &label) in wrap.labels().iter().enumerate() {
+        let entry = counts.entry(label).or_default();
+        if index < offset {
+            entry.0 += 1;
+        } else {
+            entry.1 += 1;
+        }
+    }
+    counts.retain(|_, &mut (left, right)| left > 0 && right > 0);
+    counts
+}
+
+/// Find the graphs of `corpus` that are possibly isomorphic to `query`: the indices, in increasing order, of the graphs with the same [`invariant`](fn.invariant.html). Only the structure of the graphs is hashed, so the weights need not be cloned. With the `rayon` feature enabled, the corpus is hashed in parallel.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let corpus = vec![
+///     UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0)]),
+///     UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]),
+///     UnGraph::<(), ()>::from_edges([(2, 1), (1, 0), (0, 2)]),
+/// ];
+/// let triangle = UnGraph::<(), ()>::from_edges([(1, 2), (2, 0), (0, 1)]);
+/// assert_eq!(wl_isomorphism::search_corpus(triangle, &corpus), vec![0, 2]);
+/// ```
+pub fn search_corpus<N, E, Ty, Ix>(
+    query: Graph<N, E, Ty, Ix>,
+    corpus: &[Graph<N, E, Ty, Ix>],
+) -> Vec<usize>
+where
+    N: Ord + Sync,
+    E: Sync,
+    Ty: EdgeType + Sync,
+    Ix: IndexType + Sync,
+{
+    let target = structure_invariant(&query);
+    matching(corpus, |graph| structure_invariant(graph) == target)
+}
+
+/// Like [`search_corpus`](fn.search_corpus.html), but matching on the [`invariant_2wl`](fn.invariant_2wl.html) of undirected graphs, which tells apart more graphs, such as regular graphs of the same degree. As 2-dimensional WL only refines the 1-dimensional result, only the graphs with the same size and 1-dimensional invariant as the query are hashed with it.
+pub fn search_corpus_2wl<N, E, Ix>(
+    query: Graph<N, E, Undirected, Ix>,
+    corpus: &[Graph<N, E, Undirected, Ix>],
+) -> Vec<usize>
+where
+    N: Ord + Sync,
+    E: Sync,
+    Ix: IndexType + Sync,
+{
+    let size = (query.node_count(), query.edge_count());
+    let target = structure_invariant(&query);
+    let candidates = matching(corpus, |graph| {
+        (graph.node_count(), graph.edge_count()) == size && structure_invariant(graph) == target
+    });
+    if candidates.is_empty() {
+        return candidates;
+    }
+    let target = invariant_2wl(structure(&query));
+    let candidate_graphs: Vec<_> = candidates.iter().map(|&index| &corpus[index]).collect();
+    matching(&candidate_graphs, |graph| {
+        invariant_2wl(structure(graph)) == target
+    })
+    .into_iter()
+    .map(|index| candidates[index])
+    .collect()
+}
+
+/// How much work [`invariant_combined`](fn.invariant_combined.html) may do for one graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WlBudget {
+    /// Compute the 2-dimensional WL invariant only for graphs with at most this many nodes, as it refines a label for every pair of nodes. 0 disables it for all non-empty graphs.
+    pub max_nodes_2wl: usize,
+}
+
+impl Default for WlBudget {
+    fn default() -> Self {
+        WlBudget { max_nodes_2wl: 256 }
+    }
+}
+
+/// The strongest invariant of a graph within a [`WlBudget`](struct.WlBudget.html), from [`invariant_combined`](fn.invariant_combined.html). Equality and hashing compare both components, so that combined invariants can be `HashMap` keys: a graph whose 2-dimensional invariant was skipped never equals one for which it was computed. To compare combined invariants from different budgets, use [`compatible`](struct.CombinedInvariant.html#method.compatible).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CombinedInvariant {
+    /// The [`invariant`](fn.invariant.html) of the graph.
+    pub one_wl: u64,
+    /// The [`invariant_2wl`](fn.invariant_2wl.html) of the graph, if it fitted in the budget.
+    pub two_wl: Option<u64>,
+}
+
+impl CombinedInvariant {
+    /// Whether the graphs agree on every component that both invariants computed, so that they are possibly isomorphic. A graph whose 2-dimensional invariant was skipped is compatible with every graph with the same 1-dimensional invariant. Unlike equality this is not transitive, so it cannot be used to group graphs.
+    pub fn compatible(&self, other: &Self) -> bool {
+        self.one_wl == other.one_wl
+            && match (self.two_wl, other.two_wl) {
+                (Some(left), Some(right)) => left == right,
+                _ => true,
+            }
+    }
+}
+
+/// Calculate the 1-dimensional and, if the graph fits in `budget`, the 2-dimensional invariant of an undirected graph, as one value that can serve as a `HashMap` key. Only the structure of the graph is hashed.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use wl_isomorphism::WlBudget;
+///
+/// let two_triangles = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+/// let hexagon = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+/// assert_ne!(
+///     wl_isomorphism::invariant_combined(two_triangles, WlBudget::default()),
+///     wl_isomorphism::invariant_combined(hexagon, WlBudget::default())
+/// );
+/// ```
+pub fn invariant_combined<N, E, Ix: IndexType>(
+    graph: Graph<N, E, Undirected, Ix>,
+    budget: WlBudget,
+) -> CombinedInvariant {
+    let one_wl = structure_invariant(&graph);
+    let two_wl =
+        (graph.node_count() <= budget.max_nodes_2wl).then(|| invariant_2wl(structure(&graph)));
+    CombinedInvariant { one_wl, two_wl }
+}
+
+// The indices of the graphs for which `is_match` holds, in increasing order, checked in parallel with the `rayon` feature
+fn matching<G: Sync>(corpus: &[G], is_match: impl Fn(&G) -> bool + Sync) -> Vec<usize> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        corpus
+            .par_iter()
+            .enumerate()
+            .filter(|(_, graph)| is_match(graph))
+            .map(|(index, _)| index)
+            .collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    corpus
+        .iter()
+        .enumerate()
+        .filter(|(_, graph)| is_match(graph))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+// The invariant of the structure of a graph
+fn structure_invariant<N, E, Ty: EdgeType, Ix: IndexType>(graph: &Graph<N, E, Ty, Ix>) -> u64 {
+    let mut wrap = GraphWrapper::new(structure(graph), 42, 0, true, false);
+    wrap.run();
+    wrap.get_results()
+}
+
+// Like the public invariant_2wl, but for any edge type. Only called on undirected graphs.
+fn invariant_2wl<Ty: EdgeType, Ix: IndexType>(graph: Graph<(), (), Ty, Ix>) -> u64 {
+    let mut wrap = GraphWrapper::new_2wl(graph, 42, 0, true, false);
+    wrap.run();
+    wrap.get_results()
+}
@@ -0,0 +1,43 @@
+use crate::attributed::invariant_full_attr;
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::{IndexType, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::{Directed, Graph};
+use std::collections::HashSet;
+
+/// Calculate the invariant of the condensation of a directed graph: the DAG with a node for every strongly connected component, weighted with its number of nodes, and an edge between two components if any edge of the graph goes from one to the other. Within a component every node can reach every other, so plain WL sees little difference between, e.g., a long cycle and several short ones, while their condensations differ. The condensation forgets the structure within the components, so use this next to [`invariant`](fn.invariant.html) rather than instead of it. The components are found with Tarjan's algorithm, and the DAG is hashed with [`invariant_full_attr`](fn.invariant_full_attr.html).
+/// ```rust
+/// use petgraph::graph::DiGraph;
+///
+/// // Every node of a directed 6-cycle and of two directed triangles has one edge in and one out
+/// let cycle = DiGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+/// let triangles = DiGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+/// assert_eq!(wl_isomorphism::invariant(cycle.clone()), wl_isomorphism::invariant(triangles.clone()));
+/// assert_ne!(
+///     wl_isomorphism::invariant_condensation(cycle),
+///     wl_isomorphism::invariant_condensation(triangles)
+/// );
+/// ```
+pub fn invariant_condensation<N, E, Ix: IndexType>(graph: Graph<N, E, Directed, Ix>) -> u64 {
+    let components = tarjan_scc(&graph);
+    let mut component = vec![0; graph.node_count()];
+    let mut condensation = Graph::<u64, (), Directed>::with_capacity(components.len(), 0);
+    for (index, nodes) in components.iter().enumerate() {
+        for node in nodes {
+            component[node.index()] = index;
+        }
+        condensation.add_node(nodes.len() as u64);
+    }
+    // Edges within a component and repeated edges between two components are left out, so the condensation is a simple DAG
+    let mut present = HashSet::new();
+    for edge in graph.edge_references() {
+        let (source, target) = (
+            component[edge.source().index()],
+            component[edge.target().index()],
+        );
+        if source != target && present.insert((source, target)) {
+            condensation.add_edge(NodeIndex::new(source), NodeIndex::new(target), ());
+        }
+    }
+    invariant_full_attr(condensation)
+}
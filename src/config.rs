@@ -0,0 +1,338 @@
+use crate::error::WlError;
+use crate::graphwrapper::GraphWrapper;
+use crate::transform::structure;
+use petgraph::graph::IndexType;
+use petgraph::{EdgeType, Graph, Undirected};
+use std::collections::HashSet;
+use std::fmt;
+
+/// How [`WlConfig`](struct.WlConfig.html) treats multiple edges between the same two nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MultigraphPolicy {
+    /// Every edge counts, so a node with a doubled edge to one neighbour has degree 2 and sees that neighbour twice, like a NetworkX `MultiGraph`. This is what [`invariant`](fn.invariant.html) does.
+    #[default]
+    CountEdges,
+    /// Parallel edges count once, so the degree is the number of distinct neighbours, like a NetworkX `Graph`.
+    CountDistinctNeighbours,
+    /// Parallel edges are an error ([`WlError::ParallelEdges`](enum.WlError.html#variant.ParallelEdges)).
+    Reject,
+}
+
+/// How [`WlConfig`](struct.WlConfig.html) treats edges from a node to itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelfLoopPolicy {
+    /// Self-loops are left out, as if the graph were simple.
+    Ignore,
+    /// A self-loop makes the node its own neighbour once, adding 1 to its degree. This is what [`invariant`](fn.invariant.html) does.
+    #[default]
+    CountOnce,
+    /// A self-loop makes the node its own neighbour twice, adding 2 to its degree, as in the usual convention for undirected graphs. In directed graphs a loop is already counted once as an incoming and once as an outgoing edge, and this counts both twice.
+    CountTwice,
+    /// Self-loops are an error ([`WlError::SelfLoop`](enum.WlError.html#variant.SelfLoop)).
+    Reject,
+}
+
+/// How [`WlConfig`](struct.WlConfig.html) combines the labels of the neighbours of a node in every iteration of 1-dimensional WL. Every mode gives the same hash to isomorphic graphs, as none depends on the order of the neighbours, but they differ in speed and in what they can tell apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AggregationMode {
+    /// Sort the labels and hash them all, which tells apart every two different multisets of labels. This is what [`invariant`](fn.invariant.html) does.
+    #[default]
+    SortedHash,
+    /// Scramble the labels and XOR them into one value, which saves the sort on dense graphs. Two neighbours with the same label cancel out, so a node with two neighbours of one colour looks like a node with none of them; the degree in the initial label and the node's own label limit the harm, but this is weaker than the other modes.
+    XorFold,
+    /// Scramble the labels and add them up, wrapping around, which saves the sort on dense graphs, as in [`invariant_sum_agg`](fn.invariant_sum_agg.html). Different multisets only get the same sum by a hash collision.
+    SumFold,
+}
+
+/// How [`WlConfig`](struct.WlConfig.html) treats the direction of the edges of directed graphs in 1-dimensional WL. Undirected graphs are not affected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DirectedMode {
+    /// Every node combines the labels of its in-neighbours and of its out-neighbours separately. This is what [`invariant`](fn.invariant.html) does.
+    #[default]
+    InOutSeparate,
+    /// As `InOutSeparate`, and additionally every node combines the labels of all its neighbours together. The combined multiset follows from the two separate ones, so this tells apart the same graphs, but it makes explicit that the result is never weaker than WL on the undirected skeleton. The hashes differ from those of `InOutSeparate`.
+    InOutPlusUndirected,
+    /// The graph is treated as its undirected skeleton, so the hash equals that of the graph with every edge made undirected.
+    IgnoreDirection,
+}
+
+/// The number of bits of the hash from [`invariant_width`](fn.invariant_width.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HashWidth {
+    /// The lower 32 bits of [`invariant`](fn.invariant.html), to save memory when collisions matter less.
+    W32,
+    /// The same hash as [`invariant`](fn.invariant.html).
+    #[default]
+    W64,
+    /// Two independent 64-bit hashes, for fewer collisions in very large collections. The lower 64 bits are the hash of [`invariant`](fn.invariant.html), and the upper 64 bits come from a second run with another seed, which takes twice as long.
+    W128,
+}
+
+/// Bounds on the work [`WlConfig`](struct.WlConfig.html) does for one graph, for services that hash graphs from untrusted sources. The sizes are checked before any refinement, and the number of computed labels during it, so that neither a huge graph nor one that needs many iterations can take up unbounded CPU time. `None` means no limit, which is the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WlLimits {
+    /// The largest number of nodes to accept.
+    pub max_nodes: Option<usize>,
+    /// The largest number of edges to accept, counting parallel edges.
+    pub max_edges: Option<usize>,
+    /// The largest number of labels to compute over the whole run, counting the initial labels. An iteration of 1-dimensional WL computes one label per node, one of 2-dimensional WL one per pair of nodes. A run stops before the iteration that would go over.
+    pub max_total_label_hashes: Option<usize>,
+}
+
+/// Which of the [`WlLimits`](struct.WlLimits.html) a graph exceeded, as reported in [`WlError::LimitExceeded`](enum.WlError.html#variant.LimitExceeded).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitKind {
+    /// `max_nodes`
+    Nodes,
+    /// `max_edges`
+    Edges,
+    /// `max_total_label_hashes`
+    LabelHashes,
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LimitKind::Nodes => "node",
+            LimitKind::Edges => "edge",
+            LimitKind::LabelHashes => "label hash",
+        })
+    }
+}
+
+/// Settings for computing invariants, built up with chained calls. The default matches [`invariant`](fn.invariant.html): seed 42, every edge counted, self-loops counted once, the neighbour labels sorted, in- and out-neighbours kept apart and no limits.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use wl_isomorphism::{MultigraphPolicy, WlConfig};
+///
+/// let doubled = UnGraph::<(), ()>::from_edges([(0, 1), (0, 1)]);
+/// let single = UnGraph::<(), ()>::from_edges([(0, 1)]);
+/// let config = WlConfig::new().multigraph_policy(MultigraphPolicy::CountDistinctNeighbours);
+/// assert_eq!(
+///     config.invariant(doubled).unwrap(),
+///     wl_isomorphism::invariant(single)
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WlConfig {
+    seed: u64,
+    multigraph_policy: MultigraphPolicy,
+    self_loop_policy: SelfLoopPolicy,
+    aggregation: AggregationMode,
+    directed_mode: DirectedMode,
+    limits: WlLimits,
+}
+
+impl Default for WlConfig {
+    fn default() -> Self {
+        WlConfig {
+            seed: 42,
+            multigraph_policy: MultigraphPolicy::default(),
+            self_loop_policy: SelfLoopPolicy::default(),
+            aggregation: AggregationMode::default(),
+            directed_mode: DirectedMode::default(),
+            limits: WlLimits::default(),
+        }
+    }
+}
+
+impl WlConfig {
+    /// The default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `seed` for the hash function.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Choose how parallel edges are treated.
+    pub fn multigraph_policy(mut self, policy: MultigraphPolicy) -> Self {
+        self.multigraph_policy = policy;
+        self
+    }
+
+    /// Choose how self-loops are treated.
+    pub fn self_loop_policy(mut self, policy: SelfLoopPolicy) -> Self {
+        self.self_loop_policy = policy;
+        self
+    }
+
+    /// Choose how the neighbour labels are combined. This only affects 1-dimensional WL.
+    pub fn aggregation(mut self, aggregation: AggregationMode) -> Self {
+        self.aggregation = aggregation;
+        self
+    }
+
+    /// Choose how the direction of the edges is treated. This only affects 1-dimensional WL on directed graphs.
+    pub fn directed_mode(mut self, mode: DirectedMode) -> Self {
+        self.directed_mode = mode;
+        self
+    }
+
+    /// Bound the work done for a graph, see [`WlLimits`](struct.WlLimits.html).
+    pub fn limits(mut self, limits: WlLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Calculate the graph invariant using 1-dimensional WL with these settings. Automatically stabilises.
+    ///
+    /// Fails if a policy is `Reject` and the graph has parallel edges or self-loops, or if the graph exceeds the limits.
+    pub fn invariant<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+        &self,
+        graph: Graph<N, E, Ty, Ix>,
+    ) -> Result<u64, WlError> {
+        if graph.is_directed() && self.directed_mode == DirectedMode::IgnoreDirection {
+            // The policies apply to the skeleton, where edges in opposite directions become parallel
+            let skeleton: Graph<(), (), Undirected, Ix> = structure(&graph).into_edge_type();
+            return self.invariant(skeleton);
+        }
+        self.check(&graph)?;
+        match self.multigraph_policy {
+            MultigraphPolicy::CountDistinctNeighbours => self.run(without_parallel_edges(&graph)),
+            _ => self.run(graph),
+        }
+    }
+
+    /// Like [`invariant`](struct.WlConfig.html#method.invariant), but using 2-dimensional WL, as in [`invariant_2wl`](fn.invariant_2wl.html). Parallel edges count in the initial label of a pair of nodes, and self-loops in that of a node paired with itself.
+    pub fn invariant_2wl<N: Ord, E, Ix: IndexType>(
+        &self,
+        graph: Graph<N, E, Undirected, Ix>,
+    ) -> Result<u64, WlError> {
+        self.check(&graph)?;
+        match self.multigraph_policy {
+            MultigraphPolicy::CountDistinctNeighbours => {
+                self.run_2wl(without_parallel_edges(&graph))
+            }
+            _ => self.run_2wl(graph),
+        }
+    }
+
+    fn check<N, E, Ty: EdgeType, Ix: IndexType>(
+        &self,
+        graph: &Graph<N, E, Ty, Ix>,
+    ) -> Result<(), WlError> {
+        // The sizes come first, as they are the cheapest to check
+        for (which, limit, observed) in [
+            (LimitKind::Nodes, self.limits.max_nodes, graph.node_count()),
+            (LimitKind::Edges, self.limits.max_edges, graph.edge_count()),
+        ] {
+            if let Some(limit) = limit.filter(|&limit| observed > limit) {
+                return Err(WlError::LimitExceeded {
+                    which,
+                    limit,
+                    observed,
+                    iterations: 0,
+                });
+            }
+        }
+        if self.self_loop_policy == SelfLoopPolicy::Reject {
+            if let Some(edge) = graph.raw_edges().iter().find(|e| e.source() == e.target()) {
+                return Err(WlError::SelfLoop {
+                    node: edge.source().index(),
+                });
+            }
+        }
+        if self.multigraph_policy == MultigraphPolicy::Reject {
+            if let Some((source, target)) = parallel_edge(graph) {
+                return Err(WlError::ParallelEdges { source, target });
+            }
+        }
+        Ok(())
+    }
+
+    fn self_loop_multiplicity(&self) -> usize {
+        match self.self_loop_policy {
+            SelfLoopPolicy::Ignore => 0,
+            SelfLoopPolicy::CountOnce | SelfLoopPolicy::Reject => 1,
+            SelfLoopPolicy::CountTwice => 2,
+        }
+    }
+
+    fn run<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+        &self,
+        graph: Graph<N, E, Ty, Ix>,
+    ) -> Result<u64, WlError> {
+        let mut wrap = GraphWrapper::new(graph, self.seed, 0, true, false)
+            .with_self_loop_multiplicity(self.self_loop_multiplicity())
+            .with_aggregation(self.aggregation);
+        if self.directed_mode == DirectedMode::InOutPlusUndirected {
+            wrap = wrap.with_undirected_context();
+        }
+        if let Some(limit) = self.limits.max_total_label_hashes {
+            wrap = wrap.with_label_budget(limit);
+        }
+        wrap.run();
+        self.check_budget(wrap.budget_exceeded())?;
+        Ok(wrap.get_results())
+    }
+
+    fn run_2wl<N: Ord, E, Ix: IndexType>(
+        &self,
+        graph: Graph<N, E, Undirected, Ix>,
+    ) -> Result<u64, WlError> {
+        let mut wrap = GraphWrapper::new_2wl(graph, self.seed, 0, true, false)
+            .with_self_loop_multiplicity(self.self_loop_multiplicity());
+        if let Some(limit) = self.limits.max_total_label_hashes {
+            wrap = wrap.with_label_budget(limit);
+        }
+        wrap.run();
+        self.check_budget(wrap.budget_exceeded())?;
+        Ok(wrap.get_results())
+    }
+
+    // Turn a run that ran out of its label budget into an error
+    fn check_budget(&self, exceeded: Option<(usize, usize)>) -> Result<(), WlError> {
+        match (exceeded, self.limits.max_total_label_hashes) {
+            (Some((observed, iterations)), Some(limit)) => Err(WlError::LimitExceeded {
+                which: LimitKind::LabelHashes,
+                limit,
+                observed,
+                iterations,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+// The endpoints of an edge, in the order that identifies it: undirected edges have no order, so the smaller index comes first
+fn endpoints<N, E, Ty: EdgeType, Ix: IndexType>(
+    graph: &Graph<N, E, Ty, Ix>,
+    edge: &petgraph::graph::Edge<E, Ix>,
+) -> (usize, usize) {
+    let (source, target) = (edge.source().index(), edge.target().index());
+    if graph.is_directed() || source <= target {
+        (source, target)
+    } else {
+        (target, source)
+    }
+}
+
+// The first edge that repeats an earlier one, if any
+fn parallel_edge<N, E, Ty: EdgeType, Ix: IndexType>(
+    graph: &Graph<N, E, Ty, Ix>,
+) -> Option<(usize, usize)> {
+    let mut seen = HashSet::with_capacity(graph.edge_count());
+    graph
+        .raw_edges()
+        .iter()
+        .map(|edge| endpoints(graph, edge))
+        .find(|&pair| !seen.insert(pair))
+}
+
+// A copy of the structure with only the first of every group of parallel edges
+fn without_parallel_edges<N, E, Ty: EdgeType, Ix: IndexType>(
+    graph: &Graph<N, E, Ty, Ix>,
+) -> Graph<(), (), Ty, Ix> {
+    let mut seen = HashSet::with_capacity(graph.edge_count());
+    graph.filter_map(
+        |_, _| Some(()),
+        |index, _| {
+            let edge = &graph.raw_edges()[index.index()];
+            seen.insert(endpoints(graph, edge)).then_some(())
+        },
+    )
+}
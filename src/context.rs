@@ -0,0 +1,123 @@
+use crate::error::WlError;
+use crate::features::colour_histogram;
+use crate::graphwrapper::GraphWrapper;
+use petgraph::graph::IndexType;
+use petgraph::{EdgeType, Graph};
+use std::collections::HashMap;
+
+/// The version of the labelling scheme. It changes whenever the labels computed for the same graph and seed change, so that stored colours can be recognised as stale. Version 2 gave the pairs of a node with itself their own initial label in 2-dimensional WL, which changed every 2-dimensional label and invariant.
+pub const HASH_VERSION: u32 = 2;
+
+/// A colour (label) that WL assigned to a node. Colours are only comparable between graphs if they were computed in the same [`WlContext`](struct.WlContext.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ColourId(pub u64);
+
+/// The setting in which colours are computed: the hash seed and the version of the labelling scheme. Colours and histograms computed in one context can be used as dictionary keys across graphs, but combining them with those from another context is an error.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use wl_isomorphism::WlContext;
+///
+/// let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+/// let context = WlContext::new(42);
+/// let histogram = context.wl_histogram(&g, 3);
+/// assert!(context.wl_kernel(&histogram, &histogram).unwrap() > 0.0);
+/// let other = WlContext::new(7).wl_histogram(&g, 3);
+/// assert!(context.wl_kernel(&histogram, &other).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WlContext {
+    seed: u64,
+    hash_version: u32,
+}
+
+/// The colour counts of one graph over several iterations, as used by the WL subtree kernel. It remembers the context it was computed in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WlHistogram {
+    context: WlContext,
+    counts: HashMap<ColourId, usize>,
+}
+
+impl WlHistogram {
+    /// The context the histogram was computed in.
+    pub fn context(&self) -> WlContext {
+        self.context
+    }
+
+    /// How often `colour` occurs.
+    pub fn count(&self, colour: ColourId) -> usize {
+        self.counts.get(&colour).copied().unwrap_or(0)
+    }
+
+    /// The colours that occur, with their counts, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (ColourId, usize)> + '_ {
+        self.counts.iter().map(|(&colour, &count)| (colour, count))
+    }
+}
+
+impl WlContext {
+    /// A context with the given hash seed and the current [`HASH_VERSION`](constant.HASH_VERSION.html).
+    pub fn new(seed: u64) -> Self {
+        WlContext {
+            seed,
+            hash_version: HASH_VERSION,
+        }
+    }
+
+    /// The hash seed.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The version of the labelling scheme.
+    pub fn hash_version(&self) -> u32 {
+        self.hash_version
+    }
+
+    /// The colours of every node per iteration, like [`neighbourhood_hash`](fn.neighbourhood_hash.html) but with this context's seed.
+    pub fn neighbourhood_colours<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+        &self,
+        graph: Graph<N, E, Ty, Ix>,
+        n_iters: usize,
+    ) -> Vec<Vec<ColourId>> {
+        let mut wrap = GraphWrapper::new(graph, self.seed, n_iters, false, true);
+        wrap.run();
+        wrap.subgraphs
+            .unwrap()
+            .into_iter()
+            .map(|row| row.into_iter().map(ColourId).collect())
+            .collect()
+    }
+
+    /// Count the colours of all nodes over the first `n_iters` iterations.
+    pub fn wl_histogram<N, E, Ty: EdgeType, Ix: IndexType>(
+        &self,
+        graph: &Graph<N, E, Ty, Ix>,
+        n_iters: usize,
+    ) -> WlHistogram {
+        WlHistogram {
+            context: *self,
+            counts: colour_histogram(graph, n_iters, self.seed)
+                .into_iter()
+                .map(|(label, count)| (ColourId(label), count))
+                .collect(),
+        }
+    }
+
+    /// The WL subtree kernel of two graphs: the inner product of their histograms. Both histograms must come from this context, otherwise their colours cannot be compared and a [`WlError::ContextMismatch`](enum.WlError.html) is returned.
+    pub fn wl_kernel(&self, left: &WlHistogram, right: &WlHistogram) -> Result<f64, WlError> {
+        for histogram in [left, right] {
+            if histogram.context != *self {
+                return Err(WlError::ContextMismatch {
+                    left: *self,
+                    right: histogram.context,
+                });
+            }
+        }
+        let kernel: usize = left
+            .counts
+            .iter()
+            .map(|(colour, &count)| count * right.count(*colour))
+            .sum();
+        Ok(kernel as f64)
+    }
+}
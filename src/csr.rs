@@ -0,0 +1,751 @@
+#[cfg(feature = "std")]
+use crate::config::AggregationMode;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use petgraph::graph::IndexType;
+#[cfg(feature = "std")]
+use petgraph::visit::{GraphProp, IntoNeighborsDirected, IntoNodeIdentifiers, NodeIndexable};
+#[cfg(feature = "std")]
+use petgraph::Direction::{Incoming, Outgoing};
+use twox_hash::XxHash64;
+
+/// Options for [`refine_csr`](fn.refine_csr.html) and [`refine_csr_directed`](fn.refine_csr_directed.html). The default matches [`invariant`](fn.invariant.html): seed 42, running until the colouring is stable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WlOptions {
+    /// The seed of the hash function.
+    pub seed: u64,
+    /// The number of iterations to run if `check_stable` is false. 0 means `n - 1`, which is always enough to stabilise.
+    pub n_iters: usize,
+    /// Whether to stop once the colouring is stable, in which case `n_iters` is ignored.
+    pub check_stable: bool,
+}
+
+impl Default for WlOptions {
+    fn default() -> Self {
+        WlOptions {
+            seed: 42,
+            n_iters: 0,
+            check_stable: true,
+        }
+    }
+}
+
+/// Counters for the work done by a run of 1-dimensional WL, and where the time went, see [`invariant_with_stats`](fn.invariant_with_stats.html). The clock is only read when the stats are requested, so runs without them pay nothing for the timings. The timings differ from run to run, so compare the counters rather than whole stats.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WlStats {
+    /// The number of refinement iterations (not counting the initial labelling).
+    pub iterations: usize,
+    /// The number of node labels computed.
+    pub label_computations: usize,
+    /// The number of labels fed into the hash function.
+    pub multiset_elements_hashed: usize,
+    /// The number of bytes fed into the hash function.
+    pub bytes_hashed: usize,
+    /// The time spent on the initial labelling, including building the adjacency lists.
+    pub init_time: Duration,
+    /// The time spent on computing the labels of every refinement iteration, one entry per iteration.
+    pub iteration_times: Vec<Duration>,
+    /// The total time spent on checking whether the colouring has stabilised.
+    pub stability_check_time: Duration,
+    /// The time spent on hashing the final labels into the result.
+    pub digest_time: Duration,
+}
+
+impl WlStats {
+    pub(crate) fn count_hash(&mut self, elements: usize) {
+        self.multiset_elements_hashed += elements;
+        self.bytes_hashed += elements * core::mem::size_of::<u64>();
+    }
+}
+
+// Without std there is no petgraph, so the kernel brings the part of petgraph's IndexType that it needs, for the index types of the
+// CSR functions
+#[cfg(not(feature = "std"))]
+pub(crate) trait IndexType: Copy {
+    fn index(&self) -> usize;
+}
+
+#[cfg(not(feature = "std"))]
+impl IndexType for u32 {
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl IndexType for usize {
+    fn index(&self) -> usize {
+        *self
+    }
+}
+
+// Adjacency lists in compressed sparse row format: the neighbours of node i are neighbours[offsets[i]..offsets[i + 1]]
+#[derive(Clone, Copy)]
+pub(crate) struct CsrRef<'a, Ix> {
+    offsets: &'a [usize],
+    neighbours: &'a [Ix],
+}
+
+impl<'a, Ix: IndexType> CsrRef<'a, Ix> {
+    pub(crate) fn new(offsets: &'a [usize], neighbours: &'a [Ix]) -> Self {
+        assert!(
+            offsets.last() == Some(&neighbours.len()),
+            "The offsets must start a row for every node and end with the number of neighbours"
+        );
+        CsrRef {
+            offsets,
+            neighbours,
+        }
+    }
+
+    pub(crate) fn node_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub(crate) fn neighbours(&self, node: usize) -> &'a [Ix] {
+        &self.neighbours[self.offsets[node]..self.offsets[node + 1]]
+    }
+}
+
+// Owned adjacency lists, precomputed from a petgraph graph
+#[derive(Default)]
+pub(crate) struct Csr<Ix> {
+    offsets: Vec<usize>,
+    neighbours: Vec<Ix>,
+}
+
+impl<Ix: IndexType> Csr<Ix> {
+    fn from_rows<I: Iterator<Item = Ix>>(rows: impl Iterator<Item = I>) -> Self {
+        let mut csr = Csr {
+            offsets: vec![0],
+            neighbours: Vec::new(),
+        };
+        for row in rows {
+            csr.neighbours.extend(row);
+            csr.offsets.push(csr.neighbours.len());
+        }
+        csr
+    }
+
+    fn view(&self) -> CsrRef<'_, Ix> {
+        CsrRef::new(&self.offsets, &self.neighbours)
+    }
+
+    // List every self-loop `multiplicity` times instead of once
+    #[cfg(feature = "std")]
+    fn with_self_loop_multiplicity(&self, multiplicity: usize) -> Self {
+        let view = self.view();
+        Csr::from_rows((0..view.node_count()).map(|node| {
+            let row = view.neighbours(node);
+            let loops = row.iter().filter(|other| other.index() == node).count();
+            let others = row
+                .iter()
+                .copied()
+                .filter(move |other| other.index() != node);
+            others.chain(core::iter::repeat_n(Ix::new(node), loops * multiplicity))
+        }))
+    }
+}
+
+// The neighbourhoods that the refinement looks at. Directed graphs keep in- and out-neighbours apart.
+pub(crate) enum Adjacency<'a, Ix> {
+    Undirected(CsrRef<'a, Ix>),
+    Directed {
+        incoming: CsrRef<'a, Ix>,
+        outgoing: CsrRef<'a, Ix>,
+    },
+}
+
+impl<'a, Ix: IndexType> Adjacency<'a, Ix> {
+    pub(crate) fn node_count(&self) -> usize {
+        match self {
+            Adjacency::Undirected(csr) => csr.node_count(),
+            Adjacency::Directed { outgoing, .. } => outgoing.node_count(),
+        }
+    }
+
+    // The neighbour lists to look at, in a fixed order: incoming before outgoing for directed graphs
+    #[cfg(feature = "std")]
+    pub(crate) fn lists(&self) -> Vec<CsrRef<'a, Ix>> {
+        match self {
+            Adjacency::Undirected(csr) => vec![*csr],
+            Adjacency::Directed { incoming, outgoing } => vec![*incoming, *outgoing],
+        }
+    }
+}
+
+// The precomputed adjacency of a petgraph graph, so that the refinement does not have to walk petgraph's edge lists every iteration
+#[cfg(feature = "std")]
+pub(crate) enum GraphCsr<Ix> {
+    Undirected(Csr<Ix>),
+    Directed {
+        incoming: Csr<Ix>,
+        outgoing: Csr<Ix>,
+    },
+}
+
+// The position ("slot") of every node in the label vectors, by its index. Nodes are placed in order of their index rather than in the
+// order the graph lists them, so that the per-node outputs do not depend on how the graph (or a future version of petgraph) iterates.
+// For a Graph, whose indices are 0..n, every node's slot is its index.
+#[cfg(feature = "std")]
+pub(crate) struct NodeSlots {
+    slots: Vec<usize>,
+}
+
+#[cfg(feature = "std")]
+impl NodeSlots {
+    pub(crate) fn new<G: IntoNodeIdentifiers + NodeIndexable>(graph: G) -> Self {
+        let mut indices: Vec<usize> = graph
+            .node_identifiers()
+            .map(|node| graph.to_index(node))
+            .collect();
+        indices.sort_unstable();
+        let mut slots = vec![usize::MAX; graph.node_bound()];
+        for (slot, index) in indices.into_iter().enumerate() {
+            slots[index] = slot;
+        }
+        NodeSlots { slots }
+    }
+
+    pub(crate) fn slot(&self, index: usize) -> usize {
+        self.slots[index]
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Ix: IndexType> GraphCsr<Ix> {
+    // Build the lists through the visitor traits, so that views like petgraph's filtered graphs work without copying them first
+    pub(crate) fn new<G>(graph: G) -> Self
+    where
+        G: IntoNodeIdentifiers + IntoNeighborsDirected + NodeIndexable + GraphProp,
+    {
+        Self::with_slots(graph, &NodeSlots::new(graph))
+    }
+
+    // Build the lists with every node in its slot, as numbered by `slots`
+    pub(crate) fn with_slots<G>(graph: G, slots: &NodeSlots) -> Self
+    where
+        G: IntoNodeIdentifiers + IntoNeighborsDirected + NodeIndexable + GraphProp,
+    {
+        let mut by_slot: Vec<G::NodeId> = graph.node_identifiers().collect();
+        by_slot.sort_unstable_by_key(|&node| slots.slot(graph.to_index(node)));
+        // For undirected graphs, the outgoing neighbours are all neighbours
+        let rows = |direction| {
+            Csr::from_rows(by_slot.iter().map(|&node| {
+                graph
+                    .neighbors_directed(node, direction)
+                    .map(|neighbour| Ix::new(slots.slot(graph.to_index(neighbour))))
+            }))
+        };
+        if graph.is_directed() {
+            GraphCsr::Directed {
+                incoming: rows(Incoming),
+                outgoing: rows(Outgoing),
+            }
+        } else {
+            GraphCsr::Undirected(rows(Outgoing))
+        }
+    }
+
+    // Petgraph lists a self-loop once among the neighbours (once in each direction for directed graphs). Count every loop `multiplicity` times instead, in the degree as well as in the neighbour labels.
+    pub(crate) fn with_self_loop_multiplicity(self, multiplicity: usize) -> Self {
+        if multiplicity == 1 {
+            return self;
+        }
+        match self {
+            GraphCsr::Undirected(csr) => {
+                GraphCsr::Undirected(csr.with_self_loop_multiplicity(multiplicity))
+            }
+            GraphCsr::Directed { incoming, outgoing } => GraphCsr::Directed {
+                incoming: incoming.with_self_loop_multiplicity(multiplicity),
+                outgoing: outgoing.with_self_loop_multiplicity(multiplicity),
+            },
+        }
+    }
+
+    pub(crate) fn view(&self) -> Adjacency<'_, Ix> {
+        match self {
+            GraphCsr::Undirected(csr) => Adjacency::Undirected(csr.view()),
+            GraphCsr::Directed { incoming, outgoing } => Adjacency::Directed {
+                incoming: incoming.view(),
+                outgoing: outgoing.view(),
+            },
+        }
+    }
+}
+
+// Initial labels are degrees for undirected graphs, and hashed (out, in) degree pairs for directed graphs
+pub(crate) fn initial_labels<Ix: IndexType>(
+    adjacency: &Adjacency<'_, Ix>,
+    seed: u64,
+    labels: &mut Vec<u64>,
+) {
+    match adjacency {
+        Adjacency::Undirected(csr) => {
+            labels.extend((0..csr.node_count()).map(|node| csr.neighbours(node).len() as u64))
+        }
+        Adjacency::Directed { incoming, outgoing } => {
+            labels.extend((0..outgoing.node_count()).map(|node| {
+                let out = outgoing.neighbours(node).len();
+                let ing = incoming.neighbours(node).len();
+                XxHash64::oneshot(seed, bytemuck::cast_slice(&[out, ing]))
+            }))
+        }
+    }
+}
+
+// The aggregations of `refine_step`, as const parameters
+pub(crate) const SORTED: u8 = 0;
+pub(crate) const SUM: u8 = 1;
+pub(crate) const XOR: u8 = 2;
+
+// `refine_step` with the aggregation chosen at run time
+#[cfg(feature = "std")]
+pub(crate) fn refine_step_with<Ix: IndexType, const STATS: bool>(
+    aggregation: AggregationMode,
+    adjacency: &Adjacency<'_, Ix>,
+    seed: u64,
+    labels: &[u64],
+    new_labels: &mut [u64],
+    stats: Option<&mut WlStats>,
+) {
+    match aggregation {
+        AggregationMode::SortedHash => {
+            refine_step::<Ix, STATS, SORTED>(adjacency, seed, labels, new_labels, stats)
+        }
+        AggregationMode::SumFold => {
+            refine_step::<Ix, STATS, SUM>(adjacency, seed, labels, new_labels, stats)
+        }
+        AggregationMode::XorFold => {
+            refine_step::<Ix, STATS, XOR>(adjacency, seed, labels, new_labels, stats)
+        }
+    }
+}
+
+// Compute the labels of the next iteration: every node hashes the sorted labels of its neighbours, followed by its own label.
+// With SUM or XOR, the neighbour labels are folded into one value instead of sorted, which saves the sort on dense graphs (the sum is the
+// aggregation of the WL optimal assignment kernel).
+// Statistics are only collected when requested, with a separate instantiation so that the normal path pays nothing for it.
+pub(crate) fn refine_step<Ix: IndexType, const STATS: bool, const AGG: u8>(
+    adjacency: &Adjacency<'_, Ix>,
+    seed: u64,
+    labels: &[u64],
+    new_labels: &mut [u64],
+    mut stats: Option<&mut WlStats>,
+) {
+    if STATS {
+        stats.as_mut().unwrap().iterations += 1;
+    }
+    // The neighbour labels are collected in buffers that are reused for every node
+    let aggregate = |csr: &CsrRef<'_, Ix>, node: usize, hashes: &mut Vec<u64>| {
+        hashes.clear();
+        let neighbour_labels = csr
+            .neighbours(node)
+            .iter()
+            .map(|neighbour| labels[neighbour.index()]);
+        match AGG {
+            SUM => {
+                hashes.push(neighbour_labels.fold(0u64, |sum, label| sum.wrapping_add(mix(label))))
+            }
+            XOR => hashes.push(neighbour_labels.fold(0u64, |xor, label| xor ^ mix(label))),
+            _ => {
+                hashes.extend(neighbour_labels);
+                hashes.sort_unstable(); // sort for consistency
+            }
+        }
+    };
+    let mut input_hashes = Vec::new();
+    let mut outgoing_hashes = Vec::new();
+    for node in 0..adjacency.node_count() {
+        // Collect all the relevant hashes: of the node itself and all its neighbours
+        match adjacency {
+            Adjacency::Undirected(csr) => aggregate(csr, node, &mut input_hashes),
+            Adjacency::Directed { incoming, outgoing } => {
+                aggregate(incoming, node, &mut input_hashes);
+                aggregate(outgoing, node, &mut outgoing_hashes);
+                // The two folds are already single values that keep the directions apart
+                if AGG != SORTED {
+                    input_hashes.push(outgoing_hashes[0]);
+                } else {
+                    if STATS {
+                        stats
+                            .as_mut()
+                            .unwrap()
+                            .count_hash(input_hashes.len() + outgoing_hashes.len());
+                    }
+                    //separately label the in and outgoing hashes  (Previously had a concern: what if one combination of nodes followed by another and then the node's hash itself also possible in a different way? Seems unlikely -> different hash iteration)
+                    let incoming_hash =
+                        XxHash64::oneshot(seed, bytemuck::cast_slice(&input_hashes));
+                    let outgoing_hash =
+                        XxHash64::oneshot(seed, bytemuck::cast_slice(&outgoing_hashes));
+                    input_hashes.clear();
+                    input_hashes.extend([incoming_hash, outgoing_hash]);
+                }
+            }
+        }
+        input_hashes.push(labels[node]); // In this way, the hash of the node itself is always the last one of the list!
+        if STATS {
+            let stats = stats.as_mut().unwrap();
+            stats.label_computations += 1;
+            stats.count_hash(input_hashes.len());
+        }
+        new_labels[node] = XxHash64::oneshot(seed, bytemuck::cast_slice(&input_hashes));
+    }
+}
+
+// For directed graphs, hash every node's new label together with the sorted labels of all its neighbours, the incoming and outgoing
+// ones merged. Undirected graphs are left as they are
+#[cfg(feature = "std")]
+pub(crate) fn fold_undirected_context<Ix: IndexType>(
+    adjacency: &Adjacency<'_, Ix>,
+    seed: u64,
+    labels: &[u64],
+    new_labels: &mut [u64],
+) {
+    let Adjacency::Directed { incoming, outgoing } = adjacency else {
+        return;
+    };
+    let mut input_hashes = Vec::new();
+    for (node, new_label) in new_labels.iter_mut().enumerate() {
+        input_hashes.clear();
+        input_hashes.extend(
+            incoming
+                .neighbours(node)
+                .iter()
+                .chain(outgoing.neighbours(node))
+                .map(|neighbour| labels[neighbour.index()]),
+        );
+        input_hashes.sort_unstable();
+        input_hashes.push(*new_label);
+        *new_label = XxHash64::oneshot(seed, bytemuck::cast_slice(&input_hashes));
+    }
+}
+
+// Scramble a label before it is summed or xored. The initial labels are small degrees, whose sums collide easily ({1, 3} and {2, 2}), so they are spread over all 64 bits first (the finaliser of SplitMix64)
+fn mix(label: u64) -> u64 {
+    let mut z = label.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Decides whether a refinement has stabilised: iff all nodes that had the same label still have the same label.
+// Rather than mapping the old labels to the new ones in a hash map every iteration, the classes of the old labels are kept as dense ids,
+// so that passes with a scratch vector indexed by class id compare the new labels. When the colouring is not stable yet, the ids of the
+// new labels follow from splitting the old classes: a new label held by most of a class keeps its id, so only the nodes that split off
+// are sorted. Every new label hashes the old one, so two old classes never merge unless the hash function collides. Labels that the ids
+// do not describe, such as the initial labels, are numbered with a sort, as are the new labels when many nodes split off. After the first
+// iterations nothing is allocated.
+#[derive(Debug, Default)]
+pub(crate) struct Stability {
+    classes: Vec<usize>,                 // The class id of every node
+    class_labels: Vec<u64>,              // The label of every class, all different
+    majority: Vec<(u64, usize)>, // Scratch: a majority vote on the new labels of every class, with its running count
+    split_off: Vec<(usize, u64, usize)>, // Scratch: the class, new label and node of the nodes whose new label is not the majority
+    sorted: Vec<(u64, usize)>,           // Scratch for numbering labels
+}
+
+impl Stability {
+    pub(crate) fn check(&mut self, labels: &[u64], new_labels: &[u64]) -> bool {
+        let n = labels.len();
+        if self.classes.len() != n {
+            self.number_classes(labels);
+        }
+        // When every node is a class of its own, nothing can split any more. The ids describe the labels iff every node has the label of
+        // its class, as those are all different
+        let described = |stability: &Self| {
+            (0..n).all(|node| labels[node] == stability.class_labels[stability.classes[node]])
+        };
+        if self.class_labels.len() == n && described(self) {
+            return true;
+        }
+        // Boyer-Moore voting leaves the label held by more than half of a class, if there is one, and some label of the class otherwise.
+        // Meanwhile the ids are checked.
+        'numbered: loop {
+            self.majority.clear();
+            self.majority.resize(self.class_labels.len(), (0, 0));
+            for node in 0..n {
+                let class = self.classes[node];
+                if labels[node] != self.class_labels[class] {
+                    self.number_classes(labels);
+                    continue 'numbered;
+                }
+                let (candidate, count) = &mut self.majority[class];
+                if *count == 0 {
+                    *candidate = new_labels[node];
+                    *count = 1;
+                } else if *candidate == new_labels[node] {
+                    *count += 1;
+                } else {
+                    *count -= 1;
+                }
+            }
+            break;
+        }
+        // Stable iff every node has the label its class voted for
+        self.split_off.clear();
+        for (node, (&class, &new_label)) in self.classes.iter().zip(new_labels).enumerate() {
+            if new_label != self.majority[class].0 {
+                self.split_off.push((class, new_label, node));
+                // With this many nodes splitting off, numbering all new labels in the next check is cheaper than sorting these
+                if self.split_off.len() > n / 4 {
+                    self.classes.clear();
+                    return false;
+                }
+            }
+        }
+        if self.split_off.is_empty() {
+            return true;
+        }
+
+        // The new labels are the labels of the next check: the majority of a class keeps its id, and the nodes that split off get new ones
+        for (class_label, &(majority, _)) in self.class_labels.iter_mut().zip(&self.majority) {
+            *class_label = majority;
+        }
+        self.split_off.sort_unstable();
+        for (position, &(class, new_label, node)) in self.split_off.iter().enumerate() {
+            if position == 0
+                || self.split_off[position - 1].0 != class
+                || self.split_off[position - 1].1 != new_label
+            {
+                self.class_labels.push(new_label);
+            }
+            self.classes[node] = self.class_labels.len() - 1;
+        }
+        false
+    }
+
+    // Number the classes of the labels by the rank of their label
+    fn number_classes(&mut self, labels: &[u64]) {
+        self.sorted.clear();
+        self.sorted.extend(
+            labels
+                .iter()
+                .enumerate()
+                .map(|(node, &label)| (label, node)),
+        );
+        self.sorted.sort_unstable();
+        self.classes.clear();
+        self.classes.resize(labels.len(), 0);
+        self.class_labels.clear();
+        for &(label, node) in &self.sorted {
+            if self.class_labels.last() != Some(&label) {
+                self.class_labels.push(label);
+            }
+            self.classes[node] = self.class_labels.len() - 1;
+        }
+    }
+}
+
+/// Run 1-dimensional WL directly on adjacency lists in compressed sparse row (CSR) format, without building a petgraph graph. The neighbours of node `i` are `neighbours[offsets[i]..offsets[i + 1]]`, so `offsets` has one more entry than there are nodes. Every undirected edge must be listed from both ends.
+///
+/// Without `init`, the refinement starts from the node degrees. Returns the final label of every node and the graph hash, which equals what [`invariant`](fn.invariant.html) (or [`invariant_iters`](fn.invariant_iters.html), [`invariant_with_initial`](fn.invariant_with_initial.html)) returns for the same graph and options.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use wl_isomorphism::WlOptions;
+///
+/// // The path 0 - 1 - 2
+/// let offsets = [0, 1, 3, 4];
+/// let neighbours = [1, 0, 2, 1];
+/// let (labels, hash) = wl_isomorphism::refine_csr(&offsets, &neighbours, None, WlOptions::default());
+/// assert_eq!(labels[0], labels[2]);
+/// assert_eq!(hash, wl_isomorphism::invariant(UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)])));
+/// ```
+///
+/// Panics if the offsets do not end with the number of neighbours, if a neighbour is not a node, or if `init` does not have exactly one label per node.
+pub fn refine_csr(
+    offsets: &[usize],
+    neighbours: &[u32],
+    init: Option<&[u64]>,
+    opts: WlOptions,
+) -> (Vec<u64>, u64) {
+    let adjacency = Adjacency::Undirected(CsrRef::new(offsets, neighbours));
+    refine(&adjacency, init, opts)
+}
+
+/// Like [`refine_csr`](fn.refine_csr.html), but for directed graphs, given by the CSR lists of both the outgoing and the incoming neighbours of every node.
+///
+/// Panics under the same conditions as [`refine_csr`](fn.refine_csr.html), or if both lists do not have the same number of nodes.
+pub fn refine_csr_directed(
+    out_offsets: &[usize],
+    out_neighbours: &[u32],
+    in_offsets: &[usize],
+    in_neighbours: &[u32],
+    init: Option<&[u64]>,
+    opts: WlOptions,
+) -> (Vec<u64>, u64) {
+    assert_eq!(
+        out_offsets.len(),
+        in_offsets.len(),
+        "The outgoing and incoming lists must have the same number of nodes"
+    );
+    let adjacency = Adjacency::Directed {
+        incoming: CsrRef::new(in_offsets, in_neighbours),
+        outgoing: CsrRef::new(out_offsets, out_neighbours),
+    };
+    refine(&adjacency, init, opts)
+}
+
+/// Calculate the graph invariant of an undirected graph given as adjacency lists, without building a petgraph graph: `adj[i]` lists the neighbours of node `i`, in any order. Every edge must be listed from both ends. Returns the same hash as [`invariant`](fn.invariant.html) on the equivalent graph.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// // The path 0 - 1 - 2
+/// let adj = vec![vec![1], vec![2, 0], vec![1]];
+/// let graph = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+/// assert_eq!(wl_isomorphism::invariant_from_adj_list(adj), wl_isomorphism::invariant(graph));
+/// ```
+///
+/// Panics if a neighbour is not a node.
+pub fn invariant_from_adj_list(adj: Vec<Vec<usize>>) -> u64 {
+    let csr = Csr::from_rows(adj.into_iter().map(Vec::into_iter));
+    refine(
+        &Adjacency::Undirected(csr.view()),
+        None,
+        WlOptions::default(),
+    )
+    .1
+}
+
+/// Like [`invariant_from_adj_list`](fn.invariant_from_adj_list.html), but for a directed graph: `adj[i]` lists the nodes that node `i` has an edge to.
+///
+/// Panics if a neighbour is not a node.
+pub fn invariant_from_adj_list_directed(adj: Vec<Vec<usize>>) -> u64 {
+    let mut incoming = vec![Vec::new(); adj.len()];
+    for (node, neighbours) in adj.iter().enumerate() {
+        for &neighbour in neighbours {
+            incoming[neighbour].push(node);
+        }
+    }
+    let outgoing = Csr::from_rows(adj.into_iter().map(Vec::into_iter));
+    let incoming = Csr::from_rows(incoming.into_iter().map(Vec::into_iter));
+    let adjacency = Adjacency::Directed {
+        incoming: incoming.view(),
+        outgoing: outgoing.view(),
+    };
+    refine(&adjacency, None, WlOptions::default()).1
+}
+
+/// Calculate the graph invariant of any graph that petgraph can visit, such as a `NodeFiltered` or `EdgeFiltered` view of a larger graph, without copying it into a new graph first. The result is the same as [`invariant`](fn.invariant.html) on a graph with only the visible nodes and edges.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use petgraph::visit::NodeFiltered;
+///
+/// // A triangle with a tail, seen without the tail
+/// let graph = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+/// let view = NodeFiltered::from_fn(&graph, |node| node.index() != 3);
+/// let triangle = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0)]);
+/// assert_eq!(wl_isomorphism::invariant_of_view(&view), wl_isomorphism::invariant(triangle));
+/// ```
+#[cfg(feature = "std")]
+pub fn invariant_of_view<G>(graph: G) -> u64
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected + NodeIndexable + GraphProp,
+{
+    let csr = GraphCsr::<usize>::new(graph);
+    refine(&csr.view(), None, WlOptions::default()).1
+}
+
+// The same loop as GraphWrapper::run
+pub(crate) fn refine<Ix: IndexType>(
+    adjacency: &Adjacency<'_, Ix>,
+    init: Option<&[u64]>,
+    opts: WlOptions,
+) -> (Vec<u64>, u64) {
+    let n = adjacency.node_count();
+    let mut labels = Vec::with_capacity(n);
+    match init {
+        Some(init) => {
+            assert_eq!(
+                init.len(),
+                n,
+                "There must be exactly one initial label per node"
+            );
+            labels.extend_from_slice(init);
+        }
+        None => initial_labels(adjacency, opts.seed, &mut labels),
+    }
+    let mut new_labels = vec![0; n];
+    let n_iters = if opts.n_iters == 0 || opts.n_iters > n {
+        n.saturating_sub(1)
+    } else {
+        opts.n_iters
+    };
+    let mut stability = Stability::default();
+    let mut its = 1;
+    while opts.check_stable || its < n_iters {
+        refine_step::<Ix, false, SORTED>(adjacency, opts.seed, &labels, &mut new_labels, None);
+        its += 1;
+        if opts.check_stable && stability.check(&labels, &new_labels) {
+            // As in the wrapper, supplied initial labels that are already stable are refined once, so that the edges count
+            if its == 2 && init.is_some() {
+                core::mem::swap(&mut labels, &mut new_labels);
+            }
+            break;
+        }
+        core::mem::swap(&mut labels, &mut new_labels);
+    }
+    let mut sorted = labels.clone();
+    sorted.sort_unstable();
+    (
+        labels,
+        XxHash64::oneshot(opts.seed, bytemuck::cast_slice(&sorted)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stability() {
+        let mut stability = Stability::default();
+        // Nothing changes, or only the values of the labels change
+        assert!(stability.check(&[1, 1, 2], &[1, 1, 2]));
+        assert!(stability.check(&[1, 1, 2], &[7, 7, 3]));
+        // A class splits
+        assert!(!stability.check(&[1, 1, 2], &[5, 6, 7]));
+        assert!(!stability.check(&[5, 6, 7, 5], &[1, 2, 3, 4]));
+        // Two classes merge, which does not split anything
+        assert!(stability.check(&[1, 2, 3], &[4, 4, 5]));
+        assert!(stability.check(&[], &[]));
+        // A refinement, where the ids of every check follow from the one before
+        let mut stability = Stability::default();
+        assert!(!stability.check(&[1, 1, 1, 1, 1], &[5, 5, 6, 6, 6]));
+        assert!(!stability.check(&[5, 5, 6, 6, 6], &[7, 8, 9, 9, 10]));
+        assert!(stability.check(&[7, 8, 9, 9, 10], &[1, 2, 3, 3, 4]));
+        assert!(!stability.check(&[7, 8, 9, 9, 10], &[1, 2, 3, 4, 5]));
+        // The checks above left their classes behind, which must not leak into a check of other labels of the same length
+        let mut stability = Stability::default();
+        assert!(!stability.check(&[1, 2, 2], &[1, 2, 3]));
+        assert!(stability.check(&[1, 2, 3], &[1, 2, 2]));
+        assert!(!stability.check(&[1, 2, 2], &[1, 2, 3]));
+    }
+
+    // Runs without std as well, where there is no petgraph to compare with
+    #[test]
+    fn csr_functions() {
+        // The path 0 - 1 - 2, numbered from both ends
+        let (labels, hash) = refine_csr(&[0, 1, 3, 4], &[1, 0, 2, 1], None, WlOptions::default());
+        assert_eq!(labels[0], labels[2]);
+        assert_ne!(labels[0], labels[1]);
+        let middle_first = vec![vec![1, 2], vec![0], vec![0]];
+        assert_eq!(invariant_from_adj_list(middle_first), hash);
+        // The directed path has ends with different in- and out-degrees
+        let (labels, directed) = refine_csr_directed(
+            &[0, 1, 2, 2],
+            &[1, 2],
+            &[0, 0, 1, 2],
+            &[0, 1],
+            None,
+            WlOptions::default(),
+        );
+        assert_ne!(labels[0], labels[2]);
+        assert_eq!(
+            invariant_from_adj_list_directed(vec![vec![], vec![0], vec![1]]),
+            directed
+        );
+    }
+}
@@ -0,0 +1,345 @@
+use crate::graphwrapper::GraphWrapper;
+use crate::transform::structure;
+use petgraph::graph::IndexType;
+use petgraph::visit::EdgeRef;
+use petgraph::{EdgeType, Graph};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+/// The colour classes that split during one WL iteration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WlDiff {
+    /// Every class that split, as its old label together with the (sorted) new labels of its parts.
+    pub splits: Vec<(u64, Vec<u64>)>,
+    /// The number of classes that did not split.
+    pub unchanged: usize,
+}
+
+/// Compare the labels of the same nodes before and after an iteration, and find the colour classes that split.
+///
+/// Panics if the slices differ in length.
+pub fn wl_diff(labels_before: &[u64], labels_after: &[u64]) -> WlDiff {
+    assert_eq!(
+        labels_before.len(),
+        labels_after.len(),
+        "Both labellings must cover the same nodes"
+    );
+    let mut parts: HashMap<u64, HashSet<u64>> = HashMap::new();
+    for (old, new) in labels_before.iter().zip(labels_after) {
+        parts.entry(*old).or_default().insert(*new);
+    }
+    let mut splits = Vec::new();
+    let mut unchanged = 0;
+    for (old, new) in parts {
+        if new.len() > 1 {
+            let mut new: Vec<u64> = new.into_iter().collect();
+            new.sort_unstable();
+            splits.push((old, new));
+        } else {
+            unchanged += 1;
+        }
+    }
+    splits.sort_unstable();
+    WlDiff { splits, unchanged }
+}
+
+/// Like [`invariant`](fn.invariant.html), but additionally returns which colour classes split in every iteration. The last entry belongs to the iteration that confirmed stability, so it never contains splits.
+pub fn invariant_with_diffs<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+) -> (u64, Vec<WlDiff>) {
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false);
+    let mut diffs = Vec::new();
+    wrap.run_observed(|before, after| diffs.push(wl_diff(before, after)));
+    (wrap.get_results(), diffs)
+}
+
+/// The complete label history of a WL run, see [`invariant_with_trace`](fn.invariant_with_trace.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WlTrace {
+    /// The label of every node after every iteration, `per_iter_labels[iter][node]`, starting with the initial labels. These are the labels that the run kept, so the last entry is the colouring the invariant is hashed from.
+    pub per_iter_labels: Vec<Vec<u64>>,
+    /// The index into `per_iter_labels` of the colouring that turned out to be stable, or `None` if the run stopped before that.
+    pub stable_at: Option<usize>,
+}
+
+impl WlTrace {
+    /// The number of iterations in which the colour class of `node` split, i.e. in which some node that shared its label before got a different label from it. Label values change every iteration, so this counts the refinements that actually carried information.
+    ///
+    /// Panics if `node` is not a node of the traced graph.
+    pub fn n_refinements_at(&self, node: usize) -> usize {
+        let class_size = |labels: &[u64]| {
+            labels
+                .iter()
+                .filter(|&&label| label == labels[node])
+                .count()
+        };
+        self.per_iter_labels
+            .windows(2)
+            .filter(|pair| class_size(&pair[1]) < class_size(&pair[0]))
+            .count()
+    }
+}
+
+/// Like [`invariant`](fn.invariant.html), but additionally records the labels of all nodes after every iteration, for debugging WL on specific graphs. Memory use grows with the number of nodes times the number of iterations.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let graph = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+/// let (_, trace) = wl_isomorphism::invariant_with_trace(graph);
+/// // The degrees already separate the ends from the middle, and nothing splits after that
+/// assert_eq!(trace.stable_at, Some(0));
+/// assert_eq!(trace.n_refinements_at(0), 0);
+/// ```
+pub fn invariant_with_trace<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+) -> (u64, WlTrace) {
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false);
+    let mut per_iter_labels = Vec::new();
+    wrap.run_streaming(|labels| per_iter_labels.push(labels.to_vec()));
+    // Without an iteration limit, the run only ends once the colouring is stable
+    let stable_at = per_iter_labels.len().checked_sub(1);
+    (
+        wrap.get_results(),
+        WlTrace {
+            per_iter_labels,
+            stable_at,
+        },
+    )
+}
+
+/// For tuning `n_iters` on a class of graphs: how many graphs need how many refinement iterations to reach their stable colouring, as a map from the iteration count to the number of graphs. Neither the initial labelling nor the iteration that only confirms stability is counted, so a graph that needs `k` iterations gets the stable colouring from [`invariant_iters`](fn.invariant_iters.html) with `n_iters = k + 1`.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// // The degrees are already stable on a star, a path of 6 nodes needs one iteration
+/// let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+/// let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)]);
+/// let histogram = wl_isomorphism::refinement_steps_distribution(&[star.clone(), star, path]);
+/// assert_eq!(histogram[&0], 2);
+/// assert_eq!(histogram[&1], 1);
+/// ```
+pub fn refinement_steps_distribution<N: Ord + Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    graphs: &[Graph<N, E, Ty, Ix>],
+) -> HashMap<usize, usize> {
+    let mut histogram = HashMap::new();
+    for graph in graphs {
+        *histogram
+            .entry(refinement_steps(graph.clone()))
+            .or_insert(0) += 1;
+    }
+    histogram
+}
+
+/// The largest number of iterations any of `graphs` needs to reach its stable colouring, counted as in [`refinement_steps_distribution`](fn.refinement_steps_distribution.html), or 0 if there are no graphs. Running [`invariant_iters`](fn.invariant_iters.html) with one more as `n_iters` gives every graph of the corpus its stable colouring.
+pub fn max_refinement_steps<N: Ord + Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    graphs: &[Graph<N, E, Ty, Ix>],
+) -> usize {
+    graphs
+        .iter()
+        .map(|graph| refinement_steps(graph.clone()))
+        .max()
+        .unwrap_or(0)
+}
+
+// The number of labellings a run keeps after the initial one, before it finds the colouring stable
+fn refinement_steps<N: Ord, E, Ty: EdgeType, Ix: IndexType>(graph: Graph<N, E, Ty, Ix>) -> usize {
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false);
+    let mut labellings = 0;
+    wrap.run_streaming(|_| labellings += 1);
+    labellings - 1
+}
+
+/// The number of nodes of every degree, as `(degree, count)` pairs sorted by degree.
+pub type DegreeHistogram = Vec<(usize, usize)>;
+
+/// A colour class that has a different number of nodes in the two graphs of a [`DiagnosisReport`](struct.DiagnosisReport.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassDifference {
+    /// The iteration at which the class occurs (0 is the initial colouring).
+    pub iteration: usize,
+    /// The label of the class.
+    pub label: u64,
+    /// The degree of the nodes in the class (in- plus out-degree for directed graphs), which all nodes of a class share.
+    pub degree: usize,
+    /// The number of nodes in the class in both graphs.
+    pub sizes: (usize, usize),
+    /// Up to three nodes of the class in both graphs.
+    pub examples: (Vec<usize>, Vec<usize>),
+}
+
+/// A reproducible description of how WL sees two graphs, see [`diagnose`](fn.diagnose.html). Pairs hold the value for the first and second graph. The `Display` output is a readable multi-line report, and with the `serde` feature it can be serialised to attach to bug reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiagnosisReport {
+    pub node_counts: (usize, usize),
+    pub edge_counts: (usize, usize),
+    pub degree_histograms: (DegreeHistogram, DegreeHistogram),
+    /// The number of colour classes after every iteration, until the colouring is stable.
+    pub class_counts: (Vec<usize>, Vec<usize>),
+    /// The first iteration at which the colourings of the graphs differ, or `None` if WL cannot tell them apart.
+    pub first_divergence: Option<usize>,
+    /// The class with the smallest label whose size differs at the first divergence.
+    pub differing_class: Option<ClassDifference>,
+}
+
+impl fmt::Display for DiagnosisReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "WL diagnosis (first graph vs second graph)")?;
+        writeln!(
+            f,
+            "  nodes: {} vs {}",
+            self.node_counts.0, self.node_counts.1
+        )?;
+        writeln!(
+            f,
+            "  edges: {} vs {}",
+            self.edge_counts.0, self.edge_counts.1
+        )?;
+        writeln!(
+            f,
+            "  degree histogram (degree, count): {:?} vs {:?}",
+            self.degree_histograms.0, self.degree_histograms.1
+        )?;
+        writeln!(
+            f,
+            "  classes per iteration: {:?} vs {:?}",
+            self.class_counts.0, self.class_counts.1
+        )?;
+        match (self.first_divergence, &self.differing_class) {
+            (None, _) => write!(f, "  no divergence: WL cannot tell the graphs apart"),
+            (Some(iteration), None) => write!(f, "  first divergence: iteration {}", iteration),
+            (Some(iteration), Some(class)) => {
+                writeln!(f, "  first divergence: iteration {}", iteration)?;
+                write!(
+                    f,
+                    "  first differing class: label {} (nodes of degree {}) has {} vs {} nodes, e.g. {:?} vs {:?}",
+                    class.label,
+                    class.degree,
+                    class.sizes.0,
+                    class.sizes.1,
+                    class.examples.0,
+                    class.examples.1
+                )
+            }
+        }
+    }
+}
+
+/// Describe how WL sees two graphs, for reports like "these should be isomorphic but the hashes differ": their sizes and degrees, the number of colour classes per iteration, and where the colourings first diverge, down to a class whose size differs with some of its nodes. The labels are the same as those of [`invariant`](fn.invariant.html), so the nodes can be inspected further with e.g. [`neighbourhood_hash`](fn.neighbourhood_hash.html).
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+/// let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+/// let report = wl_isomorphism::diagnose(path, star);
+/// assert_eq!(report.node_counts, (4, 4));
+/// assert_eq!(report.degree_histograms, (vec![(1, 2), (2, 2)], vec![(1, 3), (3, 1)]));
+/// // The degrees already differ, in the number of leaves
+/// assert_eq!(report.first_divergence, Some(0));
+/// let leaves = report.differing_class.unwrap();
+/// assert_eq!((leaves.degree, leaves.sizes), (1, (2, 3)));
+/// assert_eq!(leaves.examples, (vec![0, 3], vec![1, 2, 3]));
+/// ```
+pub fn diagnose<N, E, Ty: EdgeType, Ix: IndexType>(
+    g1: Graph<N, E, Ty, Ix>,
+    g2: Graph<N, E, Ty, Ix>,
+) -> DiagnosisReport {
+    // WL ignores the weights, and without them the graphs can be refined twice
+    let (g1, g2) = (structure(&g1), structure(&g2));
+    let (degrees1, degrees2) = (degrees(&g1), degrees(&g2));
+    let stable_rows = |graph: &Graph<(), (), Ty, Ix>| {
+        let mut rows = Vec::new();
+        GraphWrapper::new(graph.clone(), 42, 0, true, false)
+            .run_streaming(|labels| rows.push(labels.to_vec()));
+        rows
+    };
+    let (stable1, stable2) = (stable_rows(&g1), stable_rows(&g2));
+    let class_counts = |rows: &[Vec<u64>]| -> Vec<usize> {
+        rows.iter()
+            .map(|labels| labels.iter().collect::<HashSet<_>>().len())
+            .collect()
+    };
+
+    // A graph that is already stable can still differ from the other one in the next iteration, so compare both for one iteration more than the longest run
+    let n_iters = stable1.len().max(stable2.len()) + 1;
+    let fixed_rows = |graph: Graph<(), (), Ty, Ix>| {
+        let mut rows = Vec::new();
+        GraphWrapper::new(graph, 42, n_iters, false, false)
+            .run_streaming(|labels| rows.push(labels.to_vec()));
+        rows
+    };
+    let (rows1, rows2) = (fixed_rows(g1.clone()), fixed_rows(g2.clone()));
+    let mut first_divergence = None;
+    let mut differing_class = None;
+    for (iteration, (labels1, labels2)) in rows1.iter().zip(&rows2).enumerate() {
+        let (classes1, classes2) = (classes(labels1), classes(labels2));
+        if classes1.len() == classes2.len()
+            && classes1
+                .iter()
+                .all(|(label, nodes)| classes2.get(label).map(Vec::len) == Some(nodes.len()))
+        {
+            continue;
+        }
+        first_divergence = Some(iteration);
+        let empty = Vec::new();
+        differing_class = classes1
+            .keys()
+            .chain(classes2.keys())
+            .filter_map(|label| {
+                let nodes1 = classes1.get(label).unwrap_or(&empty);
+                let nodes2 = classes2.get(label).unwrap_or(&empty);
+                (nodes1.len() != nodes2.len()).then_some((*label, nodes1, nodes2))
+            })
+            .min_by_key(|(label, _, _)| *label)
+            .map(|(label, nodes1, nodes2)| ClassDifference {
+                iteration,
+                label,
+                degree: nodes1
+                    .first()
+                    .map(|&node| degrees1[node])
+                    .unwrap_or_else(|| degrees2[nodes2[0]]),
+                sizes: (nodes1.len(), nodes2.len()),
+                examples: (
+                    nodes1.iter().take(3).copied().collect(),
+                    nodes2.iter().take(3).copied().collect(),
+                ),
+            });
+        break;
+    }
+    DiagnosisReport {
+        node_counts: (g1.node_count(), g2.node_count()),
+        edge_counts: (g1.edge_count(), g2.edge_count()),
+        degree_histograms: (histogram(&degrees1), histogram(&degrees2)),
+        class_counts: (class_counts(&stable1), class_counts(&stable2)),
+        first_divergence,
+        differing_class,
+    }
+}
+
+// The nodes of every colour class, by label
+fn classes(labels: &[u64]) -> BTreeMap<u64, Vec<usize>> {
+    let mut classes: BTreeMap<u64, Vec<usize>> = BTreeMap::new();
+    for (node, label) in labels.iter().enumerate() {
+        classes.entry(*label).or_default().push(node);
+    }
+    classes
+}
+
+// The number of edge endpoints at every node
+fn degrees<Ty: EdgeType, Ix: IndexType>(graph: &Graph<(), (), Ty, Ix>) -> Vec<usize> {
+    let mut degrees = vec![0; graph.node_count()];
+    for edge in graph.edge_references() {
+        degrees[edge.source().index()] += 1;
+        degrees[edge.target().index()] += 1;
+    }
+    degrees
+}
+
+fn histogram(degrees: &[usize]) -> DegreeHistogram {
+    let mut counts: BTreeMap<usize, usize> = BTreeMap::new();
+    for &degree in degrees {
+        *counts.entry(degree).or_default() += 1;
+    }
+    counts.into_iter().collect()
+}
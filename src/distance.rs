@@ -0,0 +1,183 @@
+use crate::csr::{initial_labels, Adjacency, GraphCsr, Stability};
+use petgraph::graph::{IndexType, NodeIndex};
+use petgraph::{EdgeType, Graph};
+use std::collections::VecDeque;
+use twox_hash::XxHash64;
+
+/// Calculate a graph invariant with distance-encoded WL: instead of the labels of its neighbours, every node looks at the labels of all nodes within `k` hops, each paired with its distance. Two nodes whose direct neighbourhoods look the same can still be told apart by what lies further away, e.g. every node of a 6-cycle has two nodes at distance 2, while those of two triangles have none. With `k = 1` this is the refinement of [`invariant`](fn.invariant.html), although the hashes differ. Directed graphs are searched along the outgoing edges. Automatically stabilises.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let hexagon = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+/// let triangles = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+/// assert_eq!(wl_isomorphism::invariant(hexagon.clone()), wl_isomorphism::invariant(triangles.clone()));
+/// assert_ne!(
+///     wl_isomorphism::invariant_distance_encoded(hexagon, 2),
+///     wl_isomorphism::invariant_distance_encoded(triangles, 2)
+/// );
+/// ```
+///
+/// The search from every node visits its whole `k`-hop ball, so this takes O(n * (size of a ball)) time and memory per iteration, which for large `k` approaches O(n^2).
+pub fn invariant_distance_encoded<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    k: usize,
+) -> u64 {
+    let seed = 42;
+    let mut labels = distance_encoded_labels(&graph, k, seed);
+    labels.sort_unstable();
+    XxHash64::oneshot(seed, bytemuck::cast_slice(&labels))
+}
+
+/// The shortest-path distance between every pair of nodes, by a breadth-first search from every node, with `u32::MAX` for nodes that cannot be reached. Directed graphs are searched along the outgoing edges, so `distances[a][b]` is the length of the shortest path from `a` to `b`. This takes O(n (n + m)) time and O(n^2) memory.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let mut path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+/// path.add_node(());
+/// let distances = wl_isomorphism::distance_matrix_via_bfs(&path);
+/// assert_eq!(distances[0], [0, 1, 2, u32::MAX]);
+/// ```
+pub fn distance_matrix_via_bfs<N, E, Ty: EdgeType, Ix: IndexType>(
+    graph: &Graph<N, E, Ty, Ix>,
+) -> Vec<Vec<u32>> {
+    let n = graph.node_count();
+    let mut queue = VecDeque::new();
+    (0..n)
+        .map(|start| {
+            let mut distances = vec![u32::MAX; n];
+            distances[start] = 0;
+            queue.push_back(start);
+            while let Some(node) = queue.pop_front() {
+                for neighbour in graph.neighbors(NodeIndex::new(node)) {
+                    if distances[neighbour.index()] == u32::MAX {
+                        distances[neighbour.index()] = distances[node] + 1;
+                        queue.push_back(neighbour.index());
+                    }
+                }
+            }
+            distances
+        })
+        .collect()
+}
+
+// The stable labels of every node under distance-encoded refinement
+fn distance_encoded_labels<N, E, Ty: EdgeType, Ix: IndexType>(
+    graph: &Graph<N, E, Ty, Ix>,
+    k: usize,
+    seed: u64,
+) -> Vec<u64> {
+    let csr = GraphCsr::<Ix>::new(graph);
+    let adjacency = csr.view();
+    let balls = Balls::new(&adjacency, k);
+    let mut labels = Vec::with_capacity(adjacency.node_count());
+    initial_labels(&adjacency, seed, &mut labels);
+    let mut new_labels = vec![0; labels.len()];
+    let mut input_hashes = Vec::new();
+    let mut stability = Stability::default();
+    // n - 1 iterations are always enough to stabilise
+    for _ in 0..labels.len() {
+        for (node, new_label) in new_labels.iter_mut().enumerate() {
+            input_hashes.clear();
+            input_hashes.extend(balls.of(node).iter().map(|&(other, distance)| {
+                XxHash64::oneshot(seed, bytemuck::cast_slice(&[labels[other], distance]))
+            }));
+            input_hashes.sort_unstable();
+            input_hashes.push(labels[node]); // The node's own label comes last, as in the normal refinement
+            *new_label = XxHash64::oneshot(seed, bytemuck::cast_slice(&input_hashes));
+        }
+        // Unlike in the normal refinement, the new labels are kept even when they split nothing: a graph whose nodes all look alike still
+        // differs from another by what the nodes see, such as a node at distance 2 in a 6-cycle
+        let stable = stability.check(&labels, &new_labels);
+        std::mem::swap(&mut labels, &mut new_labels);
+        if stable {
+            break;
+        }
+    }
+    labels
+}
+
+// For every node, the other nodes within k hops with their distance, found by a breadth-first search from each node.
+// Stored like the CSR lists: the ball of node i is members[offsets[i]..offsets[i + 1]].
+struct Balls {
+    offsets: Vec<usize>,
+    members: Vec<(usize, u64)>,
+}
+
+impl Balls {
+    fn new<Ix: IndexType>(adjacency: &Adjacency<'_, Ix>, k: usize) -> Self {
+        let neighbours = match adjacency {
+            Adjacency::Undirected(csr) => *csr,
+            Adjacency::Directed { outgoing, .. } => *outgoing,
+        };
+        let n = adjacency.node_count();
+        let mut balls = Balls {
+            offsets: vec![0],
+            members: Vec::new(),
+        };
+        // The search that last visited each node, so the visited marks do not have to be cleared between searches
+        let mut visited_by = vec![usize::MAX; n];
+        for source in 0..n {
+            visited_by[source] = source;
+            // The ball itself is the queue: the nodes are appended in order of their distance
+            let start = balls.members.len();
+            let mut frontier = start;
+            balls.members.push((source, 0));
+            while let Some(&(node, distance)) = balls.members.get(frontier) {
+                frontier += 1;
+                if distance as usize == k {
+                    continue;
+                }
+                for neighbour in neighbours.neighbours(node) {
+                    let neighbour = neighbour.index();
+                    if visited_by[neighbour] != source {
+                        visited_by[neighbour] = source;
+                        balls.members.push((neighbour, distance + 1));
+                    }
+                }
+            }
+            // The source itself is covered by its own label
+            balls.members.remove(start);
+            balls.offsets.push(balls.members.len());
+        }
+        balls
+    }
+
+    fn of(&self, node: usize) -> &[(usize, u64)] {
+        &self.members[self.offsets[node]..self.offsets[node + 1]]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::graph::UnGraph;
+
+    #[test]
+    fn wheel() {
+        // The centre 0 joined to every node of the cycle 1..=6
+        let mut edges: Vec<(u32, u32)> = (1..=6).map(|spoke| (0, spoke)).collect();
+        edges.extend((1..=6).map(|spoke| (spoke, spoke % 6 + 1)));
+        let wheel = UnGraph::<(), ()>::from_edges(edges);
+        for k in 1..=3 {
+            let labels = distance_encoded_labels(&wheel, k, 42);
+            assert!(labels[1..].iter().all(|&label| label == labels[1]));
+            assert_ne!(labels[0], labels[1]);
+        }
+    }
+
+    #[test]
+    fn balls() {
+        // The path 0 - 1 - 2 - 3
+        let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+        let csr = GraphCsr::<u32>::new(&path);
+        let balls = Balls::new(&csr.view(), 2);
+        let sorted = |node| {
+            let mut ball = balls.of(node).to_vec();
+            ball.sort_unstable();
+            ball
+        };
+        assert_eq!(sorted(0), [(1, 1), (2, 2)]);
+        assert_eq!(sorted(1), [(0, 1), (2, 1), (3, 2)]);
+        assert!(Balls::new(&csr.view(), 0).of(1).is_empty());
+    }
+}
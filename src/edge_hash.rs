@@ -0,0 +1,102 @@
+use crate::csr::{initial_labels, GraphCsr};
+use petgraph::graph::IndexType;
+use petgraph::visit::EdgeRef;
+use petgraph::{EdgeType, Graph};
+use std::hash::{Hash, Hasher};
+use twox_hash::XxHash64;
+
+/// Generate WL hashes per edge per iteration, as features for GNNs that work on edges: `result[edge.index()][iteration]`. An edge starts with the hash of the initial labels (degrees) of its endpoints and its weight, so that edges with different weights start in different colour classes. In every iteration an edge hashes, for each of its endpoints, the sorted labels of the other edges at that endpoint, followed by its own label. For undirected graphs the endpoints are unordered; for directed graphs the source comes first, and the other edges are told apart by whether they leave or enter the endpoint.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// // The two outer edges of a path look alike, unless their weights differ
+/// let path = UnGraph::<(), u32>::from_edges([(0, 1, 7), (1, 2, 7), (2, 3, 7)]);
+/// let hashes = wl_isomorphism::edge_neighbourhood_hash(path, 3);
+/// assert_eq!(hashes[0], hashes[2]);
+/// let weighted = UnGraph::<(), u32>::from_edges([(0, 1, 7), (1, 2, 7), (2, 3, 8)]);
+/// let hashes = wl_isomorphism::edge_neighbourhood_hash(weighted, 3);
+/// assert_ne!(hashes[0][0], hashes[2][0]);
+/// ```
+///
+/// Like [`neighbourhood_hash`](fn.neighbourhood_hash.html), `n_iters` counts the initial labelling too, so every row has `n_iters` hashes.
+pub fn edge_neighbourhood_hash<N: Ord, E: Hash, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    n_iters: usize,
+) -> Vec<Vec<u64>> {
+    let seed = 42;
+    let directed = graph.is_directed();
+    let csr = GraphCsr::<Ix>::new(&graph);
+    let mut node_labels = Vec::with_capacity(graph.node_count());
+    initial_labels(&csr.view(), seed, &mut node_labels);
+
+    // The edges at every node, with whether they enter it (as opposed to leave it, or being undirected)
+    let mut incidence = vec![Vec::new(); graph.node_count()];
+    let mut labels: Vec<u64> = Vec::with_capacity(graph.edge_count());
+    for edge in graph.edge_references() {
+        let (source, target) = (edge.source().index(), edge.target().index());
+        incidence[source].push((edge.id().index(), 0u64));
+        incidence[target].push((edge.id().index(), directed as u64));
+        let ends = if directed || node_labels[source] <= node_labels[target] {
+            [node_labels[source], node_labels[target]]
+        } else {
+            [node_labels[target], node_labels[source]]
+        };
+        labels.push(XxHash64::oneshot(
+            seed,
+            bytemuck::cast_slice(&[ends[0], ends[1], weight_hash(seed, edge.weight())]),
+        ));
+    }
+    let endpoints: Vec<[usize; 2]> = graph
+        .edge_references()
+        .map(|edge| [edge.source().index(), edge.target().index()])
+        .collect();
+
+    let mut rows = vec![Vec::with_capacity(n_iters); labels.len()];
+    let mut new_labels = vec![0; labels.len()];
+    let mut input_hashes = Vec::new();
+    for iteration in 0..n_iters {
+        if iteration > 0 {
+            for (edge, new_label) in new_labels.iter_mut().enumerate() {
+                let mut ends = endpoints[edge].map(|node| {
+                    input_hashes.clear();
+                    input_hashes.extend(
+                        incidence[node]
+                            .iter()
+                            .filter(|&&(other, _)| other != edge)
+                            .map(|&(other, enters)| {
+                                if directed {
+                                    XxHash64::oneshot(
+                                        seed,
+                                        bytemuck::cast_slice(&[labels[other], enters]),
+                                    )
+                                } else {
+                                    labels[other]
+                                }
+                            }),
+                    );
+                    input_hashes.sort_unstable();
+                    XxHash64::oneshot(seed, bytemuck::cast_slice(&input_hashes))
+                });
+                if !directed {
+                    ends.sort_unstable();
+                }
+                *new_label = XxHash64::oneshot(
+                    seed,
+                    bytemuck::cast_slice(&[ends[0], ends[1], labels[edge]]),
+                );
+            }
+            std::mem::swap(&mut labels, &mut new_labels);
+        }
+        for (row, &label) in rows.iter_mut().zip(&labels) {
+            row.push(label);
+        }
+    }
+    rows
+}
+
+// Hash a node or edge weight with the standard `Hash` implementation, for weights that are not labels themselves
+pub(crate) fn weight_hash<T: Hash>(seed: u64, weight: &T) -> u64 {
+    let mut hasher = XxHash64::with_seed(seed);
+    weight.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,205 @@
+use crate::graphwrapper::GraphWrapper;
+use crate::transform::disjoint_union;
+use petgraph::graph::{IndexType, NodeIndex};
+use petgraph::{Graph, Undirected};
+use std::collections::HashMap;
+
+/// One step of an edit script from [`wl_edit_script`](fn.wl_edit_script.html). Deletions refer to the node indices of the first graph and additions to those of the second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EditOp {
+    /// Add the edge between these nodes of the second graph.
+    AddEdge(u32, u32),
+    /// Delete the edge between these nodes of the first graph.
+    DeleteEdge(u32, u32),
+    /// Add this node of the second graph.
+    AddNode(u64),
+    /// Delete this node of the first graph.
+    DeleteNode(u64),
+}
+
+/// Find a short sequence of edits that turns `g1` into a graph isomorphic to `g2`, for graphs that are close to isomorphic. The nodes are aligned with WL colours computed on both graphs together, so that they are comparable: the alignment starts from the pair whose colours agree for the most iterations and grows along the edges, each time adding the pair that keeps most edges to the nodes aligned before, with ties going to the longest agreeing colours. The greedy choices are then repaired by swapping nodes while that keeps more edges. Whatever cannot be aligned is deleted from `g1` or added from `g2`.
+///
+/// Every step compares all remaining pairs of nodes, so this takes O(n^3) time and is meant for small graphs.
+///
+/// The script lists the edge deletions, then the node deletions, the node additions and finally the edge additions. Edges of deleted and added nodes are listed too, so that the length of the script is the edit distance with unit costs under this alignment. As the alignment is greedy, that is an upper bound on the graph edit distance, which is usually close for graphs that differ in a few edges.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use wl_isomorphism::EditOp;
+///
+/// let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
+/// let cycle = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 0)]);
+/// let script = wl_isomorphism::wl_edit_script(path, cycle);
+/// assert!(matches!(script[..], [EditOp::AddEdge(_, _)]));
+/// ```
+pub fn wl_edit_script<N: Ord, E, Ix: IndexType>(
+    g1: Graph<N, E, Undirected, Ix>,
+    g2: Graph<N, E, Undirected, Ix>,
+) -> Vec<EditOp> {
+    let alignment = align(&g1, &g2);
+    let mut script = Vec::new();
+
+    // Edges of g2 between aligned nodes, with their multiplicity, to be ticked off by the edges of g1
+    let key = |a: usize, b: usize| (a.min(b), a.max(b));
+    let mut missing: HashMap<(usize, usize), usize> = HashMap::new();
+    for edge in g2.raw_edges() {
+        *missing
+            .entry(key(edge.source().index(), edge.target().index()))
+            .or_default() += 1;
+    }
+    for edge in g1.raw_edges() {
+        let (a, b) = (edge.source().index(), edge.target().index());
+        let kept = match (alignment[a], alignment[b]) {
+            (Some(x), Some(y)) => match missing.get_mut(&key(x, y)) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+        if !kept {
+            script.push(EditOp::DeleteEdge(a as u32, b as u32));
+        }
+    }
+
+    let mut aligned_in_g2 = vec![false; g2.node_count()];
+    for (node, image) in alignment.iter().enumerate() {
+        match image {
+            Some(image) => aligned_in_g2[*image] = true,
+            None => script.push(EditOp::DeleteNode(node as u64)),
+        }
+    }
+    for (node, _) in aligned_in_g2
+        .iter()
+        .enumerate()
+        .filter(|(_, &aligned)| !aligned)
+    {
+        script.push(EditOp::AddNode(node as u64));
+    }
+
+    let mut added: Vec<((usize, usize), usize)> = missing
+        .into_iter()
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    added.sort_unstable();
+    for ((a, b), count) in added {
+        for _ in 0..count {
+            script.push(EditOp::AddEdge(a as u32, b as u32));
+        }
+    }
+    script
+}
+
+// For every node of g1, the node of g2 it is aligned with, if any
+fn align<N: Ord, E, Ix: IndexType>(
+    g1: &Graph<N, E, Undirected, Ix>,
+    g2: &Graph<N, E, Undirected, Ix>,
+) -> Vec<Option<usize>> {
+    let offset = g1.node_count();
+    let union = disjoint_union(g1, g2);
+    let colours = if union.node_count() == 0 {
+        Vec::new()
+    } else {
+        let mut wrap = GraphWrapper::new(union, 42, 0, true, true);
+        wrap.run();
+        wrap.subgraphs.take().unwrap()
+    };
+    let iterations = colours.first().map_or(0, Vec::len);
+
+    let mut alignment: Vec<Option<usize>> = vec![None; g1.node_count()];
+    let mut aligned_in_g2 = vec![false; g2.node_count()];
+    // How well `node` fits `image`, given the nodes aligned before: the edges to them that are kept minus those that would have to be deleted
+    // or added, then how many iterations the two colours agree for
+    let fit = |alignment: &[Option<usize>], aligned_in_g2: &[bool], node: usize, image: usize| {
+        let (mut kept, mut edges) = (0, 0);
+        for neighbour_image in g1
+            .neighbors(NodeIndex::new(node))
+            .filter_map(|neighbour| alignment[neighbour.index()])
+        {
+            edges += 1;
+            if g2
+                .find_edge(NodeIndex::new(image), NodeIndex::new(neighbour_image))
+                .is_some()
+            {
+                kept += 1;
+            }
+        }
+        edges += g2
+            .neighbors(NodeIndex::new(image))
+            .filter(|neighbour| aligned_in_g2[neighbour.index()])
+            .count();
+        let agreement = (0..iterations)
+            .take_while(|&depth| colours[node][depth] == colours[offset + image][depth])
+            .count();
+        (2 * kept as isize - edges as isize, agreement)
+    };
+    // Start from the pair whose colours agree longest, then keep aligning the best fitting pair, so that the alignment grows along the
+    // edges from the nodes it has already placed
+    loop {
+        let mut best = None;
+        for node in (0..g1.node_count()).filter(|&node| alignment[node].is_none()) {
+            for image in (0..g2.node_count()).filter(|&image| !aligned_in_g2[image]) {
+                let score = fit(&alignment, &aligned_in_g2, node, image);
+                // Ties go to the first pair found, i.e. the smallest indices
+                if best.is_none_or(|(best_score, _, _)| score > best_score) {
+                    best = Some((score, node, image));
+                }
+            }
+        }
+        match best {
+            Some((_, node, image)) => {
+                alignment[node] = Some(image);
+                aligned_in_g2[image] = true;
+            }
+            None => break,
+        }
+    }
+
+    // The greedy choices can go wrong near the edits, where the colours no longer agree. Repair them by swapping the images of two nodes, or
+    // moving a node to an image nobody has, as long as that keeps more edges
+    let kept_edges = |alignment: &[Option<usize>], node: usize| match alignment[node] {
+        Some(image) => g1
+            .neighbors(NodeIndex::new(node))
+            .filter_map(|neighbour| alignment[neighbour.index()])
+            .filter(|&neighbour_image| {
+                g2.find_edge(NodeIndex::new(image), NodeIndex::new(neighbour_image))
+                    .is_some()
+            })
+            .count(),
+        None => 0,
+    };
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for node in 0..g1.node_count() {
+            for other in node + 1..g1.node_count() {
+                if alignment[node].is_none() && alignment[other].is_none() {
+                    continue;
+                }
+                // An edge between the two is counted twice, but equally often before and after
+                let before = kept_edges(&alignment, node) + kept_edges(&alignment, other);
+                alignment.swap(node, other);
+                if kept_edges(&alignment, node) + kept_edges(&alignment, other) > before {
+                    improved = true;
+                } else {
+                    alignment.swap(node, other);
+                }
+            }
+            if let Some(image) = alignment[node] {
+                for free in (0..g2.node_count()).filter(|&free| !aligned_in_g2[free]) {
+                    let before = kept_edges(&alignment, node);
+                    alignment[node] = Some(free);
+                    if kept_edges(&alignment, node) > before {
+                        aligned_in_g2[image] = false;
+                        aligned_in_g2[free] = true;
+                        improved = true;
+                        break;
+                    }
+                    alignment[node] = Some(image);
+                }
+            }
+        }
+    }
+    alignment
+}
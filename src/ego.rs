@@ -0,0 +1,129 @@
+use crate::csr::{initial_labels, refine, Adjacency, CsrRef, GraphCsr, WlOptions};
+use petgraph::graph::IndexType;
+use petgraph::{EdgeType, Graph};
+use twox_hash::XxHash64;
+
+/// Calculate for every node the invariant of its ego network: the subgraph induced by the nodes within `radius` hops, with the node itself marked as the centre. Unlike [`neighbourhood_hash`](fn.neighbourhood_hash.html), where colours flow in from the whole graph, this sees only the edges between the nodes of the ball, which makes it a feature of the local structure, e.g. for motifs. Directed graphs are searched along the outgoing edges, and the ego network keeps all edges between the nodes found. With the `rayon` feature enabled, the nodes are processed in parallel.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// // Every node of a cycle sees the same, but the ends of a path see less than the nodes in between
+/// let cycle = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+/// let egos = wl_isomorphism::ego_invariants(cycle, 2);
+/// assert!(egos.iter().all(|&ego| ego == egos[0]));
+/// let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)]);
+/// let egos = wl_isomorphism::ego_invariants(path, 2);
+/// assert_eq!(egos[0], egos[5]);
+/// assert_ne!(egos[0], egos[2]);
+/// ```
+///
+/// Every ego network is refined on its own, so this takes O(n * (size of a ball)^2) time in the worst case. The search buffers are shared between the nodes.
+pub fn ego_invariants<N, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    radius: usize,
+) -> Vec<u64> {
+    let csr = GraphCsr::<usize>::new(&graph);
+    let adjacency = csr.view();
+    let n = adjacency.node_count();
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        (0..n)
+            .into_par_iter()
+            .map_init(
+                || EgoBuffers::new(n),
+                |buffers, node| buffers.invariant(&adjacency, node, radius),
+            )
+            .collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        let mut buffers = EgoBuffers::new(n);
+        (0..n)
+            .map(|node| buffers.invariant(&adjacency, node, radius))
+            .collect()
+    }
+}
+
+// The buffers for extracting ego networks, reused from one centre to the next
+struct EgoBuffers {
+    position: Vec<usize>, // The position of every node in `members`, or usize::MAX if it is not in the current ego network
+    members: Vec<(usize, usize)>, // The nodes of the ego network with their distance to the centre, in the order they were found
+    offsets: [Vec<usize>; 2], // The CSR lists of the ego network: one for undirected graphs, incoming and outgoing for directed ones
+    neighbours: [Vec<usize>; 2],
+}
+
+impl EgoBuffers {
+    fn new(n: usize) -> Self {
+        EgoBuffers {
+            position: vec![usize::MAX; n],
+            members: Vec::new(),
+            offsets: Default::default(),
+            neighbours: Default::default(),
+        }
+    }
+
+    fn invariant(&mut self, adjacency: &Adjacency<'_, usize>, centre: usize, radius: usize) -> u64 {
+        let seed = 42;
+        let search = match adjacency {
+            Adjacency::Undirected(csr) => *csr,
+            Adjacency::Directed { outgoing, .. } => *outgoing,
+        };
+        // Breadth-first search, with the members as the queue
+        self.members.clear();
+        self.members.push((centre, 0));
+        self.position[centre] = 0;
+        let mut next = 0;
+        while let Some(&(node, distance)) = self.members.get(next) {
+            next += 1;
+            if distance == radius {
+                continue;
+            }
+            for &neighbour in search.neighbours(node) {
+                if self.position[neighbour] == usize::MAX {
+                    self.position[neighbour] = self.members.len();
+                    self.members.push((neighbour, distance + 1));
+                }
+            }
+        }
+
+        // The induced subgraph, numbered by position
+        let lists = adjacency.lists();
+        for (list, (offsets, neighbours)) in lists
+            .iter()
+            .zip(self.offsets.iter_mut().zip(&mut self.neighbours))
+        {
+            offsets.clear();
+            neighbours.clear();
+            offsets.push(0);
+            for &(node, _) in &self.members {
+                neighbours.extend(
+                    list.neighbours(node)
+                        .iter()
+                        .map(|&neighbour| self.position[neighbour])
+                        .filter(|&position| position != usize::MAX),
+                );
+                offsets.push(neighbours.len());
+            }
+        }
+        let ego = match adjacency {
+            Adjacency::Undirected(_) => {
+                Adjacency::Undirected(CsrRef::new(&self.offsets[0], &self.neighbours[0]))
+            }
+            Adjacency::Directed { .. } => Adjacency::Directed {
+                incoming: CsrRef::new(&self.offsets[0], &self.neighbours[0]),
+                outgoing: CsrRef::new(&self.offsets[1], &self.neighbours[1]),
+            },
+        };
+        // Start from the degrees within the ego network, with the centre marked so that it is told apart from the other nodes
+        let mut init = Vec::with_capacity(self.members.len());
+        initial_labels(&ego, seed, &mut init);
+        init[0] = XxHash64::oneshot(seed, bytemuck::cast_slice(&[init[0], 1]));
+        let (_, hash) = refine(&ego, Some(&init), WlOptions::default());
+
+        for &(node, _) in &self.members {
+            self.position[node] = usize::MAX;
+        }
+        hash
+    }
+}
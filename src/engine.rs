@@ -0,0 +1,59 @@
+use crate::graphwrapper::GraphWrapper;
+use petgraph::graph::IndexType;
+use petgraph::{EdgeType, Graph};
+
+/// Computes WL results for many graphs in a row, reusing its internal buffers between graphs. When hashing thousands of graphs of similar size this avoids most of the allocations that repeated calls to [`invariant`](fn.invariant.html) make.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let mut engine = wl_isomorphism::WlEngine::new();
+/// let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+/// assert_eq!(engine.compute(g.clone()), wl_isomorphism::invariant(g));
+/// ```
+#[derive(Debug, Default)]
+pub struct WlEngine {
+    labels_buf: Vec<u64>,
+    new_labels_buf: Vec<u64>,
+    subgraph_buf: Option<Vec<Vec<u64>>>,
+}
+
+impl WlEngine {
+    /// Create an engine with empty buffers, which grow to fit the largest graph seen.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calculate the graph invariant, exactly like [`invariant`](fn.invariant.html).
+    pub fn compute<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+        &mut self,
+        graph: Graph<N, E, Ty, Ix>,
+    ) -> u64 {
+        let buffers = (
+            std::mem::take(&mut self.labels_buf),
+            std::mem::take(&mut self.new_labels_buf),
+            None,
+        );
+        let mut wrap = GraphWrapper::with_buffers(graph, 42, 0, true, buffers);
+        wrap.run();
+        let hash = wrap.get_results();
+        (self.labels_buf, self.new_labels_buf, _) = wrap.into_buffers();
+        hash
+    }
+
+    /// Generate the subgraph hashes per node per iteration, exactly like [`neighbourhood_hash`](fn.neighbourhood_hash.html). The result borrows from the engine, so it is only valid until the next computation.
+    pub fn neighbourhood_hash<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+        &mut self,
+        graph: Graph<N, E, Ty, Ix>,
+        n_iters: usize,
+    ) -> &[Vec<u64>] {
+        let buffers = (
+            std::mem::take(&mut self.labels_buf),
+            std::mem::take(&mut self.new_labels_buf),
+            Some(self.subgraph_buf.take().unwrap_or_default()),
+        );
+        let mut wrap = GraphWrapper::with_buffers(graph, 42, n_iters, false, buffers);
+        wrap.run();
+        (self.labels_buf, self.new_labels_buf, self.subgraph_buf) = wrap.into_buffers();
+        self.subgraph_buf.as_deref().unwrap()
+    }
+}
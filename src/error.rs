@@ -0,0 +1,121 @@
+use crate::config::LimitKind;
+use crate::context::WlContext;
+use std::fmt;
+use std::io;
+
+/// Errors that can occur while loading graphs or combining results.
+#[derive(Debug)]
+pub enum WlError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The contents of the file could not be parsed. `line` is 1-based.
+    Parse { line: usize, message: String },
+    /// Results from two different [`WlContext`](struct.WlContext.html)s were combined. Their colours are not comparable.
+    ContextMismatch { left: WlContext, right: WlContext },
+    /// The graph has too many nodes to run WL of this dimension in memory.
+    TooLarge { nodes: usize, dimension: usize },
+    /// The graph has more than one edge between the same two nodes, which the [`MultigraphPolicy`](enum.MultigraphPolicy.html) rejects. `source` and `target` are the indices of the endpoints.
+    ParallelEdges { source: usize, target: usize },
+    /// The graph has an edge from a node to itself, which the [`SelfLoopPolicy`](enum.SelfLoopPolicy.html) rejects. `node` is its index.
+    SelfLoop { node: usize },
+    /// A run was rolled back further than the iterations it kept. `available` is how many it can go back.
+    RollbackTooFar { requested: usize, available: usize },
+    /// A run was continued with `resume` before it had started.
+    NotRun,
+    /// An edge of a graph given as columns of endpoints is not valid, for example because an endpoint is not a node. `index` is its row.
+    InvalidEdge { index: usize, message: String },
+    /// The graph exceeded one of the [`WlLimits`](struct.WlLimits.html), or a file declared more nodes than the readers accept (see [`graph_from_file`](fn.graph_from_file.html)). `observed` is its size, or for the label hashes the total the run would have reached with its next iteration, and `iterations` is the number of refinement iterations it completed before stopping.
+    LimitExceeded {
+        which: LimitKind,
+        limit: usize,
+        observed: usize,
+        iterations: usize,
+    },
+    /// Bytes could not be decoded, for example a [`Certificate`](struct.Certificate.html) that was cut short. `offset` is the position of the first byte that could not be used.
+    Decode { offset: usize, message: String },
+}
+
+impl WlError {
+    pub(crate) fn parse(line: usize, message: impl ToString) -> Self {
+        WlError::Parse {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for WlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WlError::Io(error) => write!(f, "I/O error: {}", error),
+            WlError::Parse { line, message } => {
+                write!(f, "parse error on line {}: {}", line, message)
+            }
+            WlError::ContextMismatch { left, right } => write!(
+                f,
+                "colours from different contexts are not comparable: {:?} and {:?}",
+                left, right
+            ),
+            WlError::TooLarge { nodes, dimension } => write!(
+                f,
+                "a graph with {} nodes is too large for {}-dimensional WL",
+                nodes, dimension
+            ),
+            WlError::ParallelEdges { source, target } => write!(
+                f,
+                "parallel edges between nodes {} and {} are not allowed",
+                source, target
+            ),
+            WlError::SelfLoop { node } => write!(f, "self-loop at node {} is not allowed", node),
+            WlError::RollbackTooFar {
+                requested,
+                available,
+            } => write!(
+                f,
+                "cannot roll back {} iterations, only {} are kept",
+                requested, available
+            ),
+            WlError::NotRun => write!(f, "cannot resume a run that has not started"),
+            WlError::InvalidEdge { index, message } => {
+                write!(f, "invalid edge in row {}: {}", index, message)
+            }
+            WlError::LimitExceeded {
+                which,
+                limit,
+                observed,
+                iterations,
+            } => write!(
+                f,
+                "{} limit of {} exceeded with {} after {} iterations",
+                which, limit, observed, iterations
+            ),
+            WlError::Decode { offset, message } => {
+                write!(f, "decode error at byte {}: {}", offset, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WlError::Io(error) => Some(error),
+            WlError::Parse { .. }
+            | WlError::ContextMismatch { .. }
+            | WlError::TooLarge { .. }
+            | WlError::ParallelEdges { .. }
+            | WlError::SelfLoop { .. }
+            | WlError::RollbackTooFar { .. }
+            | WlError::NotRun
+            | WlError::InvalidEdge { .. }
+            | WlError::LimitExceeded { .. }
+            | WlError::Decode { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for WlError {
+    fn from(error: io::Error) -> Self {
+        WlError::Io(error)
+    }
+}
@@ -0,0 +1,161 @@
+use crate::graphwrapper::GraphWrapper;
+use crate::transform::structure;
+use petgraph::graph::IndexType;
+use petgraph::{EdgeType, Graph};
+use std::collections::HashMap;
+use twox_hash::XxHash64;
+
+/// Build a feature vector of fixed length `dim` from the labels of the first `h_iters` iterations, using the hashing trick: every label of every node is counted in bucket `label % dim`. Unlike a vocabulary of colours this never grows, so it suits online learning, at the cost of occasional collisions between colours.
+///
+/// Panics if `dim` is zero.
+pub fn hashed_feature_vector<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    h_iters: usize,
+    dim: usize,
+) -> Vec<f64> {
+    feature_vector(graph, h_iters, dim, false)
+}
+
+/// Like [`hashed_feature_vector`](fn.hashed_feature_vector.html), but every label adds either +1 or -1 to its bucket, depending on a second hash of the label. Colliding colours then tend to cancel out instead of piling up, which keeps inner products between vectors unbiased.
+///
+/// Panics if `dim` is zero.
+pub fn signed_hashed_feature_vector<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    h_iters: usize,
+    dim: usize,
+) -> Vec<f64> {
+    feature_vector(graph, h_iters, dim, true)
+}
+
+fn feature_vector<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    h_iters: usize,
+    dim: usize,
+    signed: bool,
+) -> Vec<f64> {
+    assert!(dim > 0, "The feature vector needs at least one dimension");
+    let mut wrap = GraphWrapper::new(graph, 42, h_iters, false, true);
+    wrap.run();
+    let mut features = vec![0.0; dim];
+    for &label in wrap.subgraphs.unwrap().iter().flatten() {
+        // Use a different seed than the labels themselves, so the sign is independent of the bucket
+        let sign = if signed && XxHash64::oneshot(7, &label.to_ne_bytes()) & 1 == 1 {
+            -1.0
+        } else {
+            1.0
+        };
+        features[(label % dim as u64) as usize] += sign;
+    }
+    features
+}
+
+/// Compute the pairwise distances between all graphs in `graphs`, as the L1 distance between their colour histograms. A graph's histogram counts every label of every node over the first `n_iters` iterations, as in the WL subtree kernel. The result is a symmetric `n x n` matrix with zeros on the diagonal. With the `rayon` feature enabled, the pairs are computed in parallel.
+pub fn wl_distance_matrix<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graphs: &[Graph<N, E, Ty, Ix>],
+    n_iters: usize,
+) -> Vec<Vec<f64>> {
+    let histograms: Vec<_> = graphs
+        .iter()
+        .map(|graph| colour_histogram(graph, n_iters, 42))
+        .collect();
+    let row = |i: usize| -> Vec<f64> {
+        (i + 1..histograms.len())
+            .map(|j| l1_distance(&histograms[i], &histograms[j]))
+            .collect()
+    };
+
+    // Only compute the upper triangle, then mirror it
+    #[cfg(feature = "rayon")]
+    let upper: Vec<Vec<f64>> = {
+        use rayon::prelude::*;
+        (0..histograms.len()).into_par_iter().map(row).collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let upper: Vec<Vec<f64>> = (0..histograms.len()).map(row).collect();
+
+    let mut matrix = vec![vec![0.0; graphs.len()]; graphs.len()];
+    for (i, distances) in upper.into_iter().enumerate() {
+        for (offset, distance) in distances.into_iter().enumerate() {
+            matrix[i][i + 1 + offset] = distance;
+            matrix[i + 1 + offset][i] = distance;
+        }
+    }
+    matrix
+}
+
+/// Summarise a graph in `n_hashes` MinHash values for near-duplicate detection. The graph is refined until stable, and the labels of all nodes over all iterations form its set of WL features, as in the subtree kernel. Each value is the minimum of these features under a different hash function, seeded with `seed`, `seed + 1` and so on. The chance that two graphs agree on a value is the Jaccard similarity of their feature sets, which [`lsh_similarity`](fn.lsh_similarity.html) estimates. Isomorphic graphs agree on all values, and graphs with a small edit still share most of their features.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+/// let h = UnGraph::<(), ()>::from_edges([(3, 1), (1, 2), (2, 3), (3, 0)]);
+/// let (g, h) = (wl_isomorphism::invariant_lsh(g, 64, 0), wl_isomorphism::invariant_lsh(h, 64, 0));
+/// assert_eq!(wl_isomorphism::lsh_similarity(&g, &h), 1.0);
+/// ```
+///
+/// Graphs without nodes have no features, and get `u64::MAX` for every value.
+pub fn invariant_lsh<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    n_hashes: usize,
+    seed: u64,
+) -> Vec<u64> {
+    if graph.node_count() == 0 {
+        return vec![u64::MAX; n_hashes];
+    }
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, true);
+    wrap.run();
+    let mut features: Vec<u64> = wrap.subgraphs.unwrap().into_iter().flatten().collect();
+    features.sort_unstable();
+    features.dedup();
+    (0..n_hashes as u64)
+        .map(|offset| {
+            features
+                .iter()
+                .map(|feature| XxHash64::oneshot(seed.wrapping_add(offset), &feature.to_ne_bytes()))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+/// The fraction of positions at which two results of [`invariant_lsh`](fn.invariant_lsh.html) agree, an estimate of the Jaccard similarity of the graphs' WL features. Graphs are near-duplicates when more than half of the values agree. Both should be computed with the same number of hashes and the same seed.
+///
+/// Panics if the slices differ in length or are empty.
+pub fn lsh_similarity(a: &[u64], b: &[u64]) -> f64 {
+    assert_eq!(
+        a.len(),
+        b.len(),
+        "Both graphs need the same number of hashes"
+    );
+    assert!(!a.is_empty(), "The similarity needs at least one hash");
+    let matching = a.iter().zip(b).filter(|(a, b)| a == b).count();
+    matching as f64 / a.len() as f64
+}
+
+// Count the labels over all nodes and iterations of the structure of the graph
+pub(crate) fn colour_histogram<N, E, Ty: EdgeType, Ix: IndexType>(
+    graph: &Graph<N, E, Ty, Ix>,
+    n_iters: usize,
+    seed: u64,
+) -> HashMap<u64, usize> {
+    let mut wrap = GraphWrapper::new(structure(graph), seed, n_iters, false, true);
+    wrap.run();
+    let mut histogram = HashMap::new();
+    for &label in wrap.subgraphs.unwrap().iter().flatten() {
+        *histogram.entry(label).or_insert(0) += 1;
+    }
+    histogram
+}
+
+fn l1_distance(left: &HashMap<u64, usize>, right: &HashMap<u64, usize>) -> f64 {
+    let mut distance = 0;
+    for (label, &count) in left {
+        distance += count.abs_diff(right.get(label).copied().unwrap_or(0));
+    }
+    for (label, &count) in right {
+        if !left.contains_key(label) {
+            distance += count;
+        }
+    }
+    distance as f64
+}
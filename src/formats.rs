@@ -0,0 +1,631 @@
+use crate::config::LimitKind;
+use crate::error::WlError;
+use petgraph::graph::IndexType;
+use petgraph::graph::{DiGraph, NodeIndex, UnGraph};
+use petgraph::{EdgeType, Graph};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, PartialEq)]
+enum Format {
+    Gml,
+    Dimacs,
+    GraphMl,
+    Edgelist,
+}
+
+/// Read an undirected graph from a file in GML, DIMACS, GraphML or NetworkX edgelist format, detecting the format from the first non-comment line.
+/// `graph [` or `node [` means GML, `p edge` means DIMACS, a line starting with `<` means GraphML, and anything else is read as an edgelist, as in [`ungraph_from_edgelist`](fn.ungraph_from_edgelist.html).
+/// Leading whitespace and a byte order mark are ignored. Node and edge attributes are discarded, and nodes are numbered in order of appearance (for DIMACS, node `i` becomes index `i - 1`).
+///
+/// So that a small file cannot make it allocate a huge graph, a file may declare or imply at most one node per byte, or 2^20 nodes if it is smaller. The node count of a DIMACS or Pajek header and the highest id in an edgelist are checked against this before any node is added, and a file over it is rejected with [`WlError::LimitExceeded`](enum.WlError.html#variant.LimitExceeded) for [`LimitKind::Nodes`](enum.LimitKind.html#variant.Nodes).
+pub fn graph_from_file(path: impl AsRef<Path>) -> Result<UnGraph<(), ()>, WlError> {
+    let text = fs::read_to_string(path)?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+    match detect_format(text) {
+        Format::Gml => parse_gml(text),
+        Format::Dimacs => parse_dimacs(text),
+        Format::GraphMl => parse_graphml(text),
+        Format::Edgelist => parse_edgelist(text),
+    }
+}
+
+/// Read an undirected graph from a dense adjacency matrix, as written by e.g. MATLAB, scipy or R. Entries are separated by whitespace and/or commas, one row per line, and lines starting with `#` are skipped.
+/// Every non-zero entry above the diagonal is an edge; the diagonal and everything below it are ignored, so the matrix does not need to be symmetric.
+pub fn ungraph_from_adjacency_matrix(path: impl AsRef<Path>) -> Result<UnGraph<(), ()>, WlError> {
+    graph_from_matrix(&fs::read_to_string(path)?, |i, j| i < j)
+}
+
+/// Read a directed graph from a dense adjacency matrix, in the same format as [`ungraph_from_adjacency_matrix`](fn.ungraph_from_adjacency_matrix.html). A non-zero entry in row `i` and column `j` is an edge from `i` to `j`; the diagonal is ignored.
+pub fn digraph_from_adjacency_matrix(path: impl AsRef<Path>) -> Result<DiGraph<(), ()>, WlError> {
+    graph_from_matrix(&fs::read_to_string(path)?, |i, j| i != j)
+}
+
+// Read a square matrix, adding an edge for the non-zero entries at positions that `keep` selects
+fn graph_from_matrix<Ty: EdgeType>(
+    text: &str,
+    keep: impl Fn(usize, usize) -> bool,
+) -> Result<Graph<(), (), Ty>, WlError> {
+    let mut rows = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_start_matches('\u{feff}').trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let row = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                entry
+                    .parse::<f64>()
+                    .map(|value| value != 0.0)
+                    .map_err(|_| WlError::parse(i + 1, format!("invalid entry {}", entry)))
+            })
+            .collect::<Result<Vec<bool>, _>>()?;
+        rows.push((i + 1, row));
+    }
+    let n = rows.len();
+    let mut graph = Graph::with_capacity(n, 0);
+    for _ in 0..n {
+        graph.add_node(());
+    }
+    for (i, (line, row)) in rows.into_iter().enumerate() {
+        if row.len() != n {
+            return Err(WlError::parse(
+                line,
+                format!(
+                    "expected {} entries in a {0}x{0} matrix, found {}",
+                    n,
+                    row.len()
+                ),
+            ));
+        }
+        for (j, _) in row.into_iter().enumerate().filter(|(_, edge)| *edge) {
+            if keep(i, j) {
+                graph.add_edge(NodeIndex::new(i), NodeIndex::new(j), ());
+            }
+        }
+    }
+    Ok(graph)
+}
+
+/// Read an undirected, weighted graph from a Pajek `.net` file. The node weight is the x coordinate of a vertex (0 if it has none), and the edge weight is the optional third column of an edge (1 if absent). The x coordinate follows the optional label of a vertex, and a quoted label is always read as the label, even if it is a number like `"2"`. Edges from the `*Edges`, `*Arcs`, `*Edgeslist` and `*Arcslist` sections are all read as undirected edges. Lines starting with `%` are comments. A `*Vertices` count over the node limit of [`graph_from_file`](fn.graph_from_file.html) is an error.
+///
+/// Note that `f64` is not `Ord`, so to compute invariants, first map the weights to something that is, e.g. `graph.map(|_, _| (), |_, _| ())`.
+pub fn ungraph_from_pajek(path: impl AsRef<Path>) -> Result<UnGraph<f64, f64>, WlError> {
+    pajek_graph(&fs::read_to_string(path)?)
+}
+
+/// Read a directed, weighted graph from a Pajek `.net` file, like [`ungraph_from_pajek`](fn.ungraph_from_pajek.html). Arcs become directed edges, and every (undirected) edge from the `*Edges` sections becomes a pair of opposite arcs.
+pub fn digraph_from_pajek(path: impl AsRef<Path>) -> Result<DiGraph<f64, f64>, WlError> {
+    pajek_graph(&fs::read_to_string(path)?)
+}
+
+/// Write the edges of a graph to `path` as a weighted edgelist, one `source target weight` line per edge, with the nodes numbered by their index and the weight written with its `Display` implementation. This is the format of NetworkX's `write_weighted_edgelist`, and can be read back with [`weighted_ungraph_from_edgelist`](fn.weighted_ungraph_from_edgelist.html). Missing parent directories are created.
+///
+/// Note that nodes without edges are not written, so they are only recovered when a node with a higher index has an edge.
+pub fn write_edgelist_weighted<N, E: Display, Ty: EdgeType, Ix: IndexType>(
+    graph: &Graph<N, E, Ty, Ix>,
+    path: impl AsRef<Path>,
+) -> Result<(), WlError> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut w = BufWriter::new(File::create(path)?);
+    for edge in graph.raw_edges() {
+        writeln!(
+            w,
+            "{} {} {}",
+            edge.source().index(),
+            edge.target().index(),
+            edge.weight
+        )?;
+    }
+    w.flush()?;
+    Ok(())
+}
+
+/// Read an undirected graph with edge weights from a weighted edgelist, as written by [`write_edgelist_weighted`](fn.write_edgelist_weighted.html) or NetworkX's `write_weighted_edgelist`: one `source target weight` line per edge. An edge without a weight gets weight 1, and empty lines and lines starting with `#` are skipped. As with [`ungraph_from_edgelist`](fn.ungraph_from_edgelist.html), nodes are numbered by their id, skipped ids become unconnected nodes, and ids over the node limit of [`graph_from_file`](fn.graph_from_file.html) are an error.
+///
+/// Note that `f64` is not `Ord`, so to compute invariants, first map the weights to something that is, e.g. `graph.map(|_, _| (), |_, _| ())`.
+pub fn weighted_ungraph_from_edgelist(path: impl AsRef<Path>) -> Result<UnGraph<(), f64>, WlError> {
+    let text = fs::read_to_string(path)?;
+    let mut edges = Vec::new();
+    let mut nodes = 0;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let mut node = || {
+            fields
+                .next()
+                .and_then(|field| field.parse::<u32>().ok())
+                .ok_or_else(|| WlError::parse(i + 1, "Expected two node ids"))
+        };
+        let (source, target) = (node()?, node()?);
+        nodes = nodes.max((source.max(target) as usize).saturating_add(1));
+        let weight = match fields.next() {
+            Some(field) => field
+                .parse::<f64>()
+                .map_err(|_| WlError::parse(i + 1, format!("Invalid weight {:?}", field)))?,
+            None => 1.0,
+        };
+        edges.push((source, target, weight));
+    }
+    check_node_count(nodes, &text)?;
+    Ok(UnGraph::from_edges(edges))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PajekSection {
+    Preamble,
+    Vertices,
+    Edges,
+    Arcs,
+    EdgesList,
+    ArcsList,
+    Other, // e.g. *Partition or *Matrix, which are skipped
+}
+
+fn pajek_graph<Ty: EdgeType>(text: &str) -> Result<Graph<f64, f64, Ty>, WlError> {
+    let mut graph = Graph::default();
+    let mut section = PajekSection::Preamble;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_start_matches('\u{feff}').trim();
+        if line.is_empty() || line.starts_with('%') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('*') {
+            let mut fields = header.split_whitespace();
+            let name = fields.next().unwrap_or("").to_lowercase();
+            section = match name.as_str() {
+                "vertices" => {
+                    let n = fields
+                        .next()
+                        .and_then(|n| n.parse::<usize>().ok())
+                        .ok_or_else(|| WlError::parse(i + 1, "*Vertices without a count"))?;
+                    check_node_count(n, text)?;
+                    graph = Graph::with_capacity(n, 0);
+                    for _ in 0..n {
+                        graph.add_node(0.0);
+                    }
+                    PajekSection::Vertices
+                }
+                "edges" => PajekSection::Edges,
+                "arcs" => PajekSection::Arcs,
+                "edgeslist" => PajekSection::EdgesList,
+                "arcslist" => PajekSection::ArcsList,
+                _ => PajekSection::Other,
+            };
+            continue;
+        }
+        let tokens = pajek_fields(line).map_err(|message| WlError::parse(i + 1, message))?;
+        let fields: Vec<&str> = tokens.iter().map(|&(field, _)| field).collect();
+        let n = graph.node_count();
+        let node = |field: &str| match field.parse::<usize>() {
+            Ok(id) if id >= 1 && id <= n => Ok(NodeIndex::new(id - 1)),
+            _ => Err(WlError::parse(i + 1, format!("invalid vertex {}", field))),
+        };
+        let number = |field: &str| {
+            field
+                .parse::<f64>()
+                .map_err(|_| WlError::parse(i + 1, format!("invalid number {}", field)))
+        };
+        match section {
+            PajekSection::Preamble => {
+                return Err(WlError::parse(i + 1, "expected a *Vertices section"))
+            }
+            PajekSection::Other => {}
+            PajekSection::Vertices => {
+                let node = node(fields[0])?;
+                // The label is optional, the x coordinate follows it. A quoted field is always the label, even if it is a number
+                let label = match tokens.get(1) {
+                    Some(&(field, quoted)) => quoted || field.parse::<f64>().is_err(),
+                    None => false,
+                };
+                let x = fields
+                    .get(if label { 2 } else { 1 })
+                    .filter(|field| field.parse::<f64>().is_ok());
+                graph[node] = x.map_or(Ok(0.0), |x| number(x))?;
+            }
+            PajekSection::Edges | PajekSection::Arcs => {
+                let (Some(source), Some(target)) = (fields.first(), fields.get(1)) else {
+                    return Err(WlError::parse(i + 1, "expected two vertices"));
+                };
+                let (source, target) = (node(source)?, node(target)?);
+                let weight = fields.get(2).map_or(Ok(1.0), |w| number(w))?;
+                graph.add_edge(source, target, weight);
+                if section == PajekSection::Edges && Ty::is_directed() && source != target {
+                    graph.add_edge(target, source, weight);
+                }
+            }
+            PajekSection::EdgesList | PajekSection::ArcsList => {
+                let source = node(fields[0])?;
+                for target in &fields[1..] {
+                    let target = node(target)?;
+                    graph.add_edge(source, target, 1.0);
+                    if section == PajekSection::EdgesList && Ty::is_directed() && source != target {
+                        graph.add_edge(target, source, 1.0);
+                    }
+                }
+            }
+        }
+    }
+    Ok(graph)
+}
+
+// Split a Pajek line on whitespace, keeping quoted labels together (without the quotes), and note which fields were quoted
+fn pajek_fields(line: &str) -> Result<Vec<(&str, bool)>, &'static str> {
+    let mut fields = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').ok_or("unterminated label")?;
+            fields.push((&quoted[..end], true));
+            rest = &quoted[end + 1..];
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            fields.push((&rest[..end], false));
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+    Ok(fields)
+}
+
+fn detect_format(text: &str) -> Format {
+    let Some(line) = text
+        .lines()
+        .map(|line| line.trim_start_matches('\u{feff}').trim())
+        .find(|line| !is_comment(line))
+    else {
+        return Format::Edgelist;
+    };
+    let mut tokens = line.split_whitespace();
+    let first = tokens.next().unwrap_or("");
+    if line.starts_with('<') {
+        Format::GraphMl
+    } else if matches!(first.trim_end_matches('['), "graph" | "node") {
+        Format::Gml
+    } else if first == "p" && tokens.next() == Some("edge") {
+        Format::Dimacs
+    } else {
+        Format::Edgelist
+    }
+}
+
+// The most nodes a file of this size may declare or imply, see graph_from_file
+fn check_node_count(nodes: usize, text: &str) -> Result<(), WlError> {
+    let limit = text.len().max(1 << 20);
+    if nodes > limit {
+        return Err(WlError::LimitExceeded {
+            which: LimitKind::Nodes,
+            limit,
+            observed: nodes,
+            iterations: 0,
+        });
+    }
+    Ok(())
+}
+
+// Blank lines and the comment styles of the edgelist, GML and DIMACS formats
+fn is_comment(line: &str) -> bool {
+    line.is_empty() || line.starts_with('#') || line == "c" || line.starts_with("c ")
+}
+
+// Maps the node ids used in a file to consecutive node indices
+#[derive(Default)]
+struct NodeIds {
+    graph: UnGraph<(), ()>,
+    ids: HashMap<String, NodeIndex>,
+}
+
+impl NodeIds {
+    fn add(&mut self, id: &str, line: usize) -> Result<(), WlError> {
+        if self.ids.contains_key(id) {
+            return Err(WlError::parse(line, format!("duplicate node id {}", id)));
+        }
+        let index = self.graph.add_node(());
+        self.ids.insert(id.to_string(), index);
+        Ok(())
+    }
+
+    fn add_edge(&mut self, source: &str, target: &str, line: usize) -> Result<(), WlError> {
+        let lookup = |id: &str| {
+            self.ids
+                .get(id)
+                .copied()
+                .ok_or_else(|| WlError::parse(line, format!("unknown node id {}", id)))
+        };
+        let (source, target) = (lookup(source)?, lookup(target)?);
+        self.graph.add_edge(source, target, ());
+        Ok(())
+    }
+}
+
+// Read the first two columns of every line as node ids, skipping blank lines and lines starting with `#`. Edge data written by
+// NetworkX, like `{'weight': 1.0}`, is ignored
+pub(crate) fn parse_edgelist<Ty: EdgeType>(text: &str) -> Result<Graph<(), (), Ty>, WlError> {
+    let mut edges = Vec::new();
+    let mut nodes = 0;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let mut node = || -> Result<u32, WlError> {
+            let field = fields
+                .next()
+                .ok_or_else(|| WlError::parse(i + 1, "expected two node ids"))?;
+            field
+                .parse()
+                .map_err(|_| WlError::parse(i + 1, format!("invalid node id {}", field)))
+        };
+        let (source, target) = (node()?, node()?);
+        nodes = nodes.max((source.max(target) as usize).saturating_add(1));
+        edges.push((source, target));
+    }
+    check_node_count(nodes, text)?;
+    Ok(Graph::from_edges(edges))
+}
+
+fn parse_dimacs(text: &str) -> Result<UnGraph<(), ()>, WlError> {
+    let mut graph: Option<UnGraph<(), ()>> = None;
+    for (i, line) in text.lines().enumerate() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let parse = |field: &str| {
+            field
+                .parse::<usize>()
+                .map_err(|_| WlError::parse(i + 1, format!("invalid number {}", field)))
+        };
+        match fields.as_slice() {
+            [] | ["c", ..] => {}
+            ["p", "edge", n, m] => {
+                let (n, m) = (parse(n)?, parse(m)?);
+                check_node_count(n, text)?;
+                // Every edge takes a line of its own, so there cannot be more edges than bytes either
+                let mut g = UnGraph::with_capacity(n, m.min(text.len()));
+                for _ in 0..n {
+                    g.add_node(());
+                }
+                graph = Some(g);
+            }
+            ["e", u, v] => {
+                let g = graph
+                    .as_mut()
+                    .ok_or_else(|| WlError::parse(i + 1, "edge before problem line"))?;
+                let (u, v) = (parse(u)?, parse(v)?);
+                if u == 0 || v == 0 || u > g.node_count() || v > g.node_count() {
+                    return Err(WlError::parse(i + 1, "node id out of range"));
+                }
+                g.add_edge(NodeIndex::new(u - 1), NodeIndex::new(v - 1), ());
+            }
+            _ => return Err(WlError::parse(i + 1, format!("unexpected line {}", line))),
+        }
+    }
+    graph.ok_or_else(|| WlError::parse(1, "missing problem line"))
+}
+
+// A GML token with the line it starts on
+#[derive(Debug, PartialEq)]
+enum GmlToken<'a> {
+    Open,
+    Close,
+    Word(&'a str),
+}
+
+fn gml_tokens(text: &str) -> Result<Vec<(usize, GmlToken<'_>)>, WlError> {
+    let mut tokens = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let mut rest = line.trim_start();
+        while !rest.is_empty() {
+            if rest.starts_with('#') {
+                break;
+            } else if let Some(r) = rest.strip_prefix('[') {
+                tokens.push((i + 1, GmlToken::Open));
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix(']') {
+                tokens.push((i + 1, GmlToken::Close));
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix('"') {
+                // Strings do not contain quotes in GML, they are escaped as &quot;
+                let end = r
+                    .find('"')
+                    .ok_or_else(|| WlError::parse(i + 1, "unterminated string"))?;
+                tokens.push((i + 1, GmlToken::Word(&r[..end])));
+                rest = &r[end + 1..];
+            } else {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '[' || c == ']')
+                    .unwrap_or(rest.len());
+                tokens.push((i + 1, GmlToken::Word(&rest[..end])));
+                rest = &rest[end..];
+            }
+            rest = rest.trim_start();
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_gml(text: &str) -> Result<UnGraph<(), ()>, WlError> {
+    let tokens = gml_tokens(text)?;
+    let mut nodes = NodeIds::default();
+    let mut edges = Vec::new();
+    let mut pos = 0;
+    // The contents of `graph [ ... ]` are read as if they were at the top level
+    while pos < tokens.len() {
+        let (line, key) = match &tokens[pos] {
+            (_, GmlToken::Close) => {
+                pos += 1;
+                continue;
+            }
+            (line, GmlToken::Open) => return Err(WlError::parse(*line, "unexpected [")),
+            (line, GmlToken::Word(key)) => (*line, *key),
+        };
+        pos += 1;
+        match (key, tokens.get(pos)) {
+            ("graph", Some((_, GmlToken::Open))) => pos += 1,
+            ("node" | "edge", Some((_, GmlToken::Open))) => {
+                let (fields, end) = gml_list(&tokens, pos + 1)?;
+                pos = end;
+                let field = |name: &str| {
+                    fields
+                        .iter()
+                        .find(|(key, _)| *key == name)
+                        .map(|(_, value)| *value)
+                        .ok_or_else(|| WlError::parse(line, format!("{} without {}", key, name)))
+                };
+                if key == "node" {
+                    nodes.add(field("id")?, line)?;
+                } else {
+                    edges.push((field("source")?, field("target")?, line));
+                }
+            }
+            (_, Some((_, GmlToken::Open))) => pos = gml_list(&tokens, pos + 1)?.1,
+            (_, Some((_, GmlToken::Word(_)))) => pos += 1,
+            _ => return Err(WlError::parse(line, format!("{} without value", key))),
+        }
+    }
+    // Edges may refer to nodes that are declared later on
+    for (source, target, line) in edges {
+        nodes.add_edge(source, target, line)?;
+    }
+    Ok(nodes.graph)
+}
+
+type GmlFields<'a> = Vec<(&'a str, &'a str)>;
+
+// Read the key value pairs of a list starting at `pos`, skipping nested lists. Returns the pairs and the position after the closing bracket.
+fn gml_list<'a>(
+    tokens: &[(usize, GmlToken<'a>)],
+    mut pos: usize,
+) -> Result<(GmlFields<'a>, usize), WlError> {
+    let mut fields = Vec::new();
+    let mut depth = 0;
+    let mut key = None;
+    while let Some((_, token)) = tokens.get(pos) {
+        pos += 1;
+        match token {
+            GmlToken::Open => depth += 1,
+            GmlToken::Close if depth == 0 => return Ok((fields, pos)),
+            GmlToken::Close => depth -= 1,
+            GmlToken::Word(word) if depth == 0 => match key.take() {
+                None => key = Some(*word),
+                Some(k) => fields.push((k, *word)),
+            },
+            GmlToken::Word(_) => {}
+        }
+        if depth > 0 {
+            key = None;
+        }
+    }
+    let line = tokens.last().map_or(1, |(line, _)| *line);
+    Err(WlError::parse(line, "unclosed ["))
+}
+
+fn parse_graphml(text: &str) -> Result<UnGraph<(), ()>, WlError> {
+    let mut nodes = NodeIds::default();
+    let mut edges = Vec::new();
+    let mut rest = text;
+    let line_of = |rest: &str| text[..text.len() - rest.len()].matches('\n').count() + 1;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        let line = line_of(rest);
+        // Comments may contain '>', so they are skipped as a whole
+        let end_marker = if rest.starts_with("<!--") { "-->" } else { ">" };
+        let end = rest
+            .find(end_marker)
+            .ok_or_else(|| WlError::parse(line, "unterminated tag"))?;
+        let tag = &rest[1..end];
+        rest = &rest[end + end_marker.len()..];
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("");
+        let attribute = |attr: &str| {
+            xml_attribute(tag, attr)
+                .ok_or_else(|| WlError::parse(line, format!("<{}> without {}", name, attr)))
+        };
+        match name {
+            "node" => nodes.add(attribute("id")?, line)?,
+            "edge" => edges.push((attribute("source")?, attribute("target")?, line)),
+            _ => {}
+        }
+    }
+    for (source, target, line) in edges {
+        nodes.add_edge(source, target, line)?;
+    }
+    Ok(nodes.graph)
+}
+
+// Find the value of `name="value"` or `name='value'` in the contents of a tag
+fn xml_attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(pos) = rest.find(name) {
+        let preceded_by_space = rest[..pos].ends_with(char::is_whitespace);
+        let after = rest[pos + name.len()..].trim_start();
+        rest = &rest[pos + name.len()..];
+        if !preceded_by_space {
+            continue;
+        }
+        let Some(after) = after.strip_prefix('=') else {
+            continue;
+        };
+        let after = after.trim_start();
+        let quote = after.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = &after[1..];
+        return value.find(quote).map(|end| &value[..end]);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detection() {
+        assert_eq!(detect_format("graph [\n]"), Format::Gml);
+        assert_eq!(detect_format("# comment\n  node [ id 0 ]"), Format::Gml);
+        assert_eq!(detect_format("c comment\np edge 3 2"), Format::Dimacs);
+        assert_eq!(
+            detect_format("\n\t<?xml version=\"1.0\"?>"),
+            Format::GraphMl
+        );
+        assert_eq!(detect_format("\u{feff}graph["), Format::Gml);
+        assert_eq!(detect_format("0 1\n1 2"), Format::Edgelist);
+        assert_eq!(detect_format(""), Format::Edgelist);
+    }
+
+    #[test]
+    fn attributes() {
+        let tag = r#"edge id="e0" source='n1' target = "n2"/"#;
+        assert_eq!(xml_attribute(tag, "id"), Some("e0"));
+        assert_eq!(xml_attribute(tag, "source"), Some("n1"));
+        assert_eq!(xml_attribute(tag, "target"), Some("n2"));
+        assert_eq!(xml_attribute(tag, "weight"), None);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(
+            parse_dimacs("p edge 2 1\ne 1 3"),
+            Err(WlError::Parse { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse_gml("graph [\n node [ id 0 ]\n edge [ source 0 target 1 ]\n]"),
+            Err(WlError::Parse { line: 3, .. })
+        ));
+        assert!(matches!(
+            parse_edgelist::<petgraph::Undirected>("0 1\n1 x"),
+            Err(WlError::Parse { line: 2, .. })
+        ));
+    }
+}
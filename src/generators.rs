@@ -0,0 +1,350 @@
+//! Random graphs, graphs built to a specification and random perturbations of graphs, for experiments and benchmarks. All of them are deterministic for a given seed.
+
+use petgraph::graph::{IndexType, NodeIndex, UnGraph};
+use petgraph::{EdgeType, Graph};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// A small seedable random number generator (SplitMix64). We implement it ourselves so that the generated graphs for a given seed never change with a dependency update.
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        SplitMix64(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // A uniform number in 0..bound
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as usize
+    }
+
+    // A uniform number in [0, 1)
+    pub(crate) fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub(crate) fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Generate an Erdős–Rényi random graph on `n` nodes, where each of the possible edges is present independently with probability `p`.
+pub fn gnp(n: usize, p: f64, seed: u64) -> UnGraph<(), ()> {
+    let mut rng = SplitMix64::new(seed);
+    let mut graph = empty(n);
+    for i in 0..n {
+        for j in 0..i {
+            if rng.unit() < p {
+                graph.add_edge(NodeIndex::new(j), NodeIndex::new(i), ());
+            }
+        }
+    }
+    graph
+}
+
+/// Generate a Barabási–Albert preferential attachment graph on `n` nodes. Starting from `m` unconnected nodes, every new node is connected to `m` distinct existing nodes, chosen with probability proportional to their degree. The result has `m * (n - m)` edges.
+///
+/// Panics unless `1 <= m < n`.
+pub fn barabasi_albert(n: usize, m: usize, seed: u64) -> UnGraph<(), ()> {
+    assert!(m >= 1 && m < n, "Barabási–Albert graphs need 1 <= m < n");
+    let mut rng = SplitMix64::new(seed);
+    let mut graph = empty(n);
+    // Every node appears once per incident edge, so sampling from this list is proportional to degree
+    let mut repeated_nodes: Vec<usize> = Vec::with_capacity(2 * m * (n - m));
+    let mut targets: Vec<usize> = (0..m).collect();
+    for source in m..n {
+        for &target in &targets {
+            graph.add_edge(NodeIndex::new(source), NodeIndex::new(target), ());
+        }
+        repeated_nodes.extend(&targets);
+        repeated_nodes.extend(std::iter::repeat_n(source, m));
+
+        let mut chosen = HashSet::with_capacity(m);
+        targets.clear();
+        while targets.len() < m {
+            let candidate = repeated_nodes[rng.below(repeated_nodes.len())];
+            if chosen.insert(candidate) {
+                targets.push(candidate);
+            }
+        }
+    }
+    graph
+}
+
+/// Generate a random `d`-regular graph on `n` nodes, using the pairing model: every node gets `d` stubs, which are randomly paired into edges. Pairs that would form a self-loop or parallel edge are re-paired, and if that gets stuck the construction starts over. Regular graphs are exactly the class where [`invariant`](../fn.invariant.html) cannot distinguish anything (all `d`-regular graphs on `n` nodes get the same hash), while [`invariant_2wl`](../fn.invariant_2wl.html) usually can.
+///
+/// Panics if `n * d` is odd or `d >= n`, as no such graph exists.
+pub fn random_regular(n: usize, d: usize, seed: u64) -> UnGraph<(), ()> {
+    assert!(
+        (n * d).is_multiple_of(2),
+        "A d-regular graph on n nodes needs n * d to be even"
+    );
+    assert!(d < n || n == 0, "A d-regular graph on n nodes needs d < n");
+    let mut rng = SplitMix64::new(seed);
+    let edges = loop {
+        if let Some(edges) = try_pairing(n, d, &mut rng) {
+            break edges;
+        }
+    };
+    let mut graph = empty(n);
+    for (a, b) in edges {
+        graph.add_edge(NodeIndex::new(a), NodeIndex::new(b), ());
+    }
+    graph
+}
+
+// One attempt of the pairing model, returning None if the remaining stubs cannot be paired anymore
+fn try_pairing(n: usize, d: usize, rng: &mut SplitMix64) -> Option<Vec<(usize, usize)>> {
+    let mut edges = Vec::with_capacity(n * d / 2);
+    let mut present = HashSet::with_capacity(n * d / 2);
+    let mut stubs: Vec<usize> = (0..n)
+        .flat_map(|node| std::iter::repeat_n(node, d))
+        .collect();
+    while !stubs.is_empty() {
+        rng.shuffle(&mut stubs);
+        let mut leftover: BTreeMap<usize, usize> = BTreeMap::new();
+        for pair in stubs.chunks_exact(2) {
+            let (a, b) = (pair[0].min(pair[1]), pair[0].max(pair[1]));
+            if a != b && present.insert((a, b)) {
+                edges.push((a, b));
+            } else {
+                *leftover.entry(a).or_insert(0) += 1;
+                *leftover.entry(b).or_insert(0) += 1;
+            }
+        }
+        // Only retry if at least one valid edge between the leftover stubs remains
+        let nodes: Vec<usize> = leftover.keys().copied().collect();
+        let possible = nodes
+            .iter()
+            .enumerate()
+            .any(|(i, &a)| nodes[i + 1..].iter().any(|&b| !present.contains(&(a, b))));
+        if !leftover.is_empty() && !possible {
+            return None;
+        }
+        stubs = leftover
+            .into_iter()
+            .flat_map(|(node, count)| std::iter::repeat_n(node, count))
+            .collect();
+    }
+    Some(edges)
+}
+
+/// Build a representative graph of a colour structure: `class_sizes[i]` nodes of class `i`, numbered class after class and weighted with their class, and for every `(class_a, class_b, count)` of `inter_class_edges` that many edges between the two classes, or within the class if they are the same. The edges are spread as evenly as possible over the nodes of each class, so when every node can get the same number of them, the classes are exactly the stable colouring of [`colour_classes`](../fn.colour_classes.html), unless two classes have the same structure and merge. That is the case if `count` is a multiple of the least common multiple of the two class sizes, or within a class of size `s`, a multiple of `s`, or of `s / 2` for the last `s / 2` edges of a complete graph on an even `s`.
+/// ```rust
+/// use wl_isomorphism::generators;
+///
+/// // Two centres, each joined to the same three leaves
+/// let graph = generators::graph_from_color_structure(&[2, 3], &[(0, 1, 6)]);
+/// assert_eq!((graph.node_count(), graph.edge_count()), (5, 6));
+/// ```
+///
+/// Panics if a class does not exist, or if more edges are asked for than there are pairs of nodes to connect. Repeated pairs of classes are added up.
+pub fn graph_from_color_structure(
+    class_sizes: &[usize],
+    inter_class_edges: &[(usize, usize, usize)],
+) -> UnGraph<u64, ()> {
+    let mut starts = Vec::with_capacity(class_sizes.len());
+    let mut graph = UnGraph::with_capacity(class_sizes.iter().sum(), 0);
+    for (class, &size) in class_sizes.iter().enumerate() {
+        starts.push(graph.node_count());
+        for _ in 0..size {
+            graph.add_node(class as u64);
+        }
+    }
+    let mut counts: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+    for &(a, b, count) in inter_class_edges {
+        assert!(
+            a < class_sizes.len() && b < class_sizes.len(),
+            "Edges between classes that do not exist"
+        );
+        *counts.entry((a.min(b), a.max(b))).or_insert(0) += count;
+    }
+    for ((a, b), count) in counts {
+        let (size_a, size_b) = (class_sizes[a], class_sizes[b]);
+        let edges = if a == b {
+            // A circulant graph, adding every jump around the class in turn, so that every complete jump adds the same to every degree
+            let pairs = size_a * size_a.saturating_sub(1) / 2;
+            assert!(
+                count <= pairs,
+                "More edges within a class than pairs of its nodes"
+            );
+            (1..=size_a / 2)
+                .flat_map(|jump| {
+                    // Going halfway round reaches every pair twice
+                    let nodes = if 2 * jump == size_a { jump } else { size_a };
+                    (0..nodes).map(move |node| (node, (node + jump) % size_a))
+                })
+                .take(count)
+                .collect::<Vec<_>>()
+        } else {
+            // Edge k joins nodes k mod size_a and k mod size_b, which repeats after their least common multiple; every next round shifts
+            // the second class by one, which gives new pairs until all are used
+            assert!(
+                count <= size_a * size_b,
+                "More edges between two classes than pairs of their nodes"
+            );
+            let round = size_a / gcd(size_a, size_b) * size_b;
+            (0..count)
+                .map(|k| (k % size_a, (k % size_b + k / round) % size_b))
+                .collect()
+        };
+        for (u, v) in edges {
+            graph.add_edge(
+                NodeIndex::new(starts[a] + u),
+                NodeIndex::new(starts[b] + v),
+                (),
+            );
+        }
+    }
+    graph
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn empty(n: usize) -> UnGraph<(), ()> {
+    let mut graph = UnGraph::with_capacity(n, 0);
+    for _ in 0..n {
+        graph.add_node(());
+    }
+    graph
+}
+
+/// Randomly rewire the graph with `n_swaps` double edge swaps, which keep the degree of every node the same. A swap replaces two edges `(a, b)` and `(c, d)` by `(a, d)` and `(c, b)`; for directed graphs this preserves both in- and out-degrees. Swaps that would create a self-loop or a parallel edge are rejected, unless the input graph already contains those. Edge weights move along with their source node. If not enough valid swaps can be found (after `100 * n_swaps` attempts), fewer are made.
+pub fn rewire_edges<N, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    n_swaps: usize,
+    seed: u64,
+) -> Graph<N, E, Ty, Ix> {
+    let directed = graph.is_directed();
+    let (nodes, edges) = graph.into_nodes_edges();
+    let key = |a: usize, b: usize| {
+        if directed || a <= b {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    };
+    let mut endpoints: Vec<(usize, usize)> = edges
+        .iter()
+        .map(|e| (e.source().index(), e.target().index()))
+        .collect();
+    let mut multiplicity: HashMap<(usize, usize), usize> = HashMap::new();
+    for &(a, b) in &endpoints {
+        *multiplicity.entry(key(a, b)).or_insert(0) += 1;
+    }
+    let allow_loops = endpoints.iter().any(|(a, b)| a == b);
+    let allow_parallel = multiplicity.values().any(|&count| count > 1);
+
+    let mut rng = SplitMix64::new(seed);
+    let mut swaps = 0;
+    let mut attempts = 0;
+    while swaps < n_swaps && attempts < n_swaps * 100 && endpoints.len() > 1 {
+        attempts += 1;
+        let i = rng.below(endpoints.len());
+        let j = rng.below(endpoints.len());
+        if i == j {
+            continue;
+        }
+        let (a, b) = endpoints[i];
+        let (mut c, mut d) = endpoints[j];
+        // An undirected edge can be swapped in either orientation
+        if !directed && rng.next_u64() & 1 == 1 {
+            (c, d) = (d, c);
+        }
+        if !allow_loops && (a == d || c == b) {
+            continue;
+        }
+        let (first, second) = (key(a, d), key(c, b));
+        if !allow_parallel
+            && (first == second
+                || multiplicity.contains_key(&first)
+                || multiplicity.contains_key(&second))
+        {
+            continue;
+        }
+        for old in [key(a, b), key(c, d)] {
+            let count = multiplicity.get_mut(&old).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                multiplicity.remove(&old);
+            }
+        }
+        *multiplicity.entry(first).or_insert(0) += 1;
+        *multiplicity.entry(second).or_insert(0) += 1;
+        endpoints[i] = (a, d);
+        endpoints[j] = (c, b);
+        swaps += 1;
+    }
+
+    rebuild(
+        nodes.into_iter().map(|node| node.weight),
+        endpoints
+            .into_iter()
+            .zip(edges)
+            .map(|((a, b), edge)| (a, b, edge.weight)),
+    )
+}
+
+/// Randomly remove a `fraction` (between 0 and 1) of the edges, rounded to the nearest whole number of edges. The nodes are kept, as is the order of the remaining edges.
+///
+/// Panics if `fraction` is not between 0 and 1.
+pub fn drop_edges<N, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    fraction: f64,
+    seed: u64,
+) -> Graph<N, E, Ty, Ix> {
+    assert!(
+        (0.0..=1.0).contains(&fraction),
+        "The fraction of edges to drop must be between 0 and 1"
+    );
+    let (nodes, edges) = graph.into_nodes_edges();
+    let n_drop = (fraction * edges.len() as f64).round() as usize;
+
+    // A partial Fisher-Yates shuffle picks the edges to drop
+    let mut rng = SplitMix64::new(seed);
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    let mut dropped = vec![false; edges.len()];
+    for i in 0..n_drop {
+        let j = i + rng.below(order.len() - i);
+        order.swap(i, j);
+        dropped[order[i]] = true;
+    }
+
+    rebuild(
+        nodes.into_iter().map(|node| node.weight),
+        edges
+            .into_iter()
+            .zip(dropped)
+            .filter(|(_, dropped)| !dropped)
+            .map(|(edge, _)| (edge.source().index(), edge.target().index(), edge.weight)),
+    )
+}
+
+fn rebuild<N, E, Ty: EdgeType, Ix: IndexType>(
+    nodes: impl ExactSizeIterator<Item = N>,
+    edges: impl Iterator<Item = (usize, usize, E)>,
+) -> Graph<N, E, Ty, Ix> {
+    let mut graph = Graph::with_capacity(nodes.len(), 0);
+    for weight in nodes {
+        graph.add_node(weight);
+    }
+    for (a, b, weight) in edges {
+        graph.add_edge(NodeIndex::new(a), NodeIndex::new(b), weight);
+    }
+    graph
+}
@@ -1,10 +1,17 @@
-use petgraph::graph::NodeIndex;
+use crate::config::{AggregationMode, LimitKind};
+use crate::csr::{
+    fold_undirected_context, initial_labels, refine_step_with, GraphCsr, NodeSlots, Stability,
+    WlStats,
+};
+use crate::error::WlError;
+use petgraph::graph::{DefaultIx, IndexType, NodeIndex};
 // Structures used
 //use counter::Counter;
 //use petgraph::graph::NodeIndex;
 use petgraph::Graph;
-use std::collections::HashMap;
-use twox_hash::{xxhash64, XxHash64};
+use std::collections::{HashMap, VecDeque};
+use std::hash::{BuildHasherDefault, Hasher};
+use twox_hash::XxHash64;
 
 // Petgraph types
 use petgraph::EdgeType;
@@ -13,15 +20,19 @@ use petgraph::EdgeType;
 use std::fs::File;
 
 // Writing the graph to a dotfile
-use palette::{Hsv, IntoColor, Srgb};
+use crate::colour::{colour_assignment, stable_colour_for_class};
 use petgraph::dot::{Config, Dot};
 use std::collections::HashSet;
 use std::fmt::Debug;
-use std::io::Write;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Instant;
 
-use petgraph::visit::GraphProp;
+use petgraph::visit::{
+    EdgeRef, GraphProp, IntoEdgeReferences, IntoNodeReferences, NodeIndexable, NodeRef,
+};
 use petgraph::Directed;
-use petgraph::Direction::{Incoming, Outgoing};
 
 // Two methods for defining a graph type that we are opterating on
 
@@ -33,40 +44,137 @@ where
     std::any::type_name::<G::EdgeType>() == std::any::type_name::<Directed>()
 }
 
+/// The dimension of WL that a [`GraphWrapper`](struct.GraphWrapper.html) runs: [`OneWL`](struct.OneWL.html) or [`TwoWL`](struct.TwoWL.html).
 // A custom trait for the WL dimension. This is a bit more complex, but limits the if/else clutter and runtime checks in the code
 pub trait WLdim {}
+/// Marks a [`GraphWrapper`](struct.GraphWrapper.html) that runs 1-dimensional WL on the nodes.
 pub struct OneWL;
+/// Marks a [`GraphWrapper`](struct.GraphWrapper.html) that runs 2-dimensional WL on the pairs of nodes.
 pub struct TwoWL;
 impl WLdim for OneWL {}
 impl WLdim for TwoWL {}
 
-// Struct that holds the necessary fields and methods to run WL
-pub struct GraphWrapper<N, E, Ty, Wd>
+/// Struct that holds the necessary fields and methods to run WL. The functions like [`invariant`](fn.invariant.html) use it internally; use it directly for control over a run, such as going back a few iterations and continuing from there.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use wl_isomorphism::GraphWrapper;
+///
+/// let path = UnGraph::<(), ()>::from_edges((0..15).map(|node| (node, node + 1)));
+/// // Five labellings, of which the last two are undone, and four more: the same as seven labellings
+/// let mut wrap = GraphWrapper::new(path.clone(), 42, 5, false, false).with_rollback(4);
+/// wrap.run();
+/// wrap.rollback(2).unwrap();
+/// wrap.resume(4).unwrap();
+/// let mut seven = GraphWrapper::new(path, 42, 7, false, false);
+/// seven.run();
+/// assert_eq!(wrap.get_results(), seven.get_results());
+/// ```
+pub struct GraphWrapper<N, E, Ty, Wd, Ix = DefaultIx>
 where
     N: std::cmp::Ord, // Nodeweight
     Ty: EdgeType,     // Directed or undirected
     Wd: WLdim,
+    Ix: IndexType, // Index type of the graph
 {
-    pub graph: Graph<N, E, Ty>,
+    pub graph: Graph<N, E, Ty, Ix>,
     seed: u64,
     labels: Vec<u64>,
     new_labels: Vec<u64>, // To store newly calculated labels (cannot be done in place)
     niters: usize,        // After how many iterations to terminate
-    check_stable: bool,   // Whether to terminate once the colouring becomes stable
-    get_subgraphs: bool,  // Whether to store the subgraph hashes
+    requested_niters: usize, // `niters` as it was given, where 0 stands for n - 1, for resetting to another graph
+    check_stable: bool,      // Whether to terminate once the colouring becomes stable
+    get_subgraphs: bool,     // Whether to store the subgraph hashes
     pub subgraphs: Option<Vec<Vec<u64>>>, // In case we're doing subgraph hashing
+    initial: Option<Vec<u64>>, // Initial labels to use instead of the degrees
+    stats: Option<WlStats>,  // Counters for the work done, if requested
+    aggregation: AggregationMode, // How the neighbour labels are combined
+    adjacency: Option<GraphCsr<Ix>>, // Adjacency lists for 1-dimensional WL, built with the initial labels
+    slots: NodeSlots, // The position of every node in the label vectors, captured at construction
+    self_loop_multiplicity: usize, // How often a self-loop counts among a node's neighbours
+    undirected_context: bool, // Whether directed graphs also hash the neighbours regardless of direction
+    stability: Stability,     // Scratch space for checking whether the colouring is stable
+    rollback: Option<WlRingBuffer>, // The labels of the last iterations, if they are kept for rolling back
+    has_run: bool, // Whether a run has started, after which the labels are no longer the initial ones
+    budget: Option<LabelBudget>, // A limit on the number of labels to compute, if the run is to be aborted once it would go over
     _dim: std::marker::PhantomData<Wd>, // Marker for the WL dimension
 }
 
+// The label vectors of the last `capacity` iterations, oldest first, so that a run can be rolled back
+pub(crate) struct WlRingBuffer {
+    history: VecDeque<Vec<u64>>,
+    capacity: usize,
+}
+
+impl WlRingBuffer {
+    fn new(capacity: usize) -> Self {
+        WlRingBuffer {
+            history: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    // Keep a copy of the labels, dropping the oldest when full and reusing its memory
+    fn push(&mut self, labels: &[u64]) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entry = if self.history.len() == self.capacity {
+            self.history.pop_front().unwrap()
+        } else {
+            Vec::with_capacity(labels.len())
+        };
+        entry.clear();
+        entry.extend_from_slice(labels);
+        self.history.push_back(entry);
+    }
+}
+
+// A limit on the number of labels a run computes, counting the initial ones, with what was used so far
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LabelBudget {
+    limit: usize,
+    used: usize,
+    iterations: usize,       // The refinement iterations that fitted in the budget
+    exceeded: Option<usize>, // The total the run would have reached with the iteration that did not fit
+}
+
+// Labels are hashes already, so a map keyed by them can use their bits as they are
+#[derive(Default)]
+struct LabelHasher(u64);
+
+impl Hasher for LabelHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = self.0.rotate_left(8) ^ byte as u64;
+        }
+    }
+
+    fn write_u64(&mut self, label: u64) {
+        self.0 = label;
+    }
+}
+
+// The number of labels 2-dimensional WL keeps: n (n + 1) / 2 unordered pairs, including a node paired with itself. With large index types the node count can be huge, so this must not overflow silently
+pub(crate) fn pair_count(n: usize) -> Option<usize> {
+    n.checked_add(1)
+        .and_then(|m| m.checked_mul(n))
+        .map(|m| m / 2)
+}
+
 // Implementations specifically for 1-dimensional WL
-impl<N, E, Ty> GraphWrapper<N, E, Ty, OneWL>
+impl<N, E, Ty, Ix> GraphWrapper<N, E, Ty, OneWL, Ix>
 where
     N: std::cmp::Ord,
     Ty: EdgeType,
+    Ix: IndexType,
 {
-    // Make a new wrapper based on the input graph
+    /// Make a new wrapper based on the input graph. `niters` is the number of labellings to compute, counting the initial labels, where 0 means `n - 1`, which is always enough to stabilise. With `check_stable`, the run stops as soon as the colouring is stable instead. With `sub`, the labels of every iteration are kept in `subgraphs`.
     pub fn new(
-        graph: Graph<N, E, Ty>,
+        graph: Graph<N, E, Ty, Ix>,
         seed: u64,
         mut niters: usize,
         check_stable: bool,
@@ -74,8 +182,10 @@ where
     ) -> Self {
         let labels = Vec::with_capacity(graph.node_count());
         let new_labels = vec![0; graph.node_count()]; // interesting: capacity vs length!
+        let slots = NodeSlots::new(&graph);
+        let requested_niters = niters;
         if niters == 0 || niters > graph.node_count() {
-            niters = graph.node_count() - 1;
+            niters = graph.node_count().saturating_sub(1);
         }
 
         // allocate the vector of vectors to store neighbourhoods hashes, if necessary
@@ -90,164 +200,585 @@ where
             labels,
             new_labels,
             niters,
+            requested_niters,
             check_stable,
             get_subgraphs: sub,
             subgraphs,
+            initial: None,
+            stats: None,
+            aggregation: AggregationMode::SortedHash,
+            adjacency: None,
+            slots,
+            self_loop_multiplicity: 1,
+            undirected_context: false,
+            stability: Stability::default(),
+            rollback: None,
+            has_run: false,
+            budget: None,
+            _dim: std::marker::PhantomData,
+        }
+    }
+
+    /// Make a new wrapper that reuses the buffers of an earlier wrapper (see `into_buffers`) instead of allocating fresh ones.
+    /// Subgraph hashes are stored iff a subgraph buffer is passed.
+    pub fn with_buffers(
+        graph: Graph<N, E, Ty, Ix>,
+        seed: u64,
+        mut niters: usize,
+        check_stable: bool,
+        buffers: (Vec<u64>, Vec<u64>, Option<Vec<Vec<u64>>>),
+    ) -> Self {
+        let (mut labels, mut new_labels, mut subgraphs) = buffers;
+        let n = graph.node_count();
+        let slots = NodeSlots::new(&graph);
+        let requested_niters = niters;
+        if niters == 0 || niters > n {
+            niters = n.saturating_sub(1);
+        }
+        labels.clear();
+        new_labels.clear();
+        new_labels.resize(n, 0);
+        if let Some(rows) = &mut subgraphs {
+            rows.truncate(n);
+            rows.iter_mut().for_each(Vec::clear);
+            rows.resize_with(n, || Vec::with_capacity(niters));
+        }
+        GraphWrapper {
+            graph,
+            seed,
+            labels,
+            new_labels,
+            niters,
+            requested_niters,
+            check_stable,
+            get_subgraphs: subgraphs.is_some(),
+            subgraphs,
+            initial: None,
+            stats: None,
+            aggregation: AggregationMode::SortedHash,
+            adjacency: None,
+            slots,
+            self_loop_multiplicity: 1,
+            undirected_context: false,
+            stability: Stability::default(),
+            rollback: None,
+            has_run: false,
+            budget: None,
             _dim: std::marker::PhantomData,
         }
     }
 
-    // Run 1-dimensional WL on the graph
+    /// Replace the graph with another one, keeping the settings of this wrapper and reusing its buffers, to save allocations when processing a stream of graphs. The number of iterations is resolved anew for the size of the new graph, so that e.g. 0 still means `n - 1`. Counters, the budget and the labels kept for rolling back start from scratch, and initial labels set with `with_initial_labels` are dropped, as they belong to the old graph.
+    /// ```rust
+    /// use petgraph::graph::{DiGraph, UnGraph};
+    /// use wl_isomorphism::GraphWrapper;
+    ///
+    /// let mut wrap = GraphWrapper::new(UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]), 42, 0, true, false);
+    /// wrap.run();
+    /// let mut wrap = wrap.reset(DiGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0)]));
+    /// wrap.run();
+    /// assert_eq!(wrap.get_results(), wl_isomorphism::invariant(DiGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0)])));
+    /// ```
+    pub fn reset<NewN: Ord, NewE, NewTy: EdgeType>(
+        self,
+        graph: Graph<NewN, NewE, NewTy, Ix>,
+    ) -> GraphWrapper<NewN, NewE, NewTy, OneWL, Ix> {
+        let mut wrap = GraphWrapper::with_buffers(
+            graph,
+            self.seed,
+            self.requested_niters,
+            self.check_stable,
+            (self.labels, self.new_labels, self.subgraphs),
+        );
+        wrap.stats = self.stats.map(|_| WlStats::default());
+        wrap.aggregation = self.aggregation;
+        wrap.self_loop_multiplicity = self.self_loop_multiplicity;
+        wrap.undirected_context = self.undirected_context;
+        // The scratch space of the stability check renumbers itself for new labels
+        wrap.stability = self.stability;
+        wrap.rollback = self
+            .rollback
+            .map(|rollback| WlRingBuffer::new(rollback.capacity));
+        if let Some(budget) = self.budget {
+            wrap = wrap.with_label_budget(budget.limit);
+        }
+        wrap
+    }
+
+    /// Hand back the buffers, so that they can be reused for the next graph
+    pub fn into_buffers(self) -> (Vec<u64>, Vec<u64>, Option<Vec<Vec<u64>>>) {
+        (self.labels, self.new_labels, self.subgraphs)
+    }
+
+    /// Count the work done while refining
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(WlStats::default());
+        self
+    }
+
+    /// Sum the neighbour labels instead of sorting them
+    pub fn with_sum_aggregation(self) -> Self {
+        self.with_aggregation(AggregationMode::SumFold)
+    }
+
+    /// Choose how the neighbour labels are combined
+    pub fn with_aggregation(mut self, aggregation: AggregationMode) -> Self {
+        self.aggregation = aggregation;
+        self
+    }
+
+    /// Count every self-loop `multiplicity` times among the neighbours of its node, rather than once
+    pub fn with_self_loop_multiplicity(mut self, multiplicity: usize) -> Self {
+        self.self_loop_multiplicity = multiplicity;
+        self
+    }
+
+    /// For directed graphs, also hash all neighbours of a node together, regardless of the direction of their edges, into its new label
+    pub fn with_undirected_context(mut self) -> Self {
+        self.undirected_context = true;
+        self
+    }
+
+    /// Keep the labels of the last `capacity` iterations, so that the run can be rolled back
+    pub fn with_rollback(mut self, capacity: usize) -> Self {
+        self.rollback = Some(WlRingBuffer::new(capacity));
+        self
+    }
+
+    pub fn stats(&self) -> Option<&WlStats> {
+        self.stats.as_ref()
+    }
+
+    /// Start the refinement from the given labels instead of the node degrees, one per node in order of the node indices. If they are stable from the start, such as the same label for every node of a regular graph, the labels of the first refinement are kept anyway, so that the edges always count
+    pub fn with_initial_labels(mut self, labels: Vec<u64>) -> Self {
+        assert_eq!(
+            labels.len(),
+            self.graph.node_count(),
+            "There must be exactly one initial label per node"
+        );
+        self.initial = Some(labels);
+        self
+    }
+
+    /// Run 1-dimensional WL on the graph. A wrapper runs only once: later calls to `run` and its variants leave the labels as they are, so use `resume` to refine further
     pub fn run(&mut self) {
+        self.run_observed(|_, _| {});
+    }
+
+    /// Run 1-dimensional WL and return the hash of `get_results` together with the final label of every node, in node order. Unlike calling `get_results`, this leaves the labels in node order too
+    pub fn run_and_labels(&mut self) -> (u64, Vec<u64>) {
+        self.run();
+        (self.digest(), self.labels.clone())
+    }
+
+    /// Run 1-dimensional WL, passing the labels before and after every iteration to `observe`
+    pub fn run_observed(&mut self, observe: impl FnMut(&[u64], &[u64])) {
+        self.run_inner(observe, |_| true);
+    }
+
+    /// Run 1-dimensional WL, passing the labels of every iteration to `sink` once they are kept, starting with the initial labels.
+    /// These are the same rows that subgraph hashing stores, but the caller decides what to retain.
+    pub fn run_streaming(&mut self, mut sink: impl FnMut(&[u64])) {
+        self.run_inner(
+            |_, _| {},
+            |labels| {
+                sink(labels);
+                true
+            },
+        );
+    }
+
+    /// Run up to `n_iters` more iterations from the current labels, for instance after a rollback. Stops early once the colouring is stable, if that is checked. The iterations count against the label budget of the run, if it has one.
+    ///
+    /// Returns an error if the wrapper has not run yet, as there are no labels to continue from, or if the next iteration would exceed the budget, in which case the labels are those of the last iteration that fitted.
+    pub fn resume(&mut self, n_iters: usize) -> Result<(), WlError> {
+        if !self.has_run {
+            return Err(WlError::NotRun);
+        }
+        for _ in 0..n_iters {
+            if !self.charge(true) {
+                let budget = self.budget.as_ref().unwrap();
+                return Err(WlError::LimitExceeded {
+                    which: LimitKind::LabelHashes,
+                    limit: budget.limit,
+                    observed: budget.exceeded.unwrap(),
+                    iterations: budget.iterations,
+                });
+            }
+            self.calculate_new_labels();
+            if self.check_stable && self.stabilised() {
+                break;
+            }
+            self.update_graph();
+        }
+        Ok(())
+    }
+
+    /// Run 1-dimensional WL until `keep_going` returns false for the labels of an iteration, which are then the current labels. Returns whether it stopped early.
+    pub fn run_until(&mut self, mut keep_going: impl FnMut(&[u64]) -> bool) -> bool {
+        self.run_inner(|_, _| {}, |labels| keep_going(labels))
+    }
+
+    /// Whether every node has a colour of its own in the current labelling
+    pub fn is_individualized(&self) -> bool {
+        self.colour_count() == self.graph.node_count()
+    }
+
+    fn run_inner(
+        &mut self,
+        mut observe: impl FnMut(&[u64], &[u64]),
+        mut sink: impl FnMut(&[u64]) -> bool,
+    ) -> bool {
+        if self.has_run {
+            return false;
+        }
+        #[cfg(feature = "tracing")]
+        let _run = tracing::info_span!("wl_run", n = self.graph.node_count()).entered();
         self.initial_graph();
+        if !self.charge(false) || !sink(&self.labels) {
+            return true;
+        }
         let mut its = 1;
         while self.check_stable || its < self.niters {
+            if !self.charge(true) {
+                return true;
+            }
             self.calculate_new_labels();
+            observe(&self.labels, &self.new_labels);
             its += 1;
             if self.check_stable && self.stabilised() {
+                // Supplied initial labels can be stable from the start, and hashing them would leave out the edges, so the first
+                // refinement is kept. It splits nothing, but folds in the neighbourhood of every node
+                if its == 2 && self.initial.is_some() {
+                    self.update_graph();
+                    if !sink(&self.labels) {
+                        return true;
+                    }
+                }
                 break;
             }
             self.update_graph();
+            #[cfg(feature = "tracing")]
+            tracing::debug!(iter = its, n_colors = self.colour_count());
+            if !sink(&self.labels) {
+                return true;
+            }
         }
+        false
     }
 
     // Get the labels for the next iteration based on the current state
     fn calculate_new_labels(&mut self) {
-        for node in self.graph.node_indices() {
-            // Collect all the relevant hashes: of the node itself and all its neighbours
-            let mut input_hashes = Vec::new();
-            if !is_directed(&self.graph) {
-                for neighbour in self.graph.neighbors(node) {
-                    input_hashes.push(self.labels[neighbour.index()]);
-                }
-                input_hashes.sort_unstable(); // sort for consistency
-            } else {
-                for neighbour in self.graph.neighbors_directed(node, Incoming) {
-                    input_hashes.push(self.labels[neighbour.index()]);
-                }
-                let mut outgoing_hashes = Vec::new();
-                for neighbour in self.graph.neighbors_directed(node, Outgoing) {
-                    outgoing_hashes.push(self.labels[neighbour.index()]);
-                }
-
-                outgoing_hashes.sort_unstable();
-
-                //separately label the in and outgoing hashes  (Previously had a concern: what if one combination of nodes followed by another and then the node's hash itself also possible in a different way? Seems unlikely -> different hash iteration)
-                input_hashes = vec![
-                    XxHash64::oneshot(self.seed, bytemuck::cast_slice(&input_hashes)),
-                    XxHash64::oneshot(self.seed, bytemuck::cast_slice(&outgoing_hashes)),
-                ];
-            }
-
-            input_hashes.push(self.labels[node.index()]); // In this way, the hash of the node itself is always the last one of the list!
-            let hash = XxHash64::oneshot(self.seed, bytemuck::cast_slice(&input_hashes));
-            self.new_labels[node.index()] = hash;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("calculate_new_labels").entered();
+        let start = self.start_timer();
+        let adjacency = self
+            .adjacency
+            .as_ref()
+            .expect("The initial labels must be set before refining")
+            .view();
+        let (seed, labels, new_labels) = (self.seed, &self.labels, &mut self.new_labels);
+        let aggregation = self.aggregation;
+        match &mut self.stats {
+            Some(stats) => refine_step_with::<Ix, true>(
+                aggregation,
+                &adjacency,
+                seed,
+                labels,
+                new_labels,
+                Some(stats),
+            ),
+            None => refine_step_with::<Ix, false>(
+                aggregation,
+                &adjacency,
+                seed,
+                labels,
+                new_labels,
+                None,
+            ),
+        }
+        if self.undirected_context {
+            fold_undirected_context(&adjacency, seed, labels, new_labels);
+        }
+        if let (Some(stats), Some(start)) = (&mut self.stats, start) {
+            stats.iteration_times.push(start.elapsed());
         }
     }
 
+    // Set the initial labels, replacing any earlier ones
     fn initial_graph(&mut self) {
-        // Initial weights are (hashed) degrees Is hashing here even really necessary at all?
-        let mut hash: u64;
-        if !is_directed(&self.graph) {
-            // do this kind of stuff with macros? Is that worth the complexity? Might be good bc repetetive use? Maybe better to just not check at runtime at all..
-            for node in self.graph.node_indices() {
-                hash = self.graph.neighbors(node).count() as u64;
-                self.labels.push(hash);
-            }
+        let start = self.start_timer();
+        self.has_run = true;
+        self.labels.clear();
+        if let Some(rows) = &mut self.subgraphs {
+            rows.iter_mut().for_each(Vec::clear);
+        }
+        // Precompute the adjacency lists once, so that every iteration can walk plain arrays
+        let adjacency = GraphCsr::with_slots(&self.graph, &self.slots)
+            .with_self_loop_multiplicity(self.self_loop_multiplicity);
+        if let Some(initial) = &self.initial {
+            self.labels.extend_from_slice(initial);
         } else {
-            for node in self.graph.node_indices() {
-                let out = self.graph.neighbors_directed(node, Outgoing).count();
-                let ing = self.graph.neighbors_directed(node, Incoming).count();
-                hash = XxHash64::oneshot(self.seed, bytemuck::cast_slice(&[out, ing]));
-                self.labels.push(hash);
-            }
+            initial_labels(&adjacency.view(), self.seed, &mut self.labels);
         }
+        self.adjacency = Some(adjacency);
         if self.get_subgraphs {
+            let rows = self.subgraphs.as_mut().unwrap();
             for node in self.graph.node_indices() {
-                self.subgraphs.as_mut().unwrap()[node.index()].push(self.labels[node.index()]);
+                let slot = self.slots.slot(node.index());
+                rows[slot].push(self.labels[slot]);
             }
         }
+        if let (Some(stats), Some(start)) = (&mut self.stats, start) {
+            stats.init_time += start.elapsed();
+        }
     }
 }
 
+// Refine two graphs side by side and compare the multisets of labels after every iteration.
+// Returns whether the graphs are possibly isomorphic and how many labellings were compared before deciding.
+pub fn lockstep_compare<N, E, Ty, Ix>(
+    first: &mut GraphWrapper<N, E, Ty, OneWL, Ix>,
+    second: &mut GraphWrapper<N, E, Ty, OneWL, Ix>,
+) -> (bool, usize)
+where
+    N: std::cmp::Ord,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    if first.graph.node_count() != second.graph.node_count() {
+        return (false, 0);
+    }
+    first.initial_graph();
+    second.initial_graph();
+    let mut compared = 1;
+    if !same_multiset(&first.labels, &second.labels) {
+        return (false, compared);
+    }
+    loop {
+        first.calculate_new_labels();
+        second.calculate_new_labels();
+        compared += 1;
+        if !same_multiset(&first.new_labels, &second.new_labels) {
+            return (false, compared);
+        }
+        // Equal multisets have an equal number of colour classes, so the graphs normally stabilise together
+        if first.stabilised() && second.stabilised() {
+            return (true, compared);
+        }
+        first.update_graph();
+        second.update_graph();
+    }
+}
+
+fn same_multiset(left: &[u64], right: &[u64]) -> bool {
+    let mut left = left.to_vec();
+    let mut right = right.to_vec();
+    left.sort_unstable();
+    right.sort_unstable();
+    left == right
+}
+
 // Implementations specifically for writing it to dotfile, this requires debug.
-impl<N, E, Ty> GraphWrapper<N, E, Ty, OneWL>
+impl<N, E, Ty, Ix> GraphWrapper<N, E, Ty, OneWL, Ix>
 where
     N: std::cmp::Ord,
     E: Debug,
     Ty: EdgeType,
+    Ix: IndexType,
 {
-    // Write the final graph to a dot file, with colouring of the nodes based on what colour class they are in
-    pub fn write_dot(&self, path: &str) {
-        let hash_to_colour = self.get_colour_map();
-
-        // get a new graph with the colour strings as weights
-        let graph = self.graph.map(
-            |index, _weight| hash_to_colour[&self.labels[index.index()]].clone(), // Get the colour that belongs to the hash
-            |_index, weight| weight, // For edges, simply return the input weight
-        );
-
-        // Create a file, create a Dot formatter from petgraph and write that to the file
-        let mut f = File::create(path).expect("failed to create the dot file");
-        let dot = Dot::with_attr_getters(
-            &graph,
-            &[Config::NodeIndexLabel, Config::EdgeNoLabel],
-            &|_graph, _edge| String::new(),
-            &|_graph, node| node.1.to_string(),
-        );
-        f.write_all(format!("{:?}", dot).as_bytes())
-            .expect("failed to write from input to file");
+    /// Write the final graph to a dot file, with colouring of the nodes based on what colour class they are in. The nodes are written in order of their index and the edges sorted by their endpoints
+    pub fn write_dot(&self, path: impl AsRef<Path>, options: DotOptions) -> io::Result<()> {
+        write_coloured_dot(&self.graph, &self.labels, None, path, options)
     }
 
-    // Get a hashmap that translates labels (hashes) to associated colours:
-    // find the unique labels, get the same number of contrasting colours and finally zip that into a hashmap
-    fn get_colour_map(&self) -> HashMap<&u64, String> {
-        let unique_hashes: Vec<_> = HashSet::<_>::from_iter(self.labels.iter())
-            .into_iter()
+    /// Like [`write_dot`](struct.GraphWrapper.html#method.write_dot), but every node is labelled with its weight instead of its index, while the fill colour still shows its class. When there are too many classes for colours, the class number is written next to the node instead.
+    pub fn write_dot_weighted(&self, path: impl AsRef<Path>, options: DotOptions) -> io::Result<()>
+    where
+        N: std::fmt::Display,
+    {
+        let weights = self
+            .graph
+            .node_weights()
+            .map(|weight| weight.to_string())
             .collect();
+        write_coloured_dot(&self.graph, &self.labels, Some(weights), path, options)
+    }
+}
 
-        let hash_to_colour = if unique_hashes.len() > 8 {
-            // Map hashes to numbers
-            unique_hashes
-                .iter()
-                .enumerate()
-                .map(|(i, &hash)| (hash, format!("label = {}", i)))
-                .collect()
-        } else {
-            // Map hashes to contrasting colors
-            let colours = generate_contrasting_colors(unique_hashes.len()).map(|c| {
-                format!(
-                    "style = filled fillcolor= \"#{:02X}{:02X}{:02X}\"",
-                    c.red, c.green, c.blue
-                )
-            });
+/// Options for the dot output of [`invariant_dot_with_options`](fn.invariant_dot_with_options.html). The default colours the nodes only, as [`invariant_dot`](fn.invariant_dot.html) does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DotOptions {
+    /// Whether to also colour every edge by the classes of its endpoints, so that edges between the same two classes get the same colour. For directed graphs the order of the classes matters, which shows where edges converge and diverge; for undirected graphs it does not. The colours come from [`stable_colour_for_class`](fn.stable_colour_for_class.html), so the same pair of classes gets the same colour in every graph.
+    pub colour_edges: bool,
+    /// Whether to wrap every colour class in a `subgraph cluster_k`, so that Graphviz draws the nodes of a class together in a box. The clusters are numbered as [`colour_classes`](fn.colour_classes.html) numbers the classes.
+    pub cluster_by_class: bool,
+}
 
-            unique_hashes.iter().copied().zip(colours).collect()
+// Write any graph to a dot file, colouring node `graph.to_index(node)` by `labels[graph.to_index(node)]`, and labelling it with
+// `weights[graph.to_index(node)]` if given, or its index otherwise.
+// Only the visitor traits are used, so this does not depend on how the graph is stored: the output is built as a temporary graph with the colour strings as weights.
+pub(crate) fn write_coloured_dot<G>(
+    graph: G,
+    labels: &[u64],
+    weights: Option<Vec<String>>,
+    path: impl AsRef<Path>,
+    options: DotOptions,
+) -> io::Result<()>
+where
+    G: IntoNodeReferences + IntoEdgeReferences + NodeIndexable + GraphProp,
+{
+    let hash_to_colour = get_colour_map(labels, weights.is_some());
+
+    // get a new graph with the colour strings as weights, numbering the nodes in order of their index
+    let mut order: Vec<usize> = graph
+        .node_references()
+        .map(|node| graph.to_index(node.id()))
+        .collect();
+    order.sort_unstable();
+    let mut new_index = vec![NodeIndex::<DefaultIx>::end(); graph.node_bound()];
+    let mut coloured = Graph::<String, String, G::EdgeType>::with_capacity(order.len(), 0);
+    for &index in &order {
+        // Get the colour that belongs to the hash
+        let attributes = match &weights {
+            Some(weights) => format!(
+                "label = \"{}\" {}",
+                dot_escape(&weights[index]),
+                hash_to_colour[&labels[index]]
+            ),
+            None => hash_to_colour[&labels[index]].clone(),
+        };
+        new_index[index] = coloured.add_node(attributes);
+    }
+    // The edges are added sorted by their endpoints rather than in the order the graph stores them, so that the same coloured graph always
+    // gives the same file. Undirected edges are written from their smaller endpoint
+    let mut edges: Vec<(usize, usize)> = graph
+        .edge_references()
+        .map(|edge| {
+            let (source, target) = (graph.to_index(edge.source()), graph.to_index(edge.target()));
+            if graph.is_directed() || source <= target {
+                (source, target)
+            } else {
+                (target, source)
+            }
+        })
+        .collect();
+    edges.sort_unstable();
+    for (source, target) in edges {
+        let attributes = if options.colour_edges {
+            edge_colour(graph.is_directed(), labels[source], labels[target])
+        } else {
+            String::new()
         };
+        coloured.add_edge(new_index[source], new_index[target], attributes);
+    }
 
-        hash_to_colour
+    // Create the file and its missing parent directories, create a Dot formatter from petgraph and write that to the file
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut f = File::create(path)?;
+    let config = [
+        if weights.is_some() {
+            Config::NodeNoLabel
+        } else {
+            Config::NodeIndexLabel
+        },
+        Config::EdgeNoLabel,
+        Config::GraphContentOnly,
+    ];
+    let dot = Dot::with_attr_getters(
+        &coloured,
+        &config,
+        &|_graph, edge| edge.weight().clone(),
+        &|_graph, node| node.1.to_string(),
+    );
+    let kind = if graph.is_directed() {
+        "digraph"
+    } else {
+        "graph"
+    };
+    let mut text = format!("{} {{\n{:?}", kind, dot);
+    if options.cluster_by_class {
+        // Nodes that were declared above join the cluster they are named in again
+        let mut unique = labels.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+        let mut clusters = vec![Vec::new(); unique.len()];
+        for &index in &order {
+            clusters[unique.binary_search(&labels[index]).unwrap()].push(new_index[index].index());
+        }
+        for (k, members) in clusters.iter().enumerate() {
+            text.push_str(&format!("    subgraph cluster_{} {{\n", k));
+            for member in members {
+                text.push_str(&format!("        {};\n", member));
+            }
+            text.push_str("    }\n");
+        }
     }
+    text.push_str("}\n");
+    f.write_all(text.as_bytes())
 }
 
-// Get colours that are as opposing as possible
-fn generate_contrasting_colors(n: usize) -> impl Iterator<Item = Srgb<u8>> {
-    (0..n).map(move |i| {
-        let contrast = (360.0 / n as f32) * i as f32; // Spread hues (for colours) and lightness (for black and white) evenly lightness doesn't do what was hoped :(
-        let hsv = Hsv::new(contrast, 1.0, 1.0); // Full saturation
-        let srgb: Srgb = hsv.into_color();
-        srgb.into_format() // Convert to u8 format
-    })
+// Escape a string for use between double quotes in a dot file
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
 }
 
+// The dot attribute of an edge between the given classes. Undirected edges have no order, so their classes are sorted first.
+fn edge_colour(directed: bool, source: u64, target: u64) -> String {
+    let pair = if directed || source <= target {
+        [source, target]
+    } else {
+        [target, source]
+    };
+    let class = XxHash64::oneshot(42, bytemuck::cast_slice(&pair));
+    format!("color = \"{}\"", stable_colour_for_class(class).hex())
+}
+
+// Get a hashmap that translates labels (hashes) to their dot attributes: contrasting colours, or numbers if there are too many classes to tell the colours apart.
+// If the nodes are labelled by their weights, the numbers go next to the nodes instead
+fn get_colour_map(labels: &[u64], weighted: bool) -> HashMap<u64, String> {
+    let colours = colour_assignment(labels);
+    if colours.len() > 8 {
+        // Map hashes to numbers, in the same order as the colours
+        let mut unique: Vec<u64> = colours.into_keys().collect();
+        unique.sort_unstable();
+        unique
+            .into_iter()
+            .enumerate()
+            .map(|(i, hash)| {
+                let attribute = if weighted { "xlabel" } else { "label" };
+                (hash, format!("{} = {}", attribute, i))
+            })
+            .collect()
+    } else {
+        colours
+            .into_iter()
+            .map(|(hash, colour)| {
+                (
+                    hash,
+                    format!("style = filled fillcolor= \"{}\"", colour.hex()),
+                )
+            })
+            .collect()
+    }
+}
+
+// The initial label of the pair of a node with itself is its number of self-loops with this bit set
+const DIAGONAL: u64 = 1 << 63;
+
 // Implementations specifically for 2-dimensional WL
-impl<N, E, Ty> GraphWrapper<N, E, Ty, TwoWL>
+impl<N, E, Ty, Ix> GraphWrapper<N, E, Ty, TwoWL, Ix>
 where
     N: std::cmp::Ord,
     Ty: EdgeType,
+    Ix: IndexType,
 {
-    // Make a new wrapper based on the input graph
+    /// Make a new wrapper based on the input graph
     pub fn new_2wl(
-        graph: Graph<N, E, Ty>,
+        graph: Graph<N, E, Ty, Ix>,
         seed: u64,
         mut niters: usize,
         check_stable: bool,
@@ -259,124 +790,227 @@ where
         if is_directed(&graph) {
             panic!("Directed graphs are not yet supported for 2-dimensional WL");
         }
-        let number_tuples = ((graph.node_count() - 1)
-            .checked_pow(2)
-            .expect("This grapsize exceeds support for 2-dimensional WL")
-            + graph.node_count()
-            - 1)
-            / 2
-            + graph.node_count();
+        let number_tuples = pair_count(graph.node_count())
+            .expect("This grapsize exceeds support for 2-dimensional WL");
         let labels = Vec::with_capacity(number_tuples);
         let new_labels = vec![0; number_tuples];
+        let requested_niters = niters;
         if niters == 0 || niters > number_tuples {
-            niters = number_tuples - 1;
+            niters = number_tuples.saturating_sub(1);
         }
 
         let subgraphs = None;
+        let slots = NodeSlots::new(&graph);
         GraphWrapper {
             graph,
             seed,
             labels,
             new_labels,
             niters,
+            requested_niters,
             check_stable,
             get_subgraphs: sub,
             subgraphs,
+            initial: None,
+            stats: None,
+            aggregation: AggregationMode::SortedHash,
+            adjacency: None,
+            slots,
+            self_loop_multiplicity: 1,
+            undirected_context: false,
+            stability: Stability::default(),
+            rollback: None,
+            has_run: false,
+            budget: None,
             _dim: std::marker::PhantomData,
         }
     }
 
-    // Run 2-dimensional WL on the graph.
-    // Unfortunately a duplicate of the code for 1-dimensional WL. This was necessary because otherwise there is difficulty with scoping of the methods.
+    /// Run 2-dimensional WL on the graph. As for 1-dimensional WL, a wrapper runs only once and later calls leave the labels as they are.
     pub fn run(&mut self) {
+        // Unfortunately a duplicate of the code for 1-dimensional WL. This was necessary because otherwise there is difficulty with scoping of the methods.
+        if self.has_run {
+            return;
+        }
+        #[cfg(feature = "tracing")]
+        let _run = tracing::info_span!("wl_run", n = self.graph.node_count()).entered();
         self.initial_graph();
+        if !self.charge(false) {
+            return;
+        }
         let mut its = 1;
         while self.check_stable || its < self.niters {
+            if !self.charge(true) {
+                break;
+            }
             self.calculate_new_labels();
             its += 1;
             if self.check_stable && self.stabilised() {
                 break;
             }
             self.update_graph();
+            #[cfg(feature = "tracing")]
+            tracing::debug!(iter = its, n_colors = self.colour_count());
         }
     }
 
+    /// Run 2-dimensional WL and return the hash of `get_results` together with the final label of every pair of nodes, in the order of `labels`. Unlike calling `get_results`, this leaves the labels in that order too
+    pub fn run_and_labels(&mut self) -> (u64, Vec<u64>) {
+        self.run();
+        (self.digest(), self.labels.clone())
+    }
+
+    /// Count every self-loop `multiplicity` times in the label of the pair of its node with itself, rather than once
+    pub fn with_self_loop_multiplicity(mut self, multiplicity: usize) -> Self {
+        self.self_loop_multiplicity = multiplicity;
+        self
+    }
+
+    // Set the initial labels, replacing any earlier ones
     fn initial_graph(&mut self) {
+        self.has_run = true;
+        self.labels.clear();
         for left in 0..self.graph.node_count() {
             let left_node = NodeIndex::new(left);
             for right in 0..=left {
-                self.labels.push(
-                    self.graph
-                        .edges_connecting(left_node, NodeIndex::new(right))
-                        .count() as u64,
-                )
+                let edges = self
+                    .graph
+                    .edges_connecting(left_node, NodeIndex::new(right))
+                    .count();
+                // The pair of a node with itself is marked, so that it is never mistaken for a pair of distinct nodes with as many edges
+                let label = if left == right {
+                    DIAGONAL | (edges * self.self_loop_multiplicity) as u64
+                } else {
+                    edges as u64
+                };
+                self.labels.push(label)
             }
         }
     }
 
     // Get the labels for the next iteration based on the current state
+    // The labels of the pairs are stored as a lower triangle, row by row: pair (a, b) with a >= b is at row_start(a) + b, where row_start(a) = a (a + 1) / 2.
+    // Rather than computing that for every lookup, the loop over the alternatives is split at `right` and `left`, so that which of the two nodes is largest is known, and the row starts are kept up to date by addition.
     fn calculate_new_labels(&mut self) {
-        for left in 0..self.graph.node_count() {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("calculate_new_labels").entered();
+        let n = self.graph.node_count();
+        let labels = &self.labels;
+        let mut input_hashes: Vec<[u64; 2]> = Vec::with_capacity(n);
+        let mut flat: Vec<u64> = Vec::with_capacity(n * 2 + 1);
+        let mut left_start = 0; // row_start(left)
+        for left in 0..n {
+            let mut right_start = 0; // row_start(right)
             for right in 0..=left {
-                let mut input_hashes: Vec<[u64; 2]> = Vec::with_capacity(self.graph.node_count());
-                for alternative in 0..self.graph.node_count() {
-                    let left_replace = self.labels[get_label_index(alternative, right)]; // Better way to access?
-                    let right_replace = self.labels[get_label_index(left, alternative)];
+                input_hashes.clear();
+                let mut push = |left_replace: u64, right_replace: u64| {
                     if left_replace < right_replace {
                         input_hashes.push([left_replace, right_replace]);
                     } else {
                         input_hashes.push([right_replace, left_replace])
                     }
+                };
+                // alternative < right: the pairs (right, alternative) and (left, alternative)
+                for alternative in 0..right {
+                    push(
+                        labels[right_start + alternative],
+                        labels[left_start + alternative],
+                    );
+                }
+                // right <= alternative <= left: the pairs (alternative, right) and (left, alternative)
+                let mut alternative_start = right_start;
+                for alternative in right..=left {
+                    push(
+                        labels[alternative_start + right],
+                        labels[left_start + alternative],
+                    );
+                    alternative_start += alternative + 1;
+                }
+                // left < alternative: the pairs (alternative, right) and (alternative, left)
+                for alternative in left + 1..n {
+                    push(
+                        labels[alternative_start + right],
+                        labels[alternative_start + left],
+                    );
+                    alternative_start += alternative + 1;
                 }
                 input_hashes.sort_unstable();
-                // Technically faster to allocate first, though I don't really see that in the results yet.
-                let mut flat: Vec<u64> = Vec::with_capacity(input_hashes.len() * 2 + 1);
-                flat.extend(input_hashes.into_iter().flatten());
-                let current_index = get_label_index(left, right);
-                flat.push(self.labels[current_index]);
+                flat.clear();
+                flat.extend(input_hashes.iter().flatten());
+                let current_index = left_start + right;
+                flat.push(labels[current_index]);
                 let hash = XxHash64::oneshot(self.seed, bytemuck::cast_slice(&flat));
                 self.new_labels[current_index] = hash;
+                right_start += right + 1;
             }
+            left_start += left + 1;
         }
     }
 }
 
-fn get_label_index(mut left: usize, mut right: usize) -> usize {
-    if right > left {
-        (left, right) = (right, left);
-    }
-    (left * left + left) / 2 + right
-}
-
 // Implementations generic for all WL dimensions
-impl<N, E, Ty, Wd> GraphWrapper<N, E, Ty, Wd>
+impl<N, E, Ty, Wd, Ix> GraphWrapper<N, E, Ty, Wd, Ix>
 where
     N: std::cmp::Ord,
     Ty: EdgeType,
     Wd: WLdim,
+    Ix: IndexType,
 {
-    // Maps labels from the previous round to their new values. Iff all labels that were the same are still the same colouring has stabilised
-    fn stabilised(&self) -> bool {
-        let mut label_mapping: HashMap<u64, u64, xxhash64::State> =
-            HashMap::with_hasher(xxhash64::State::with_seed(self.seed));
-        for (idx, old_hash) in self.labels.iter().enumerate() {
-            match label_mapping.get(old_hash) {
-                Some(new_hash) => {
-                    if self.new_labels[idx] != *new_hash {
-                        return false;
-                    }
-                }
-                None => {
-                    label_mapping.insert(*old_hash, self.new_labels[idx]);
-                }
-            }
+    // Abort the run once it has computed `limit` labels, counting the initial labels, before starting an iteration that would go over
+    pub(crate) fn with_label_budget(mut self, limit: usize) -> Self {
+        self.budget = Some(LabelBudget {
+            limit,
+            used: 0,
+            iterations: 0,
+            exceeded: None,
+        });
+        self
+    }
+
+    // If the budget ran out: the total the run would have reached with the next iteration, and how many iterations it completed
+    pub(crate) fn budget_exceeded(&self) -> Option<(usize, usize)> {
+        let budget = self.budget.as_ref()?;
+        budget
+            .exceeded
+            .map(|observed| (observed, budget.iterations))
+    }
+
+    // Account for the initial labels, or with `refining` for the next iteration, which computes as many. Returns whether they fit in the budget
+    fn charge(&mut self, refining: bool) -> bool {
+        let Some(budget) = &mut self.budget else {
+            return true;
+        };
+        let total = budget.used.saturating_add(self.labels.len());
+        if total > budget.limit {
+            budget.exceeded = Some(total);
+            return false;
         }
+        budget.used = total;
+        budget.iterations += refining as usize;
         true
     }
 
+    // Iff all labels that were the same are still the same, the colouring has stabilised
+    fn stabilised(&mut self) -> bool {
+        let start = self.start_timer();
+        let stable = self.stability.check(&self.labels, &self.new_labels);
+        if let (Some(stats), Some(start)) = (&mut self.stats, start) {
+            stats.stability_check_time += start.elapsed();
+        }
+        stable
+    }
+
+    // Start timing a phase of the run, if the stats are requested
+    fn start_timer(&self) -> Option<Instant> {
+        self.stats.as_ref().map(|_| Instant::now())
+    }
+
     // Update the labels in the graph with the values calculated in the last round.
     // If we're doing subgraph hashing, store those in the array.
     fn update_graph(&mut self) {
+        if let Some(rollback) = &mut self.rollback {
+            rollback.push(&self.labels);
+        }
         if self.get_subgraphs {
             for (idx, hash) in self.new_labels.iter().enumerate() {
                 self.subgraphs.as_mut().unwrap()[idx].push(*hash);
@@ -385,10 +1019,139 @@ where
         std::mem::swap(&mut self.labels, &mut self.new_labels);
     }
 
-    // Get the final graph hash
+    /// Go back `n_iters` iterations, to the labels kept by `with_rollback`. Subgraph hashes of the undone iterations are dropped as well
+    pub fn rollback(&mut self, n_iters: usize) -> Result<(), WlError> {
+        if n_iters == 0 {
+            return Ok(());
+        }
+        let available = self
+            .rollback
+            .as_ref()
+            .map_or(0, |buffer| buffer.history.len());
+        if n_iters > available {
+            return Err(WlError::RollbackTooFar {
+                requested: n_iters,
+                available,
+            });
+        }
+        let history = &mut self.rollback.as_mut().unwrap().history;
+        history.truncate(available - n_iters + 1);
+        self.labels = history.pop_back().unwrap();
+        if let Some(rows) = &mut self.subgraphs {
+            for row in rows {
+                row.truncate(row.len() - n_iters);
+            }
+        }
+        Ok(())
+    }
+
+    /// The current label of every node
+    pub fn labels(&self) -> &[u64] {
+        &self.labels
+    }
+
+    /// Number the colour classes by the rank of their label, giving dense ids 0..k
+    pub fn dense_classes(&self) -> Vec<usize> {
+        let mut unique = self.labels.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        self.labels
+            .iter()
+            .map(|label| unique.binary_search(label).unwrap())
+            .collect()
+    }
+
+    /// Number the colour classes in order of their first node, so that partitions can be compared regardless of the label values
+    pub fn class_ids(&self) -> Vec<usize> {
+        let mut ids = HashMap::new();
+        self.labels
+            .iter()
+            .map(|label| {
+                let next = ids.len();
+                *ids.entry(*label).or_insert(next)
+            })
+            .collect()
+    }
+
+    /// The number of colour classes, i.e. distinct labels, of the current labelling. For 2-dimensional WL these are classes of pairs of nodes
+    pub fn colour_count(&self) -> usize {
+        self.labels.iter().collect::<HashSet<_>>().len()
+    }
+
+    /// Get the final graph hash
     pub fn get_results(&mut self) -> u64 {
+        let start = self.start_timer();
         self.labels.sort_unstable(); // unstable is faster than 'normal' sort
-        XxHash64::oneshot(self.seed, bytemuck::cast_slice(&self.labels))
+        let hash = XxHash64::oneshot(self.seed, bytemuck::cast_slice(&self.labels));
+        if let (Some(stats), Some(start)) = (&mut self.stats, start) {
+            stats.digest_time += start.elapsed();
+        }
+        hash
+    }
+
+    /// The same hash as `get_results`, but sorting a copy, so that the labels stay in node order for other outputs
+    pub fn digest(&self) -> u64 {
+        let mut labels = self.labels.clone();
+        labels.sort_unstable();
+        XxHash64::oneshot(self.seed, bytemuck::cast_slice(&labels))
+    }
+
+    /// A hash of the labels like `digest`, but computed from the number of labels of every colour instead of from all labels sorted. When there are far fewer colours than labels, counting is much cheaper than the sort, and only the distinct colours are sorted. The bytes hashed differ from those of `digest`, so the hash does too, but it is equal for exactly the same multisets of labels: only compare it with other counted digests
+    pub fn counted_digest(&self) -> u64 {
+        XxHash64::oneshot(self.seed, bytemuck::cast_slice(&self.colour_counts()))
+    }
+
+    // Every distinct label with the number of nodes that have it, sorted by label
+    pub(crate) fn colour_counts(&self) -> Vec<[u64; 2]> {
+        let mut counts: HashMap<u64, u64, BuildHasherDefault<LabelHasher>> = HashMap::default();
+        for &label in &self.labels {
+            *counts.entry(label).or_insert(0) += 1;
+        }
+        let mut pairs: Vec<[u64; 2]> = counts
+            .into_iter()
+            .map(|(label, count)| [label, count])
+            .collect();
+        pairs.sort_unstable();
+        pairs
+    }
+}
+
+// Debug output for inspecting a run. The graph itself and the dimension marker are left out, only its size is shown.
+impl<N, E, Ty, Wd, Ix> std::fmt::Debug for GraphWrapper<N, E, Ty, Wd, Ix>
+where
+    N: std::cmp::Ord + Debug,
+    E: Debug,
+    Ty: EdgeType,
+    Wd: WLdim,
+    Ix: IndexType,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GraphWrapper")
+            .field("nodes", &self.graph.node_count())
+            .field("edges", &self.graph.edge_count())
+            .field("iter", &self.niters)
+            .field("n_colors", &self.colour_count())
+            .field("labels", &self.labels)
+            .finish()
+    }
+}
+
+// A compact one-line summary
+impl<N, E, Ty, Wd, Ix> std::fmt::Display for GraphWrapper<N, E, Ty, Wd, Ix>
+where
+    N: std::cmp::Ord,
+    Ty: EdgeType,
+    Wd: WLdim,
+    Ix: IndexType,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "WL[{}n {}e iter={}]",
+            self.graph.node_count(),
+            self.graph.edge_count(),
+            self.niters
+        )
     }
 }
 
@@ -487,29 +1250,273 @@ mod tests {
         assert_eq!(wl1.get_results(), wl2.get_results());
     }
 
-    // #[test]
-    // fn examples_practical_isomorphism() {
-    //     let g = ungraph_from_edgelist("graphs/practical/is-iso1.edgelist");
-    //     let f = ungraph_from_edgelist("graphs/practical/is-iso2.edgelist");
-    //     let mut wl_graphg = GraphWrapper::new(g, 42, 0, true);
-    //     let mut wl_graphf = GraphWrapper::new(f, 42, 0, true);
-    //     // This is the test based on the practical isomorphism paper where two of them are actually isomorphic, so important it does not return difference:
-    //     assert_eq!(wl_graphf.run(), wl_graphg.run());
-
-    //     let g = ungraph_from_edgelist("graphs/practical/not-iso1.edgelist");
-    //     let f = ungraph_from_edgelist("graphs/practical/not-iso1.edgelist");
-    //     let mut wl_graphg = GraphWrapper::new(g, 42, 0, true);
-    //     let mut wl_graphf = GraphWrapper::new(f, 42, 0, true);
-    //     // Here they are *not* isomorphic, but is constructed in such a way WL cannot distinguish it, so again relevant it would not return unequal:
-    //     assert_eq!(wl_graphf.run(), wl_graphg.run());
-    // }
-    // #[test] # doesn't work on git since i'm not pushing graphs -- probably should reconsider?
-    // fn weird_test() {
-    //     let g = ungraph_from_edgelist("graphs/rantree-iso/rantree-000020.edgelist");
-    //     let f = ungraph_from_edgelist("graphs/rantree-iso/rantree-000020-iso.edgelist");
-    //     let mut wl_graphg = GraphWrapper::new(g, 42, 0, true);
-    //     let mut wl_graphf = GraphWrapper::new(f, 42, 0, true);
-    //     // This is the test based on the practical isomorphism paper where two of them are actually isomorphic, so important it does not return difference:
-    //     assert_eq!(wl_graphf.run(), wl_graphg.run());
-    // }
+    #[test]
+    fn run_twice() {
+        let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (1, 5)]);
+        let mut once = GraphWrapper::new(g.clone(), 42, 0, true, true);
+        once.run();
+        let mut twice = GraphWrapper::new(g.clone(), 42, 0, true, true);
+        twice.run();
+        twice.run();
+        assert_eq!(twice.labels(), once.labels());
+        assert_eq!(twice.subgraphs, once.subgraphs);
+        // Later runs do not see any labels
+        let mut streamed = 0;
+        twice.run_streaming(|_| streamed += 1);
+        assert_eq!(streamed, 0);
+        assert!(!twice.run_until(|_| false));
+        assert_eq!(twice.get_results(), once.get_results());
+
+        let mut once: GraphWrapper<(), (), _, TwoWL> =
+            GraphWrapper::new_2wl(g.clone(), 42, 0, true, false);
+        once.run();
+        let mut twice: GraphWrapper<(), (), _, TwoWL> =
+            GraphWrapper::new_2wl(g, 42, 0, true, false);
+        twice.run();
+        twice.run();
+        assert_eq!(twice.labels(), once.labels());
+    }
+
+    #[test]
+    fn run_and_labels() {
+        let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+        let mut wrap = GraphWrapper::new(g.clone(), 42, 0, true, false);
+        let (hash, labels) = wrap.run_and_labels();
+        assert_eq!(wrap.labels(), labels);
+        // The tail's end differs from the triangle nodes it does not touch, which are alike
+        assert_eq!(labels[0], labels[1]);
+        assert_ne!(labels[0], labels[3]);
+        assert_eq!(hash, wrap.get_results());
+
+        let mut wrap: GraphWrapper<(), (), _, TwoWL> =
+            GraphWrapper::new_2wl(g.clone(), 42, 0, true, false);
+        let (hash, labels) = wrap.run_and_labels();
+        assert_eq!(wrap.labels(), labels);
+        assert_eq!(hash, wrap.get_results());
+    }
+
+    #[test]
+    fn reset() {
+        let small = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+        let large = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (1, 5)]);
+        let directed = DiGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+        // Fixed iterations with subgraphs, and running to stability
+        for (niters, check_stable) in [(3, false), (0, true)] {
+            let mut wrap = GraphWrapper::new(small.clone(), 7, niters, check_stable, true)
+                .with_sum_aggregation()
+                .with_stats();
+            wrap.run();
+            let mut wrap = wrap.reset(large.clone());
+            wrap.run();
+            // The settings carry over, and the counters start from scratch
+            let mut sum = GraphWrapper::new(large.clone(), 7, niters, check_stable, true)
+                .with_sum_aggregation()
+                .with_stats();
+            sum.run();
+            assert_eq!(wrap.subgraphs, sum.subgraphs);
+            let (stats, expected) = (wrap.stats().unwrap(), sum.stats().unwrap());
+            assert_eq!(stats.iterations, expected.iterations);
+            assert_eq!(stats.label_computations, expected.label_computations);
+            assert_eq!(stats.bytes_hashed, expected.bytes_hashed);
+
+            let mut wrap = wrap
+                .reset(small.clone())
+                .with_aggregation(AggregationMode::SortedHash);
+            wrap.run();
+            let mut expected = GraphWrapper::new(small.clone(), 7, niters, check_stable, true);
+            expected.run();
+            assert_eq!(wrap.subgraphs, expected.subgraphs);
+            assert_eq!(wrap.get_results(), expected.get_results());
+
+            let mut wrap = wrap.reset(directed.clone());
+            wrap.run();
+            let mut expected = GraphWrapper::new(directed.clone(), 7, niters, check_stable, true);
+            expected.run();
+            assert_eq!(wrap.subgraphs, expected.subgraphs);
+            assert_eq!(wrap.get_results(), expected.get_results());
+        }
+    }
+
+    #[test]
+    fn colour_count() {
+        // A complete graph is regular, so refining it never splits the single class
+        let complete =
+            UnGraph::<(), ()>::from_edges((0..5u32).flat_map(|a| (a + 1..5).map(move |b| (a, b))));
+        let mut wl = GraphWrapper::new(complete, 42, 2, false, false);
+        wl.run();
+        assert_eq!(wl.colour_count(), 1);
+        assert!(!wl.is_individualized());
+        // Neither is the triangular prism, a regular graph on 6 nodes
+        let prism = UnGraph::<(), ()>::from_edges([
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (3, 4),
+            (4, 5),
+            (5, 3),
+            (0, 3),
+            (1, 4),
+            (2, 5),
+        ]);
+        let mut wl = GraphWrapper::new(prism, 42, 0, true, false);
+        wl.run();
+        assert_eq!(wl.colour_count(), 1);
+        assert!(!wl.is_individualized());
+        // A spider with legs of lengths 1, 2 and 3 has no automorphisms, and WL gives every node its own colour
+        let spider =
+            UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (2, 6)]);
+        let mut wl = GraphWrapper::new(spider, 42, 0, true, false);
+        wl.run();
+        assert_eq!(wl.colour_count(), 7);
+        assert!(wl.is_individualized());
+    }
+
+    #[test]
+    fn lockstep_degree_mismatch() {
+        let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
+        let g2 = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3), (0, 4)]);
+        let mut wl1 = GraphWrapper::new(g, 42, 0, true, false);
+        let mut wl2 = GraphWrapper::new(g2, 42, 0, true, false);
+        // Only the initial (degree) labelling should have been compared
+        assert_eq!(lockstep_compare(&mut wl1, &mut wl2), (false, 1));
+    }
+    #[test]
+    fn lockstep_late_divergence() {
+        // Same degree sequence, but the position of the degree-3 node differs
+        let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (1, 5)]);
+        let g2 = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (2, 5)]);
+        let mut wl1 = GraphWrapper::new(g, 42, 0, true, false);
+        let mut wl2 = GraphWrapper::new(g2, 42, 0, true, false);
+        assert_eq!(lockstep_compare(&mut wl1, &mut wl2), (false, 2));
+    }
+    #[test]
+    fn lockstep_isomorphic() {
+        let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
+        let g2 = UnGraph::<(), ()>::from_edges([(1, 0), (2, 1), (2, 3), (4, 3)]);
+        let mut wl1 = GraphWrapper::new(g, 42, 0, true, false);
+        let mut wl2 = GraphWrapper::new(g2, 42, 0, true, false);
+        let (equal, compared) = lockstep_compare(&mut wl1, &mut wl2);
+        assert!(equal);
+        assert!(compared > 1);
+    }
+
+    // The 2-dimensional refinement step as it was first written, looking up every pair's index separately
+    fn reference_2wl_step(labels: &[u64], n: usize, seed: u64) -> Vec<u64> {
+        let mut new_labels = vec![0; labels.len()];
+        for left in 0..n {
+            for right in 0..=left {
+                let mut input_hashes: Vec<[u64; 2]> = Vec::with_capacity(n);
+                for alternative in 0..n {
+                    let left_replace = labels[get_label_index(alternative, right)];
+                    let right_replace = labels[get_label_index(left, alternative)];
+                    if left_replace < right_replace {
+                        input_hashes.push([left_replace, right_replace]);
+                    } else {
+                        input_hashes.push([right_replace, left_replace])
+                    }
+                }
+                input_hashes.sort_unstable();
+                let mut flat: Vec<u64> = input_hashes.into_iter().flatten().collect();
+                let current_index = get_label_index(left, right);
+                flat.push(labels[current_index]);
+                new_labels[current_index] = XxHash64::oneshot(seed, bytemuck::cast_slice(&flat));
+            }
+        }
+        new_labels
+    }
+
+    fn get_label_index(mut left: usize, mut right: usize) -> usize {
+        if right > left {
+            (left, right) = (right, left);
+        }
+        (left * left + left) / 2 + right
+    }
+
+    #[test]
+    fn row_offsets_2wl() {
+        for seed in 0..10 {
+            let g = crate::generators::gnp(9, 0.4, seed);
+            let n = g.node_count();
+            let mut wl = GraphWrapper::new_2wl(g, 42, 0, true, false);
+            wl.initial_graph();
+            for _ in 0..4 {
+                let expected = reference_2wl_step(&wl.labels, n, 42);
+                wl.calculate_new_labels();
+                assert_eq!(wl.new_labels, expected);
+                wl.update_graph();
+            }
+        }
+    }
+
+    #[test]
+    fn debug_and_display() {
+        let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+        let mut wl = GraphWrapper::new(g, 42, 0, true, false);
+        wl.run();
+        let debug = format!("{:?}", wl);
+        for field in [
+            "GraphWrapper {",
+            "nodes: 3",
+            "edges: 2",
+            "iter: 2",
+            "n_colors: 2",
+            "labels: [",
+        ] {
+            assert!(debug.contains(field), "{}", debug);
+        }
+        assert!(!debug.contains("_dim"), "{}", debug);
+        assert_eq!(wl.to_string(), "WL[3n 2e iter=2]");
+    }
+
+    #[test]
+    fn rollback() {
+        // A path of 16 nodes is not stable before 8 iterations
+        let path = UnGraph::<(), ()>::from_edges((0..15).map(|node| (node, node + 1)));
+        let mut wl = GraphWrapper::new(path.clone(), 42, 5, false, true).with_rollback(4);
+        wl.run();
+        wl.rollback(2).unwrap();
+        wl.resume(4).unwrap();
+        let mut seven = GraphWrapper::new(path.clone(), 42, 7, false, true);
+        seven.run();
+        assert_eq!(wl.subgraphs, seven.subgraphs);
+        assert_eq!(wl.get_results(), seven.get_results());
+
+        // Four iterations are kept
+        assert!(matches!(
+            wl.rollback(5),
+            Err(WlError::RollbackTooFar {
+                requested: 5,
+                available: 4
+            })
+        ));
+        let mut wl = GraphWrapper::new(path, 42, 5, false, false);
+        wl.run();
+        assert!(wl.rollback(1).is_err());
+        assert!(wl.rollback(0).is_ok());
+    }
+
+    #[test]
+    fn resume() {
+        let path = UnGraph::<(), ()>::from_edges((0..15).map(|node| (node, node + 1)));
+        // There are no labels to continue from before the run
+        let mut wl = GraphWrapper::new(path.clone(), 42, 3, false, false);
+        assert!(matches!(wl.resume(1), Err(WlError::NotRun)));
+
+        // The initial labels and three iterations of 16 labels each fit in the budget, of which the run takes two
+        let mut wl = GraphWrapper::new(path, 42, 3, false, false).with_label_budget(64);
+        wl.run();
+        assert_eq!(wl.budget_exceeded(), None);
+        assert!(wl.resume(1).is_ok());
+        let labels = wl.labels().to_vec();
+        assert!(matches!(
+            wl.resume(1),
+            Err(WlError::LimitExceeded {
+                which: LimitKind::LabelHashes,
+                limit: 64,
+                observed: 80,
+                iterations: 3
+            })
+        ));
+        assert_eq!(wl.labels(), labels);
+    }
 }
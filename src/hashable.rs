@@ -0,0 +1,39 @@
+use crate::invariant;
+use petgraph::graph::{DefaultIx, IndexType};
+use petgraph::{EdgeType, Graph};
+use std::hash::{Hash, Hasher};
+
+/// A graph that is hashed and compared by its WL [`invariant`](fn.invariant.html), so that graphs can be deduplicated in a `HashSet` or used as `HashMap` keys. The invariant is recomputed on every call to `hash` or `eq`, so for large graphs or many lookups it is cheaper to key on the invariant itself.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use std::collections::HashSet;
+/// use wl_isomorphism::WlHashable;
+///
+/// let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+/// let relabelled = UnGraph::<(), ()>::from_edges([(2, 0), (0, 1)]);
+/// let set: HashSet<_> = [WlHashable(path), WlHashable(relabelled)].into_iter().collect();
+/// assert_eq!(set.len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct WlHashable<N, E, Ty: EdgeType, Ix: IndexType = DefaultIx>(pub Graph<N, E, Ty, Ix>);
+
+impl<N: Ord + Clone, E: Clone, Ty: EdgeType, Ix: IndexType> WlHashable<N, E, Ty, Ix> {
+    fn invariant(&self) -> u64 {
+        invariant(self.0.clone())
+    }
+}
+
+impl<N: Ord + Clone, E: Clone, Ty: EdgeType, Ix: IndexType> Hash for WlHashable<N, E, Ty, Ix> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.invariant());
+    }
+}
+
+impl<N: Ord + Clone, E: Clone, Ty: EdgeType, Ix: IndexType> PartialEq for WlHashable<N, E, Ty, Ix> {
+    fn eq(&self, other: &Self) -> bool {
+        self.invariant() == other.invariant()
+    }
+}
+
+/// Note that this equality is only a sound approximation of isomorphism: isomorphic graphs are always equal, but graphs that WL cannot distinguish (such as some pairs of regular graphs) are equal as well, so a `HashSet` may merge non-isomorphic graphs.
+impl<N: Ord + Clone, E: Clone, Ty: EdgeType, Ix: IndexType> Eq for WlHashable<N, E, Ty, Ix> {}
@@ -0,0 +1,115 @@
+use crate::graphwrapper::GraphWrapper;
+use petgraph::graph::IndexType;
+use petgraph::{EdgeType, Graph};
+use twox_hash::XxHash64;
+
+/// A compact set of graph invariants, answering "have I seen a graph that WL cannot distinguish from this one?". It is a Bloom filter, so it never forgets an inserted invariant, but may claim to contain one that was never inserted, with (about) the false positive rate it was created with. With the `serde` feature it can be serialised, to keep it between runs.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use wl_isomorphism::InvariantIndex;
+///
+/// let mut index = InvariantIndex::new(1000, 0.01);
+/// index.insert_graph(UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]));
+/// assert!(index.contains_graph(UnGraph::<(), ()>::from_edges([(1, 0), (0, 2)])));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InvariantIndex {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+    seed: u64,
+    len: usize,
+}
+
+impl InvariantIndex {
+    /// Create an index sized for `expected_items` invariants at the given false positive rate (between 0 and 1, exclusive). Graphs are hashed with the default seed 42, as in [`invariant`](fn.invariant.html).
+    ///
+    /// Panics if the false positive rate is not between 0 and 1.
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        Self::with_seed(expected_items, false_positive_rate, 42)
+    }
+
+    /// Like [`new`](struct.InvariantIndex.html#method.new), but graphs are hashed with `seed`. Hashes passed to [`insert`](struct.InvariantIndex.html#method.insert) must be computed with the same seed to be comparable.
+    pub fn with_seed(expected_items: usize, false_positive_rate: f64, seed: u64) -> Self {
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "The false positive rate must be between 0 and 1"
+        );
+        // The optimal size is -n ln(p) / ln(2)^2 bits, with (m / n) ln(2) hash functions
+        let n = expected_items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = ((-n * false_positive_rate.ln() / (ln2 * ln2)).ceil() as u64).max(64);
+        let num_hashes = ((num_bits as f64 / n * ln2).round() as u32).max(1);
+        InvariantIndex {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            num_hashes,
+            seed,
+            len: 0,
+        }
+    }
+
+    /// The seed used to hash graphs.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The number of insertions so far (counting duplicates).
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether nothing was inserted yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Add an invariant to the index.
+    pub fn insert(&mut self, hash: u64) {
+        for bit in self.bit_positions(hash) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+        self.len += 1;
+    }
+
+    /// Whether the invariant was (probably) inserted before. Inserted invariants are always found.
+    pub fn contains(&self, hash: u64) -> bool {
+        self.bit_positions(hash)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Compute the invariant of the graph with the index's seed and add it.
+    pub fn insert_graph<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+        &mut self,
+        graph: Graph<N, E, Ty, Ix>,
+    ) {
+        let hash = self.hash_graph(graph);
+        self.insert(hash);
+    }
+
+    /// Whether a graph with the same invariant (under the index's seed) was (probably) inserted before.
+    pub fn contains_graph<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+        &self,
+        graph: Graph<N, E, Ty, Ix>,
+    ) -> bool {
+        self.contains(self.hash_graph(graph))
+    }
+
+    fn hash_graph<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+        &self,
+        graph: Graph<N, E, Ty, Ix>,
+    ) -> u64 {
+        let mut wrap = GraphWrapper::new(graph, self.seed, 0, true, false);
+        wrap.run();
+        wrap.get_results()
+    }
+
+    // Double hashing: the i-th position is h1 + i * h2, which is as good as independent hash functions for a Bloom filter
+    fn bit_positions(&self, hash: u64) -> impl Iterator<Item = u64> {
+        let h1 = XxHash64::oneshot(1, &hash.to_le_bytes());
+        let h2 = XxHash64::oneshot(2, &hash.to_le_bytes()) | 1;
+        let num_bits = self.num_bits;
+        (0..self.num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+}
@@ -0,0 +1,74 @@
+use crate::graphwrapper::GraphWrapper;
+use petgraph::graph::{NodeIndex, UnGraph};
+use twox_hash::XxHash64;
+
+/// Build the interval graph of closed intervals `(start, end)`: node `i` is the `i`th interval, and two nodes are adjacent iff their intervals overlap (touching counts). An interval with `start > end` is read as `(end, start)`. The edges are found with a sweep over the sorted endpoints, in O(n log n + m) time.
+pub fn interval_graph<N: Ord + Copy>(intervals: &[(N, N)]) -> UnGraph<(), ()> {
+    let mut graph = UnGraph::with_capacity(intervals.len(), 0);
+    for _ in intervals {
+        graph.add_node(());
+    }
+    // Starts sort before ends at the same coordinate, so that touching intervals overlap
+    let mut events: Vec<(N, bool, usize)> = Vec::with_capacity(2 * intervals.len());
+    for (index, &interval) in intervals.iter().enumerate() {
+        let (start, end) = ordered(interval);
+        events.push((start, false, index));
+        events.push((end, true, index));
+    }
+    events.sort_unstable_by_key(|&(coordinate, is_end, _)| (coordinate, is_end));
+
+    // The intervals that are currently open, with the position of every interval in that list
+    let mut open: Vec<usize> = Vec::new();
+    let mut position = vec![usize::MAX; intervals.len()];
+    for (_, is_end, index) in events {
+        if is_end {
+            let at = position[index];
+            open.swap_remove(at);
+            if let Some(&moved) = open.get(at) {
+                position[moved] = at;
+            }
+        } else {
+            for &other in &open {
+                graph.add_edge(NodeIndex::new(other), NodeIndex::new(index), ());
+            }
+            position[index] = open.len();
+            open.push(index);
+        }
+    }
+    graph
+}
+
+/// Calculate a WL invariant of the interval graph of `intervals` (see [`interval_graph`](fn.interval_graph.html)), starting from labels that combine the degree with the length of the interval. The length is measured as the number of distinct endpoints it spans, so only the order of the coordinates matters. Longer intervals tend to have more neighbours, and the lengths often separate nodes that the degrees alone do not. Automatically stabilises.
+///
+/// Note that this is an invariant of the interval model rather than of the graph alone: it does not change when the intervals are reordered or the coordinates are transformed in an order-preserving way, but two different interval models of the same graph may get different hashes. Use [`invariant`](fn.invariant.html) on [`interval_graph`](fn.interval_graph.html) to compare with graphs that have no interval model.
+pub fn invariant_interval<N: Ord + Copy>(intervals: &[(N, N)]) -> u64 {
+    let graph = interval_graph(intervals);
+    let mut endpoints: Vec<N> = intervals
+        .iter()
+        .flat_map(|&(start, end)| [start, end])
+        .collect();
+    endpoints.sort_unstable();
+    endpoints.dedup();
+    let rank = |x: N| endpoints.binary_search(&x).unwrap();
+    let initial = intervals
+        .iter()
+        .enumerate()
+        .map(|(index, &interval)| {
+            let (start, end) = ordered(interval);
+            let degree = graph.neighbors(NodeIndex::new(index)).count();
+            let length = rank(end) - rank(start);
+            XxHash64::oneshot(42, bytemuck::cast_slice(&[degree as u64, length as u64]))
+        })
+        .collect();
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false).with_initial_labels(initial);
+    wrap.run();
+    wrap.get_results()
+}
+
+fn ordered<N: Ord>((start, end): (N, N)) -> (N, N) {
+    if start <= end {
+        (start, end)
+    } else {
+        (end, start)
+    }
+}
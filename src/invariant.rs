@@ -0,0 +1,176 @@
+use crate::context::HASH_VERSION;
+use crate::error::WlError;
+use crate::{invariant, invariant_2wl};
+use petgraph::graph::IndexType;
+use petgraph::{EdgeType, Graph, Undirected};
+use std::fmt;
+use std::str::FromStr;
+
+/// A graph invariant together with the WL dimension and the version of the labelling scheme that computed it. It is displayed (and parsed) as the dimension and the scheme version followed by the hash as 16 hex digits, like `wl1v2:e2c0ea7fd44d1ab4`, which is easier to compare by eye and to paste into reports than a decimal `u64`, and cannot be mistaken for a count. Invariants from the first scheme leave the version out, like `wl1:e2c0ea7fd44d1ab4`, as they were written before it was recorded. With the `serde` feature it is serialised as that string.
+///
+/// It compares equal to the plain `u64` hash, so code that still stores the numbers keeps working.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use wl_isomorphism::Invariant;
+///
+/// let graph = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+/// let hash = Invariant::of(graph.clone());
+/// assert_eq!(hash, wl_isomorphism::invariant(graph));
+/// assert_eq!(hash.to_string().parse::<Invariant>().unwrap(), hash);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Invariant {
+    /// The WL dimension: 1 for [`invariant`](fn.invariant.html), 2 for [`invariant_2wl`](fn.invariant_2wl.html).
+    pub dimension: u8,
+    /// The version of the labelling scheme, see [`HASH_VERSION`](constant.HASH_VERSION.html).
+    pub scheme: u32,
+    /// The hash itself.
+    pub hash: u64,
+}
+
+impl Invariant {
+    /// The same hash as [`invariant`](fn.invariant.html).
+    pub fn of<N: Ord, E, Ty: EdgeType, Ix: IndexType>(graph: Graph<N, E, Ty, Ix>) -> Self {
+        Invariant {
+            dimension: 1,
+            scheme: HASH_VERSION,
+            hash: invariant(graph),
+        }
+    }
+
+    /// The same hash as [`invariant_2wl`](fn.invariant_2wl.html).
+    pub fn of_2wl<N: Ord, E, Ix: IndexType>(graph: Graph<N, E, Undirected, Ix>) -> Self {
+        Invariant {
+            dimension: 2,
+            scheme: HASH_VERSION,
+            hash: invariant_2wl(graph),
+        }
+    }
+
+    /// Compare with another invariant, prefer this over `==` where the outcome matters. Invariants of different dimensions or labelling schemes are never equal, but that does not mean the graphs differ, so those are reported separately. The hashes are compared in constant time, so that the time taken does not reveal how much of a stored hash was guessed right.
+    /// ```rust
+    /// use petgraph::graph::UnGraph;
+    /// use wl_isomorphism::{Invariant, MatchOutcome};
+    ///
+    /// let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+    /// let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+    /// assert_eq!(Invariant::of(path.clone()).matches(&Invariant::of(path.clone())), MatchOutcome::Match);
+    /// assert_eq!(Invariant::of(path.clone()).matches(&Invariant::of(star)), MatchOutcome::Mismatch);
+    /// assert!(!Invariant::of(path.clone()).matches(&Invariant::of_2wl(path)).is_match());
+    /// ```
+    pub fn matches(&self, other: &Invariant) -> MatchOutcome {
+        if self.dimension != other.dimension {
+            return MatchOutcome::IncomparableDimension;
+        }
+        if self.scheme != other.scheme {
+            return MatchOutcome::IncomparableScheme;
+        }
+        // Accumulate the differing bits of every byte instead of stopping at the first difference; `black_box` keeps the compiler from
+        // turning this back into an early exit
+        let difference = self
+            .hash
+            .to_le_bytes()
+            .iter()
+            .zip(other.hash.to_le_bytes())
+            .fold(0u8, |acc, (a, b)| acc | std::hint::black_box(a ^ b));
+        if difference == 0 {
+            MatchOutcome::Match
+        } else {
+            MatchOutcome::Mismatch
+        }
+    }
+}
+
+/// The result of [`Invariant::matches`](struct.Invariant.html#method.matches).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchOutcome {
+    /// The hashes are equal, so the graphs are probably isomorphic.
+    Match,
+    /// The hashes differ, so the graphs are not isomorphic.
+    Mismatch,
+    /// The invariants were computed with different versions of the labelling scheme, so they cannot be compared.
+    IncomparableScheme,
+    /// The invariants were computed with different WL dimensions, so they cannot be compared.
+    IncomparableDimension,
+}
+
+impl MatchOutcome {
+    /// Whether this is a [`Match`](enum.MatchOutcome.html#variant.Match).
+    pub fn is_match(self) -> bool {
+        self == MatchOutcome::Match
+    }
+}
+
+impl fmt::Display for Invariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "wl{}", self.dimension)?;
+        // The first scheme is left implicit, so that strings written before the version was recorded still parse
+        if self.scheme != 1 {
+            write!(f, "v{}", self.scheme)?;
+        }
+        write!(f, ":{:016x}", self.hash)
+    }
+}
+
+impl fmt::Debug for Invariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invariant({})", self)
+    }
+}
+
+impl FromStr for Invariant {
+    type Err = WlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || WlError::parse(1, format!("invalid invariant '{}'", s));
+        let (prefix, hash) = s
+            .strip_prefix("wl")
+            .and_then(|rest| rest.split_once(':'))
+            .ok_or_else(invalid)?;
+        let (dimension, scheme) = match prefix.split_once('v') {
+            Some((dimension, scheme)) => (dimension, scheme.parse().map_err(|_| invalid())?),
+            None => (prefix, 1),
+        };
+        if hash.len() != 16 || !hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        Ok(Invariant {
+            dimension: dimension.parse().map_err(|_| invalid())?,
+            scheme,
+            hash: u64::from_str_radix(hash, 16).map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl From<Invariant> for u64 {
+    fn from(invariant: Invariant) -> Self {
+        invariant.hash
+    }
+}
+
+impl PartialEq<u64> for Invariant {
+    fn eq(&self, other: &u64) -> bool {
+        self.hash == *other
+    }
+}
+
+impl PartialEq<Invariant> for u64 {
+    fn eq(&self, other: &Invariant) -> bool {
+        *self == other.hash
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Invariant {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Invariant {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -29,64 +29,764 @@
 //! * <b> The WL algorithm is not a complete isomorphism test</b>. This means that when the algorithm returns the same hash for two graphs, they are *possibly* isomorphic, but not guaranteed. On certain classes of graphs (such as random graphs) this is almost always a good indicator of isomorphism, but it is for example not trustworthy on regular graphs. It is, however, a *sound* test, meaning that if the algorithm returns different hashes, the graphs are guaranteed to be non-isomorphic.
 //! * <b> Hash values depend on the number of iterations</b>. For algorithms with a fixed iteration count, even the same graph will yield different hashes for different iteration counts.
 //! * <b> Hash values depend on device endianness</b>. The same graph will produce different hashes on little-endian and big-endian systems. Compare hashes only on the same device or verify results using example graphs.
+//! * <b> Without the default `std` feature, only the CSR functions are built</b>. Graphs are petgraph graphs, and petgraph 0.6 cannot be built without the standard library, but [`refine_csr`](fn.refine_csr.html), [`refine_csr_directed`](fn.refine_csr_directed.html), [`invariant_from_adj_list`](fn.invariant_from_adj_list.html) and [`invariant_from_adj_list_directed`](fn.invariant_from_adj_list_directed.html) only need `alloc`. With `default-features = false` the crate is `no_std`, for embedded targets, and these functions return the same hashes as with `std`.
+//! * <b> Graphs are petgraph 0.6 graphs</b>, version 0.6.5 or later within 0.6. Graphs from another major version of petgraph are different types, which shows up as confusing errors like "expected `Graph`, found `Graph`". To avoid that, build graphs with the petgraph re-exported as `wl_isomorphism::petgraph`, or import [`prelude`](prelude/index.html), which has the graph types as well as the main functions, and [`ungraph_from_edges`](fn.ungraph_from_edges.html) and [`digraph_from_edges`](fn.digraph_from_edges.html) to build graphs without naming petgraph types.
 //!
 //! # Features
 //! * <b>Isomorphism testing</b>.  
 //!     * Calculate a graph's hash to compare it with other graphs' hashes to determine if they are isomorphic.  
 //!     * Use [`invariant`](fn.invariant.html), or if you want the algorithm to run for a specific number of iterations, use [`invariant_iters`](fn.invariant_iters.html).
+//!     * For graphs with node and edge weights, such as molecules, [`invariant_full_attr`](fn.invariant_full_attr.html) starts from the node weights and pairs every neighbour with the weight of the edge to it.
+//!     * [`WlConfig`](struct.WlConfig.html) collects settings like the seed, whether parallel edges count separately ([`MultigraphPolicy`](enum.MultigraphPolicy.html)), how self-loops count ([`SelfLoopPolicy`](enum.SelfLoopPolicy.html)) how the neighbour labels are combined ([`AggregationMode`](enum.AggregationMode.html)) and how the direction of edges counts ([`DirectedMode`](enum.DirectedMode.html)). For graphs from untrusted sources, [`WlLimits`](struct.WlLimits.html) bound the size of a graph and the work done for it.
+//!     * To switch between directed and undirected graphs before hashing, [`to_undirected_skeleton`](fn.to_undirected_skeleton.html) makes every edge undirected, merging reciprocal pairs as chosen with a [`ReciprocalPolicy`](enum.ReciprocalPolicy.html), and [`to_symmetric_digraph`](fn.to_symmetric_digraph.html) replaces every undirected edge with a pair of reciprocal ones.
+//!     * To test that a computation does not depend on the order of the nodes, [`apply_node_permutation`](fn.apply_node_permutation.html) renumbers them.
 //!     * Alternatively, use the two-dimensional versions of these, [`invariant_2wl`](fn.invariant_wl.html) and [`iter_2wl`](fn.iter_2wl.html), which offer greater distinguishing power—particularly for regular graphs—at the cost of a significant runtime penalty.
+//!     * [`pair_classes_2wl`](fn.pair_classes_2wl.html) lists which pairs of nodes 2-dimensional WL considers equivalent, e.g. to seed an exact matcher.
+//!     * The pair classes refine the shortest-path distances, which [`distance_matrix_via_bfs`](fn.distance_matrix_via_bfs.html) computes; [`pair_distance_classes`](fn.pair_distance_classes.html) stops after a few iterations, which is enough to tell apart short distances.
+//!     * To compare two graphs in one call, [`compare`](fn.compare.html) escalates from 1-dimensional to 2-dimensional WL and finally an exact test on small graphs, and reports which step decided.
+//!     * For the strongest hash within a budget, [`invariant_combined`](fn.invariant_combined.html) gives a [`CombinedInvariant`](struct.CombinedInvariant.html) of both invariants, leaving out 2-dimensional WL for graphs over the node limit of the [`WlBudget`](struct.WlBudget.html).
+//!     * For graphs that are almost isomorphic, [`prefix_match_iters`](fn.prefix_match_iters.html) counts how many iterations WL needs to tell them apart.
+//!     * For research on the power of WL, [`distinguishing_dimension`](fn.distinguishing_dimension.html) reports the smallest dimension that tells two graphs apart.
+//!     * To see what structure two graphs share, [`wl_color_intersection`](fn.wl_color_intersection.html) counts the colour classes that occur in both.
+//!     * For graphs that are almost isomorphic, [`wl_edit_script`](fn.wl_edit_script.html) aligns the nodes by their colours and lists the edits that turn one into the other.
+//!     * [`Invariant`](struct.Invariant.html) wraps a hash with its WL dimension, and displays it in hex, like `wl1v2:e2c0ea7fd44d1ab4`. Compare them with [`Invariant::matches`](struct.Invariant.html#method.matches), which tells invariants of other dimensions or labelling schemes apart from real mismatches, and compares the hashes in constant time.
+//!     * To find the graphs of a corpus that are possibly isomorphic to a query, use [`search_corpus`](fn.search_corpus.html), or [`search_corpus_2wl`](fn.search_corpus_2wl.html) to match on 2-dimensional WL.
+//!     * To check a new graph against a large corpus of seen graphs, an [`InvariantIndex`](struct.InvariantIndex.html) stores their invariants compactly in a Bloom filter.
+//!     * Wrap graphs in [`WlHashable`](struct.WlHashable.html) to deduplicate them in a `HashSet` or use them as `HashMap` keys, with WL deciding equality.
+//!     * For large graphs with few colour classes, [`invariant_counted`](fn.invariant_counted.html) hashes the number of nodes of every colour rather than sorting all labels.
+//!     * [`invariant_width`](fn.invariant_width.html) gives 32-bit hashes to save memory, or 128-bit ones with fewer collisions, as chosen with [`HashWidth`](enum.HashWidth.html).
+//!     * To store a graph's colouring and check new graphs against it later without refining the stored graph again, [`certificate`](fn.certificate.html) gives a [`Certificate`](struct.Certificate.html) with a compact byte encoding.
+//!     * [`invariant_sum_agg`](fn.invariant_sum_agg.html) sums the neighbour labels instead of sorting them, as in the WL optimal assignment kernel, which is faster on dense graphs.
+//!     * On graphs that need many iterations to stabilise, such as long paths and trees, [`invariant_pt`](fn.invariant_pt.html) computes the same colouring with partition refinement in O((n + m) log n) time.
+//!     * [`invariant_distance_encoded`](fn.invariant_distance_encoded.html) refines on the labels of all nodes within `k` hops together with their distance, which tells apart graphs like a 6-cycle and two triangles that plain WL confuses.
+//!     * [`invariant_spectral_init`](fn.invariant_spectral_init.html) starts the refinement from the Fiedler vectors of the graph, which tells apart some regular graphs that plain WL confuses, at a cost of O(n^3) time.
+//!     * [`invariant_rwpe`](fn.invariant_rwpe.html) starts the refinement from the probability that a random walk returns to its start after a number of steps, which sees the short cycles through every node.
+//!     * For directed graphs, [`invariant_condensation`](fn.invariant_condensation.html) hashes the DAG of strongly connected components with their sizes, which tells apart e.g. one long cycle from several short ones.
+//!     * Hypergraphs, such as co-authorship networks, are hashed with [`invariant_hypergraph`](fn.invariant_hypergraph.html), which refines nodes and hyperedges in turn.
+//!     * For logging, [`summarise`](fn.summarise.html) reports the size, density, maximum degree and number of WL classes of a graph together with its invariant.
+//!     * [`validate`](fn.validate.html) lists the isolated nodes, self-loops, parallel edges and reciprocal edges of a graph as [`GraphWarning`](enum.GraphWarning.html)s, which often come from mistakes in the input, and [`invariant_validated`](fn.invariant_validated.html) returns them together with the invariant.
 //! * <b>Subgraph hashing </b>.  
 //!     * Obtain subgraph hashes for each node at each iteration for tasks like feature extraction for graph kernels.
 //!     * Use [`neighbourhood_hash`](fn.neighbourhood_hash.html) for a fixed number of iterations  or [`neighbourhood_stable`](fn.neighbourhood_stable.html) to run until stabilisation.
+//!     * When the invariant is needed as well, [`invariant_and_neighbourhood_hash`](fn.invariant_and_neighbourhood_hash.html) and [`invariant_and_neighbourhood_stable`](fn.invariant_and_neighbourhood_stable.html) compute both in one run.
+//!     * For motif-aware features, [`ego_invariants`](fn.ego_invariants.html) hashes the subgraph within a given radius of every node on its own, so that only the local structure counts.
+//!     * [`wl_motif_lower_bound`](fn.wl_motif_lower_bound.html) counts the embeddings of a small motif that respect the stable colours, a lower bound on the number of its embeddings.
+//!     * For GNNs that work on edges, [`edge_neighbourhood_hash`](fn.edge_neighbourhood_hash.html) hashes every edge from its endpoints, its weight and the edges around it.
+//!     * For a whole dataset, [`neighbourhood_hash_batch`](fn.neighbourhood_hash_batch.html) hashes many graphs at once, in parallel with the `rayon` feature.
+//!     * With the `arrow` feature, `neighbourhood_hash_to_arrow` returns the hashes as an Arrow chunk for data science pipelines, and `invariant_from_arrow` and `neighbourhood_hash_from_arrow` take the graph from two Arrow columns of edge endpoints.
+//! * <b>Feature extraction</b>.
+//!     * Turn graphs into fixed-length feature vectors with [`hashed_feature_vector`](fn.hashed_feature_vector.html), or compare a whole corpus at once with [`wl_distance_matrix`](fn.wl_distance_matrix.html).
+//!     * [`wl_hierarchical_cluster`](fn.wl_hierarchical_cluster.html) groups a corpus at every WL depth, from the degree sequences to the stable invariants, as a [`Dendrogram`](struct.Dendrogram.html).
+//!     * For near-duplicate detection, [`invariant_lsh`](fn.invariant_lsh.html) summarises the WL features of a graph in MinHash values, and [`lsh_similarity`](fn.lsh_similarity.html) estimates from them how many features two graphs share.
+//!     * [`wl_boundary_edges`](fn.wl_boundary_edges.html) counts the edges between different colour classes, a measure of the structure WL finds.
+//!     * [`dense_colours`](fn.dense_colours.html) numbers the stable colour classes `0..k` in an order that does not depend on the hash values, so that isomorphic graphs give corresponding nodes the same id, e.g. for table columns or feature names.
+//!     * [`colour_adjacency_counts`](fn.colour_adjacency_counts.html) counts the edges between every pair of stable colours, a flat version of the quotient graph.
+//! * <b>Refinement on plain arrays</b>.
+//!     * Graphs stored outside petgraph can be refined directly from CSR adjacency lists with [`refine_csr`](fn.refine_csr.html) or [`refine_csr_directed`](fn.refine_csr_directed.html).
+//!     * Plain `Vec<Vec<usize>>` adjacency lists are hashed with [`invariant_from_adj_list`](fn.invariant_from_adj_list.html) and [`invariant_from_adj_list_directed`](fn.invariant_from_adj_list_directed.html).
+//!     * Filtered views of petgraph graphs, like `NodeFiltered`, are hashed without copying with [`invariant_of_view`](fn.invariant_of_view.html).
+//! * <b>Controlling a run</b>.
+//!     * [`GraphWrapper`](struct.GraphWrapper.html) runs WL step by step. With `with_rollback` it keeps the labels of the last iterations, so that a run can go back with `rollback` and continue with `resume`.
+//!     * To choose `n_iters` for a class of graphs, [`refinement_steps_distribution`](fn.refinement_steps_distribution.html) counts how many iterations the graphs of a sample need to stabilise, and [`max_refinement_steps`](fn.max_refinement_steps.html) gives the largest of them.
+//!     * To judge how far a run got, `colour_count` gives the number of colour classes and `is_individualized` whether every node has a colour of its own.
+//!     * With the `tracing` feature, every run of a `GraphWrapper` is a `wl_run` span, with a `calculate_new_labels` span per iteration and a debug event with the number of colours after it, for profiling in production.
 //! * <b>Dot file output</b>.
 //!     * Write the graph to a dot file, where the colour class of each node is visualised.
 //!     * Use [`invariant_dot`](fn.invariant_dot.html) or [`iter_dot`](fn.iter_dot.html).
+//!     * [`invariant_dot_graphmap`](fn.invariant_dot_graphmap.html) writes a `GraphMap` in the same way.
+//!     * With [`invariant_dot_with_options`](fn.invariant_dot_with_options.html), edges can be coloured by the classes of their endpoints as well.
+//!     * [`invariant_dot_weighted`](fn.invariant_dot_weighted.html) labels the nodes with their weights instead of their indices, and the classes can be drawn as clusters.
+//!     * The same colours are available for other visualisations with [`colour_assignment`](fn.colour_assignment.html), or [`stable_colour_for_class`](fn.stable_colour_for_class.html) for colours that stay the same across graphs.
 //! * <b>Read from NetworkX edgelist file</b>
 //!     * Load graphs from text files in the NetworkX edgelist format.
 //!     *  Use [`ungraph_from_edgelist`](fn.ungraph_from_edgelist.html) or [`digraph_from_edgelist`](fn.digraph_from_edgelist.html).
+//!     * For datasets in mixed formats, [`graph_from_file`](fn.graph_from_file.html) also reads GML, DIMACS and GraphML, detecting the format from the contents.
+//!     * Dense 0/1 matrices are read with [`ungraph_from_adjacency_matrix`](fn.ungraph_from_adjacency_matrix.html) or [`digraph_from_adjacency_matrix`](fn.digraph_from_adjacency_matrix.html).
+//!     * Weighted Pajek `.net` files are read with [`ungraph_from_pajek`](fn.ungraph_from_pajek.html) or [`digraph_from_pajek`](fn.digraph_from_pajek.html).
+//!     * Weighted edgelists are written with [`write_edgelist_weighted`](fn.write_edgelist_weighted.html) and read with [`weighted_ungraph_from_edgelist`](fn.weighted_ungraph_from_edgelist.html).
 //!
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub use petgraph;
+
+#[cfg(feature = "std")]
+mod attributed;
+#[cfg(feature = "std")]
+mod cache;
+#[cfg(feature = "std")]
+mod certificate;
+#[cfg(feature = "std")]
+mod cluster;
+#[cfg(feature = "std")]
+mod colour;
+#[cfg(feature = "std")]
+mod compare;
+#[cfg(feature = "std")]
+mod condensation;
+#[cfg(feature = "std")]
+mod config;
+#[cfg(feature = "std")]
+mod context;
+mod csr;
+#[cfg(feature = "std")]
+mod diagnostics;
+#[cfg(feature = "std")]
+mod distance;
+#[cfg(feature = "std")]
+mod edge_hash;
+#[cfg(feature = "std")]
+mod edit;
+#[cfg(feature = "std")]
+mod ego;
+#[cfg(feature = "std")]
+mod engine;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "std")]
+mod features;
+#[cfg(feature = "std")]
+mod formats;
+#[cfg(feature = "std")]
+pub mod generators;
+#[cfg(feature = "std")]
 mod graphwrapper; // Declare the graphwrapper module.
-use graphwrapper::GraphWrapper; // Re-export GraphWrapper if needed.
-use graphwrapper::{OneWL, TwoWL};
+#[cfg(feature = "std")]
+mod hashable;
+#[cfg(feature = "std")]
+mod hypergraph;
+#[cfg(feature = "std")]
+mod index;
+#[cfg(feature = "std")]
+mod interval;
+#[cfg(feature = "std")]
+mod invariant;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "std")]
+mod partition;
+#[cfg(feature = "std")]
+pub mod prelude;
+#[cfg(feature = "std")]
+mod spectral;
+#[cfg(feature = "std")]
+mod summary;
+#[cfg(feature = "std")]
+mod transform;
+#[cfg(feature = "std")]
+pub use attributed::invariant_full_attr;
+#[cfg(feature = "std")]
+pub use cache::WlCache;
+#[cfg(feature = "std")]
+pub use certificate::{certificate, Certificate};
+#[cfg(feature = "std")]
+pub use cluster::{wl_hierarchical_cluster, Dendrogram};
+#[cfg(feature = "std")]
+pub use colour::{colour_assignment, stable_colour_for_class, Rgb8};
+#[cfg(feature = "std")]
+pub use compare::{
+    compare, distinguishing_dimension, invariant_combined, search_corpus, search_corpus_2wl,
+    wl_color_intersection, CombinedInvariant, CompareOptions, Comparison, Decider, WlBudget,
+};
+#[cfg(feature = "std")]
+pub use condensation::invariant_condensation;
+#[cfg(feature = "std")]
+pub use config::{
+    AggregationMode, DirectedMode, HashWidth, LimitKind, MultigraphPolicy, SelfLoopPolicy,
+    WlConfig, WlLimits,
+};
+#[cfg(feature = "std")]
+pub use context::{ColourId, WlContext, WlHistogram, HASH_VERSION};
+#[cfg(feature = "std")]
+pub use csr::invariant_of_view;
+pub use csr::{
+    invariant_from_adj_list, invariant_from_adj_list_directed, refine_csr, refine_csr_directed,
+    WlOptions, WlStats,
+};
+#[cfg(feature = "std")]
+pub use diagnostics::{
+    diagnose, invariant_with_diffs, invariant_with_trace, max_refinement_steps,
+    refinement_steps_distribution, wl_diff, ClassDifference, DegreeHistogram, DiagnosisReport,
+    WlDiff, WlTrace,
+};
+#[cfg(feature = "std")]
+pub use distance::{distance_matrix_via_bfs, invariant_distance_encoded};
+#[cfg(feature = "std")]
+pub use edge_hash::edge_neighbourhood_hash;
+#[cfg(feature = "std")]
+pub use edit::{wl_edit_script, EditOp};
+#[cfg(feature = "std")]
+pub use ego::ego_invariants;
+#[cfg(feature = "std")]
+pub use engine::WlEngine;
+#[cfg(feature = "std")]
+pub use error::WlError;
+#[cfg(feature = "std")]
+pub use features::{
+    hashed_feature_vector, invariant_lsh, lsh_similarity, signed_hashed_feature_vector,
+    wl_distance_matrix,
+};
+#[cfg(feature = "std")]
+pub use formats::{
+    digraph_from_adjacency_matrix, digraph_from_pajek, graph_from_file,
+    ungraph_from_adjacency_matrix, ungraph_from_pajek, weighted_ungraph_from_edgelist,
+    write_edgelist_weighted,
+};
+#[cfg(feature = "std")]
+use graphwrapper::lockstep_compare;
+#[cfg(feature = "std")]
+pub use graphwrapper::{DotOptions, GraphWrapper, OneWL, TwoWL, WLdim};
+#[cfg(feature = "std")]
+pub use hashable::WlHashable;
+#[cfg(feature = "std")]
+pub use hypergraph::{invariant_hypergraph, Hypergraph};
+#[cfg(feature = "std")]
+pub use index::InvariantIndex;
+#[cfg(feature = "std")]
+pub use interval::{interval_graph, invariant_interval};
+#[cfg(feature = "std")]
+pub use invariant::{Invariant, MatchOutcome};
+#[cfg(feature = "serde")]
+pub use io::write_node_colours_json;
+#[cfg(feature = "std")]
+pub use io::{
+    colour_classes, dense_colours, pair_classes_2wl, pair_distance_classes, read_node_colours,
+    write_node_colours, PairClasses,
+};
+#[cfg(feature = "arrow")]
+pub use io::{
+    invariant_from_arrow, neighbourhood_hash_arrow_fields, neighbourhood_hash_from_arrow,
+    neighbourhood_hash_to_arrow,
+};
+#[cfg(feature = "std")]
+pub use partition::{invariant_pt, PT_HASH_VERSION};
+#[cfg(feature = "std")]
 use petgraph::Undirected;
+#[cfg(feature = "std")]
+pub use spectral::{invariant_rwpe, invariant_spectral_init};
+#[cfg(feature = "std")]
+pub use summary::{invariant_validated, summarise, validate, GraphSummary, GraphWarning};
+#[cfg(feature = "std")]
+pub use transform::{
+    apply_node_permutation, contract_edge, to_symmetric_digraph, to_undirected_skeleton,
+    ReciprocalPolicy,
+};
+#[cfg(feature = "std")]
+use transform::{connected_component_graphs, disjoint_union, structure};
 
-use petgraph::graph::{DiGraph, UnGraph};
+#[cfg(feature = "std")]
+use petgraph::graph::{DefaultIx, DiGraph, IndexType, NodeIndex, UnGraph};
+#[cfg(feature = "std")]
+use petgraph::graphmap::{GraphMap, NodeTrait};
+#[cfg(feature = "std")]
 use petgraph::{EdgeType, Graph};
+#[cfg(feature = "std")]
 use std::cmp::Ord;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "std")]
 use std::fmt::Debug;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use twox_hash::XxHash64;
+
+/// The seeds used by [`invariant_consensus`](fn.invariant_consensus.html) when no seeds are given.
+#[cfg(feature = "std")]
+pub const DEFAULT_CONSENSUS_SEEDS: [u64; 3] = [42, 137, 0xDEAD_BEEF];
 
 /// Calculate the graph invariant using 1-dimensional WL. Automatically stabilises. On graph classes like regular graphs, it is better to use [`invariant_2wl`](fn.invariant_2wl.html), which is more expressive but slower.
-pub fn invariant<N: Ord, E, Ty: EdgeType>(graph: Graph<N, E, Ty>) -> u64 {
-    let mut wrap: GraphWrapper<N, E, Ty, OneWL> = GraphWrapper::new(graph, 42, 0, true, false);
+///
+/// Parallel edges all count: a node with two edges to the same neighbour has degree 2 and sees that neighbour twice. A self-loop makes a node its own neighbour once. To count these differently, or to reject them, use [`WlConfig`](struct.WlConfig.html) with a [`MultigraphPolicy`](enum.MultigraphPolicy.html) or [`SelfLoopPolicy`](enum.SelfLoopPolicy.html).
+#[cfg(feature = "std")]
+pub fn invariant<N: Ord, E, Ty: EdgeType, Ix: IndexType>(graph: Graph<N, E, Ty, Ix>) -> u64 {
+    let mut wrap: GraphWrapper<N, E, Ty, OneWL, Ix> = GraphWrapper::new(graph, 42, 0, true, false);
     wrap.run();
     wrap.get_results()
 }
 
 /// Calculate the graph invariant using 2-dimensional WL. Automatically stabilises. This is an implementation of '2-FWL'. This is more expressive than 1-dimensional WL, but much slower. Therefore only use this on graph classes where our default [`invariant`](fn.invariant.html) does not work well.
-pub fn invariant_2wl<N: Ord, E>(graph: Graph<N, E, Undirected>) -> u64 {
-    let mut wrap: GraphWrapper<N, E, Undirected, TwoWL> =
+#[cfg(feature = "std")]
+pub fn invariant_2wl<N: Ord, E, Ix: IndexType>(graph: Graph<N, E, Undirected, Ix>) -> u64 {
+    let mut wrap: GraphWrapper<N, E, Undirected, TwoWL, Ix> =
         GraphWrapper::new_2wl(graph, 42, 0, true, false);
     wrap.run();
     wrap.get_results()
 }
 
 /// Calculate the graph invariant using 1-dimensional WL. Runs for `n_iters`. Regular graphs tend to need at most 3 iterations for stabilisation, but for example random trees significantly more. We recommend using [`invariant`](fn.invariant.html) for optimal results, if you don't require a specific number of iterations.
-pub fn invariant_iters<N: Ord, E, Ty: EdgeType>(graph: Graph<N, E, Ty>, n_iters: usize) -> u64 {
+#[cfg(feature = "std")]
+pub fn invariant_iters<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    n_iters: usize,
+) -> u64 {
     let mut wrap = GraphWrapper::new(graph, 42, n_iters, false, false);
     wrap.run();
     wrap.get_results()
 }
 
+/// Calculate the graph invariant using 1-dimensional WL with the given [`HashWidth`](enum.HashWidth.html), as a `u128` that can be cast to the width. The 32 and 64-bit hashes are [`invariant`](fn.invariant.html) truncated and as is, and the 128-bit hash adds a second run with seed 43 in the upper 64 bits. Automatically stabilises.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use wl_isomorphism::HashWidth;
+///
+/// let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+/// let short = wl_isomorphism::invariant_width(g.clone(), HashWidth::W32) as u32;
+/// assert_eq!(short, wl_isomorphism::invariant(g) as u32);
+/// ```
+#[cfg(feature = "std")]
+pub fn invariant_width<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    width: HashWidth,
+) -> u128 {
+    let upper = match width {
+        HashWidth::W128 => {
+            let mut wrap = GraphWrapper::new(structure(&graph), 43, 0, true, false);
+            wrap.run();
+            Some(wrap.get_results())
+        }
+        _ => None,
+    };
+    let hash = invariant(graph);
+    match width {
+        HashWidth::W32 => hash as u32 as u128,
+        HashWidth::W64 => hash as u128,
+        HashWidth::W128 => (upper.unwrap() as u128) << 64 | hash as u128,
+    }
+}
+
+/// Like [`invariant`](fn.invariant.html), but hashing the number of nodes of every colour instead of all labels sorted, which is faster on large graphs with few colour classes. The hashes differ from those of [`invariant`](fn.invariant.html), but two graphs get equal hashes from the one exactly when they do from the other, so only compare them with other results of this function. Automatically stabilises.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+/// let h = UnGraph::<(), ()>::from_edges([(3, 1), (1, 2), (2, 3), (3, 0)]);
+/// assert_eq!(wl_isomorphism::invariant_counted(g), wl_isomorphism::invariant_counted(h));
+/// ```
+#[cfg(feature = "std")]
+pub fn invariant_counted<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+) -> u64 {
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false);
+    wrap.run();
+    wrap.counted_digest()
+}
+
+/// Like [`invariant`](fn.invariant.html), but additionally counts the work that was done, e.g. to compare performance strategies independently of wall time, and measures how long every phase of the run took, for capacity planning.
+#[cfg(feature = "std")]
+pub fn invariant_with_stats<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+) -> (u64, WlStats) {
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false).with_stats();
+    wrap.run();
+    let hash = wrap.get_results();
+    let stats = wrap.stats().cloned().unwrap_or_default();
+    (hash, stats)
+}
+
+/// Calculate a graph invariant using 1-dimensional WL, where every node sums the labels of its neighbours instead of sorting them, as in the WL optimal assignment kernel. Skipping the sort makes every iteration cheaper on dense graphs. The sum is taken over scrambled labels, so that different neighbourhoods only collide by chance, like the hashes themselves. The hashes are not comparable with those of [`invariant`](fn.invariant.html), so only compare them with other results of this function. Automatically stabilises.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+/// let h = UnGraph::<(), ()>::from_edges([(3, 1), (1, 2), (2, 3), (3, 0)]);
+/// assert_eq!(wl_isomorphism::invariant_sum_agg(g), wl_isomorphism::invariant_sum_agg(h));
+/// ```
+#[cfg(feature = "std")]
+pub fn invariant_sum_agg<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+) -> u64 {
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false).with_sum_aggregation();
+    wrap.run();
+    wrap.get_results()
+}
+
+/// Run 1-dimensional WL until the colouring has exactly `k` colours, and return the graph hash at that iteration. The initial colouring (the degrees) counts as well. Returns `None` if the colouring becomes stable with a different number of colours, which happens when WL skips over `k` or never reaches it.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// // The ends, the nodes next to them, and the middle node
+/// let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
+/// assert!(wl_isomorphism::invariant_until_k_colors(path.clone(), 3).is_some());
+/// assert_eq!(wl_isomorphism::invariant_until_k_colors(path, 4), None);
+/// ```
+#[cfg(feature = "std")]
+pub fn invariant_until_k_colors<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    k: usize,
+) -> Option<u64> {
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false);
+    let reached = wrap.run_until(|labels| labels.iter().collect::<HashSet<_>>().len() != k);
+    reached.then(|| wrap.get_results())
+}
+
+/// Calculate the graph invariant using 1-dimensional WL, starting from the given labels instead of the node degrees. `initial` holds one label per node, for example node types or a partition computed elsewhere (see [`read_node_colours`](fn.read_node_colours.html)). The result is always refined at least once, so even with the same label for every node, the edges are part of the hash. Automatically stabilises.
+///
+/// Panics if `initial` does not have exactly one label per node.
+#[cfg(feature = "std")]
+pub fn invariant_with_initial<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    initial: &[u64],
+) -> u64 {
+    let mut wrap =
+        GraphWrapper::new(graph, 42, 0, true, false).with_initial_labels(initial.to_vec());
+    wrap.run();
+    wrap.get_results()
+}
+
+/// Like [`invariant_with_initial`](fn.invariant_with_initial.html), but the initial label of every node is computed by `init` from the graph and the node, e.g. from its weight, or from a centrality like PageRank. Automatically stabilises.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// // The same path, with the heavy node at an end or in the middle
+/// let end = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2)]).map(|n, _| (n.index() == 0) as u64, |_, _| ());
+/// let middle = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2)]).map(|n, _| (n.index() == 1) as u64, |_, _| ());
+/// let weight = |g: &UnGraph<u64, ()>, n| g[n];
+/// assert_ne!(
+///     wl_isomorphism::invariant_with_init(end, weight),
+///     wl_isomorphism::invariant_with_init(middle, weight)
+/// );
+/// ```
+#[cfg(feature = "std")]
+pub fn invariant_with_init<N: Ord, E, Ty: EdgeType, Ix: IndexType, F>(
+    graph: Graph<N, E, Ty, Ix>,
+    init: F,
+) -> u64
+where
+    F: Fn(&Graph<N, E, Ty, Ix>, NodeIndex<Ix>) -> u64,
+{
+    let initial = graph
+        .node_indices()
+        .map(|node| init(&graph, node))
+        .collect();
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false).with_initial_labels(initial);
+    wrap.run();
+    wrap.get_results()
+}
+
+/// Calculate the graph invariant of a bipartite graph using 1-dimensional WL, where the nodes start out labelled by their side together with their degree. The nodes in `left_nodes` form one side and all others the other side, so that two graphs that only differ in which side plays which role get different hashes. Automatically stabilises.
+///
+/// Panics if a node in `left_nodes` is not in the graph.
+#[cfg(feature = "std")]
+pub fn invariant_bipartite<N: Ord, E, Ix: IndexType>(
+    graph: Graph<N, E, Undirected, Ix>,
+    left_nodes: &[NodeIndex<Ix>],
+) -> u64 {
+    let seed = 42;
+    let mut is_left = vec![false; graph.node_count()];
+    for node in left_nodes {
+        assert!(
+            node.index() < graph.node_count(),
+            "The left nodes must be nodes of the graph"
+        );
+        is_left[node.index()] = true;
+    }
+    let initial = graph
+        .node_indices()
+        .map(|node| {
+            let side = is_left[node.index()] as u64;
+            let degree = graph.neighbors(node).count() as u64;
+            XxHash64::oneshot(seed, bytemuck::cast_slice(&[side, degree]))
+        })
+        .collect();
+    let mut wrap = GraphWrapper::new(graph, seed, 0, true, false).with_initial_labels(initial);
+    wrap.run();
+    wrap.get_results()
+}
+
 /// Calculate the graph invariant using 2-dimensional WL. Runs for `n_iters`. We recommend using [`invariant_2wl`](fn.invariant_2wl.html) for optimal results if you don't require a specific number of iterations.
-pub fn iter_2wl<N: Ord, E, Ty: EdgeType>(graph: Graph<N, E, Ty>, n_iters: usize) -> u64 {
+#[cfg(feature = "std")]
+pub fn iter_2wl<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    n_iters: usize,
+) -> u64 {
     let mut wrap = GraphWrapper::new_2wl(graph, 42, n_iters, false, false);
     wrap.run();
     wrap.get_results()
 }
 
+/// Calculate the graph invariant using 1-dimensional WL under several hash seeds, and combine the results into one hash. This makes the outcome less dependent on a single seed. The per-seed invariants are sorted before combining, so the order of `seeds` does not matter. An empty `seeds` falls back to [`DEFAULT_CONSENSUS_SEEDS`](constant.DEFAULT_CONSENSUS_SEEDS.html).
+#[cfg(feature = "std")]
+pub fn invariant_consensus<N: Ord + Clone, E: Clone, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    seeds: &[u64],
+) -> u64 {
+    let seeds = if seeds.is_empty() {
+        &DEFAULT_CONSENSUS_SEEDS[..]
+    } else {
+        seeds
+    };
+    let mut sorted_hashes: Vec<u64> = seeds
+        .iter()
+        .map(|&seed| {
+            let mut wrap = GraphWrapper::new(graph.clone(), seed, 0, true, false);
+            wrap.run();
+            wrap.get_results()
+        })
+        .collect();
+    sorted_hashes.sort_unstable();
+    XxHash64::oneshot(42, bytemuck::cast_slice(&sorted_hashes))
+}
+
+/// Calculate the graph invariant of the graph minor obtained by contracting the edge between `u` and `v`, see [`contract_edge`](fn.contract_edge.html), which also describes what happens if there is no such edge.
+#[cfg(feature = "std")]
+pub fn invariant_contracted<N: Ord, E, Ix: IndexType>(
+    graph: Graph<N, E, Undirected, Ix>,
+    u: NodeIndex<Ix>,
+    v: NodeIndex<Ix>,
+) -> u64 {
+    invariant(contract_edge(graph, u, v))
+}
+
+/// Check whether two undirected graphs are possibly isomorphic by comparing the multisets of [`invariant`](fn.invariant.html)s of their connected components. Graphs are isomorphic iff their components are, so this is sound, and it is more powerful than comparing the invariants of the whole graphs: for example, WL does not distinguish two triangles from a hexagon, but their components differ.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let two_triangles = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+/// let hexagon = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+/// assert!(!wl_isomorphism::components_possibly_isomorphic(two_triangles, hexagon));
+/// ```
+#[cfg(feature = "std")]
+pub fn components_possibly_isomorphic<N: Ord + Clone, E: Clone, Ix: IndexType>(
+    g1: Graph<N, E, Undirected, Ix>,
+    g2: Graph<N, E, Undirected, Ix>,
+) -> bool {
+    if g1.node_count() != g2.node_count() || g1.edge_count() != g2.edge_count() {
+        return false;
+    }
+    let component_hashes = |graph: &Graph<N, E, Undirected, Ix>| {
+        let mut hashes: Vec<u64> = connected_component_graphs(graph)
+            .into_iter()
+            .map(invariant)
+            .collect();
+        hashes.sort_unstable();
+        hashes
+    };
+    component_hashes(&g1) == component_hashes(&g2)
+}
+
+/// Check that the stable partition of the nodes does not depend on the hash seed. Labels are hashes, so in principle a collision could merge two colour classes under one seed but not another. This runs the refinement under every seed in `seeds` and compares the induced partitions (not the label values). If two seeds disagree, the offending pair is returned as the error.
+#[cfg(feature = "std")]
+pub fn partition_agreement<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    seeds: &[u64],
+) -> Result<(), (u64, u64)> {
+    let mut reference: Option<(u64, Vec<usize>)> = None;
+    for &seed in seeds {
+        let mut wrap = GraphWrapper::new(structure(&graph), seed, 0, true, false);
+        wrap.run();
+        let classes = wrap.class_ids();
+        match &reference {
+            Some((first_seed, first_classes)) => {
+                if *first_classes != classes {
+                    return Err((*first_seed, seed));
+                }
+            }
+            None => reference = Some((seed, classes)),
+        }
+    }
+    Ok(())
+}
+
+/// Count the colour-consistent embeddings of `motif` in `host`: maps that send every node of the motif to a distinct host node with the same stable colour, and every edge of the motif to an edge between the matched host nodes. The colours are computed on the disjoint union of both graphs, so that they are comparable. Every colour-consistent embedding is an embedding, so the count is a lower bound on the number of embeddings of the motif in the host. It is exact when the colours cannot rule out any embedding, as when counting triangles in disjoint triangles, and zero when a motif node's colour does not occur in the host, e.g. because its degree differs (a triangle in K4, which has 24 embeddings).
+///
+/// The embeddings are enumerated one by one, with the colours pruning the candidates for every motif node, so this takes time exponential in the size of the motif in the worst case and is meant for small motifs.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// // Both triangles can be mapped onto each other in 3! ways, and no triangle fits in a hexagon
+/// let two_triangles = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+/// let hexagon = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+/// let triangle = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0)]);
+/// assert_eq!(wl_isomorphism::wl_motif_lower_bound(two_triangles, triangle.clone()), 12);
+/// assert_eq!(wl_isomorphism::wl_motif_lower_bound(hexagon, triangle), 0);
+/// ```
+#[cfg(feature = "std")]
+pub fn wl_motif_lower_bound<N: Ord, E, Ix: IndexType>(
+    host: Graph<N, E, Undirected, Ix>,
+    motif: Graph<N, E, Undirected, Ix>,
+) -> usize {
+    let offset = host.node_count();
+    let union = disjoint_union(&host, &motif);
+    let mut wrap = GraphWrapper::new(union, 42, 0, true, false);
+    wrap.run();
+    let labels = wrap.labels();
+
+    let mut by_colour: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (node, &label) in labels[..offset].iter().enumerate() {
+        by_colour.entry(label).or_default().push(node);
+    }
+    let mut candidates = Vec::with_capacity(motif.node_count());
+    for &label in &labels[offset..] {
+        match by_colour.get(&label) {
+            Some(nodes) => candidates.push(nodes.as_slice()),
+            None => return 0,
+        }
+    }
+    let mut search = MotifSearch {
+        host: &host,
+        motif: &motif,
+        candidates,
+        placed: vec![usize::MAX; motif.node_count()],
+        used: vec![false; offset],
+    };
+    search.count(0)
+}
+
+// The state of the search for embeddings in wl_motif_lower_bound: the host nodes that every motif node may go to, where the
+// motif nodes before the current one went, and which host nodes they occupy
+#[cfg(feature = "std")]
+struct MotifSearch<'a, N, E, Ix: IndexType> {
+    host: &'a Graph<N, E, Undirected, Ix>,
+    motif: &'a Graph<N, E, Undirected, Ix>,
+    candidates: Vec<&'a [usize]>,
+    placed: Vec<usize>,
+    used: Vec<bool>,
+}
+
+#[cfg(feature = "std")]
+impl<N, E, Ix: IndexType> MotifSearch<'_, N, E, Ix> {
+    // The number of embeddings that extend the placement of the motif nodes before `next`
+    fn count(&mut self, next: usize) -> usize {
+        if next == self.placed.len() {
+            return 1;
+        }
+        let mut total = 0usize;
+        for &target in self.candidates[next] {
+            if self.used[target] || !self.fits(next, target) {
+                continue;
+            }
+            self.placed[next] = target;
+            self.used[target] = true;
+            total = total.saturating_add(self.count(next + 1));
+            self.used[target] = false;
+        }
+        self.placed[next] = usize::MAX;
+        total
+    }
+
+    // Whether every edge from `node` to an already placed motif node, or to itself, has a counterpart at `target`
+    fn fits(&self, node: usize, target: usize) -> bool {
+        self.motif.neighbors(NodeIndex::new(node)).all(|neighbour| {
+            let placed = if neighbour.index() == node {
+                target
+            } else {
+                self.placed[neighbour.index()]
+            };
+            placed == usize::MAX
+                || self
+                    .host
+                    .contains_edge(NodeIndex::new(target), NodeIndex::new(placed))
+        })
+    }
+}
+
+/// Count the edges whose endpoints end up in different stable colour classes, a measure of how much structure WL finds in the graph. When WL cannot refine the graph at all, as in a regular graph, there are no such edges; when every node gets a colour of its own, every edge is counted. Self-loops are never counted, and parallel edges are counted separately.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// // The two ends of a path of four nodes differ from the middle, which is one class
+/// let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+/// assert_eq!(wl_isomorphism::wl_boundary_edges(path), 2);
+/// ```
+#[cfg(feature = "std")]
+pub fn wl_boundary_edges<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+) -> usize {
+    let endpoints: Vec<(usize, usize)> = graph
+        .raw_edges()
+        .iter()
+        .map(|edge| (edge.source().index(), edge.target().index()))
+        .collect();
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false);
+    wrap.run();
+    let labels = wrap.labels();
+    endpoints
+        .into_iter()
+        .filter(|&(source, target)| labels[source] != labels[target])
+        .count()
+}
+
+/// Count, for every pair of stable colours, how many edges join a node of the one to a node of the other: a flat descriptor of the quotient graph that WL finds, e.g. as a feature for machine learning. For undirected graphs the pair is ordered with the smaller colour first; for directed graphs the colour of the source comes first. The colours are the labels of the stable colouring, so they are comparable between graphs, and the counts of isomorphic graphs are identical. Parallel edges are counted separately, so the counts add up to the number of edges.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// // All edges of a star join the centre to a leaf
+/// let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+/// let counts = wl_isomorphism::colour_adjacency_counts(star);
+/// assert_eq!(counts.len(), 1);
+/// assert_eq!(counts.values().next(), Some(&3));
+/// ```
+#[cfg(feature = "std")]
+pub fn colour_adjacency_counts<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+) -> HashMap<(u64, u64), usize> {
+    let directed = graph.is_directed();
+    let endpoints: Vec<(usize, usize)> = graph
+        .raw_edges()
+        .iter()
+        .map(|edge| (edge.source().index(), edge.target().index()))
+        .collect();
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false);
+    wrap.run();
+    let labels = wrap.labels();
+    let mut counts = HashMap::new();
+    for (source, target) in endpoints {
+        let (mut first, mut second) = (labels[source], labels[target]);
+        if !directed && second < first {
+            std::mem::swap(&mut first, &mut second);
+        }
+        *counts.entry((first, second)).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Check whether two graphs are possibly isomorphic by refining them side by side. This gives the same answer as comparing their [`invariant`](fn.invariant.html)s, but stops as soon as the multisets of labels differ, so for non-isomorphic graphs it usually returns long before either graph has stabilised.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+/// let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+/// // The degree sequences already differ, so no refinement is needed
+/// assert!(!wl_isomorphism::possibly_isomorphic_early_exit(path, star));
+/// ```
+#[cfg(feature = "std")]
+pub fn possibly_isomorphic_early_exit<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    g1: Graph<N, E, Ty, Ix>,
+    g2: Graph<N, E, Ty, Ix>,
+) -> bool {
+    let mut first = GraphWrapper::new(g1, 42, 0, true, false);
+    let mut second = GraphWrapper::new(g2, 42, 0, true, false);
+    lockstep_compare(&mut first, &mut second).0
+}
+
+/// Count how many labellings of two graphs agree, starting from the initial labels, by refining them side by side as in [`possibly_isomorphic_early_exit`](fn.possibly_isomorphic_early_exit.html). For `k` up to the result, [`invariant_iters`](fn.invariant_iters.html) with `k` labellings gives the two graphs equal hashes, and with one more labelling different ones. Graphs that WL cannot tell apart agree on every labelling up to stability, and the result counts all of them including the one that shows that the colouring is stable. Graphs of different sizes agree on nothing, so that gives 0.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// // The same degrees, but the degree-3 node sits next to a leaf in one and not in the other
+/// let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (1, 5)]);
+/// let h = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (2, 5)]);
+/// assert_eq!(wl_isomorphism::prefix_match_iters(g, h), 1);
+/// ```
+#[cfg(feature = "std")]
+pub fn prefix_match_iters<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    g1: Graph<N, E, Ty, Ix>,
+    g2: Graph<N, E, Ty, Ix>,
+) -> usize {
+    let mut first = GraphWrapper::new(g1, 42, 0, true, false);
+    let mut second = GraphWrapper::new(g2, 42, 0, true, false);
+    match lockstep_compare(&mut first, &mut second) {
+        (true, compared) => compared,
+        // The last labelling compared is the first that differs
+        (false, compared) => compared.saturating_sub(1),
+    }
+}
+
 /// Generate the subgraph hashes per node per iteration. Can, for example, be used for feature extraction for graph kernels. The computed hash values give some information on the i-hop neighbourhood. The first hash, for example, gives some information on the neighbourhood of each node reachable within one hop.
 ///
 /// In this example, we see each has one neighbour:
@@ -108,8 +808,9 @@ pub fn iter_2wl<N: Ord, E, Ty: EdgeType>(graph: Graph<N, E, Ty>, n_iters: usize)
 /// ```
 /// In this example, the neighbourhoods of nodes 1 from g1 and 5 from g2 appear isomorphic up to their 3-hop neighbourhoods, but once the fourth hop is considered you can see they are not.
 /// (NB: petgraph introduces an unconnected 0th node in this case, because it uses all node labels from 0 to the highest one indicated. Hence the indexing corresponds to the node's number.)
-pub fn neighbourhood_hash<E, Ty: EdgeType>(
-    graph: Graph<u64, E, Ty>,
+#[cfg(feature = "std")]
+pub fn neighbourhood_hash<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
     n_iters: usize,
 ) -> Vec<Vec<u64>> {
     let mut wrap = GraphWrapper::new(graph, 42, n_iters, false, true);
@@ -117,52 +818,242 @@ pub fn neighbourhood_hash<E, Ty: EdgeType>(
     wrap.subgraphs.unwrap()
 }
 
+/// Generate the subgraph hashes of [`neighbourhood_hash`](fn.neighbourhood_hash.html) for every graph of a dataset: `result[graph][node][iteration]` is the hash of `node` of the `graph`th graph after `iteration` iterations. With the `rayon` feature enabled, the graphs are processed in parallel.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let graphs = vec![
+///     UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2)]),
+///     UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0)]),
+/// ];
+/// let hashes = wl_isomorphism::neighbourhood_hash_batch(graphs.clone(), 2);
+/// assert_eq!(hashes[1], wl_isomorphism::neighbourhood_hash(graphs[1].clone(), 2));
+/// ```
+#[cfg(feature = "std")]
+pub fn neighbourhood_hash_batch<N, E, Ty, Ix>(
+    graphs: Vec<Graph<N, E, Ty, Ix>>,
+    n_iters: usize,
+) -> Vec<Vec<Vec<u64>>>
+where
+    N: Ord + Send,
+    E: Send,
+    Ty: EdgeType + Send,
+    Ix: IndexType + Send,
+{
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        graphs
+            .into_par_iter()
+            .map(|graph| neighbourhood_hash(graph, n_iters))
+            .collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    graphs
+        .into_iter()
+        .map(|graph| neighbourhood_hash(graph, n_iters))
+        .collect()
+}
+
+/// Stream the same hashes as [`neighbourhood_hash`](fn.neighbourhood_hash.html), one iteration at a time: `f` is called with the iteration number (from 0) and the hash of every node at that iteration, indexed by node. Only the labels of the current iteration are kept in memory, so this needs O(n) memory rather than O(n * n_iters).
+///
+/// The hashes of one node at all iterations are only known once the last iteration is done, so rows per node cannot be streamed in less memory than the full result; collect the columns you need in `f` instead.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let g = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+/// let mut distinct_per_iteration = Vec::new();
+/// wl_isomorphism::for_each_node_hashes(g, 3, |_, hashes| {
+///     let mut distinct = hashes.to_vec();
+///     distinct.sort_unstable();
+///     distinct.dedup();
+///     distinct_per_iteration.push(distinct.len());
+/// });
+/// assert_eq!(distinct_per_iteration, [2, 2, 2]);
+/// ```
+#[cfg(feature = "std")]
+pub fn for_each_node_hashes<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    n_iters: usize,
+    mut f: impl FnMut(usize, &[u64]),
+) {
+    let mut wrap = GraphWrapper::new(graph, 42, n_iters, false, false);
+    let mut iteration = 0;
+    wrap.run_streaming(|labels| {
+        f(iteration, labels);
+        iteration += 1;
+    });
+}
+
+/// The same hashes as [`neighbourhood_hash`](fn.neighbourhood_hash.html), but stored per iteration instead of per node: `labels_per_iteration(graph, n_iters)[i][v] == neighbourhood_hash(graph, n_iters)[v][i]`. This is the convenient layout for e.g. histograms of all colours at one iteration, and it is filled directly, without transposing.
+#[cfg(feature = "std")]
+pub fn labels_per_iteration<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    n_iters: usize,
+) -> Vec<Vec<u64>> {
+    let mut wrap = GraphWrapper::new(graph, 42, n_iters, false, false);
+    let mut columns = Vec::new();
+    wrap.run_streaming(|labels| columns.push(labels.to_vec()));
+    columns
+}
+
 /// Like [`neighbourhood_hash`](fn.neighbourhood_hash.html), but instead calculated until stability is achieved. (Note that we do not return the last calulated hashes, as these do not provide any new information: they are stable with respect to the last ones that áre returned.)
-pub fn neighbourhood_stable<N: Ord, E, Ty: EdgeType>(graph: Graph<N, E, Ty>) -> Vec<Vec<u64>> {
+#[cfg(feature = "std")]
+pub fn neighbourhood_stable<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+) -> Vec<Vec<u64>> {
     let mut wrap = GraphWrapper::new(graph, 42, 0, true, true);
     wrap.run();
     wrap.subgraphs.unwrap()
 }
 
-/// Like [`invariant`](fn.invariant.html), but it additionally writes the graph with the final colouring in dot format to `path`.
-pub fn invariant_dot<N: Ord, E: Debug, Ty: EdgeType>(graph: Graph<N, E, Ty>, path: &str) -> u64 {
+/// Compute [`invariant_iters`](fn.invariant_iters.html) and [`neighbourhood_hash`](fn.neighbourhood_hash.html) of a graph in a single run, for pipelines that need both, e.g. to deduplicate a dataset and extract kernel features.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let g = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+/// let (hash, hashes) = wl_isomorphism::invariant_and_neighbourhood_hash(g.clone(), 3);
+/// assert_eq!(hash, wl_isomorphism::invariant_iters(g.clone(), 3));
+/// assert_eq!(hashes, wl_isomorphism::neighbourhood_hash(g, 3));
+/// ```
+#[cfg(feature = "std")]
+pub fn invariant_and_neighbourhood_hash<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    n_iters: usize,
+) -> (u64, Vec<Vec<u64>>) {
+    let mut wrap = GraphWrapper::new(graph, 42, n_iters, false, true);
+    wrap.run();
+    (wrap.digest(), wrap.subgraphs.take().unwrap())
+}
+
+/// Like [`invariant_and_neighbourhood_hash`](fn.invariant_and_neighbourhood_hash.html), but run until stability is achieved: the results are those of [`invariant`](fn.invariant.html) and [`neighbourhood_stable`](fn.neighbourhood_stable.html).
+#[cfg(feature = "std")]
+pub fn invariant_and_neighbourhood_stable<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+) -> (u64, Vec<Vec<u64>>) {
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, true);
+    wrap.run();
+    (wrap.digest(), wrap.subgraphs.take().unwrap())
+}
+
+/// Like [`invariant`](fn.invariant.html), but it additionally writes the graph with the final colouring in dot format to `path`, creating missing parent directories. Nodes are named by their index, and are written in order of it, followed by the edges sorted by their endpoints, so that the same graph gives the same file however its edges were added. For a `GraphMap`, use [`invariant_dot_graphmap`](fn.invariant_dot_graphmap.html).
+#[cfg(feature = "std")]
+pub fn invariant_dot<N: Ord, E: Debug, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    path: impl AsRef<Path>,
+) -> std::io::Result<u64> {
+    invariant_dot_with_options(graph, path, DotOptions::default())
+}
+
+/// Like [`invariant_dot`](fn.invariant_dot.html), for a `GraphMap`. Nodes are named by their position in the order they were inserted, and the weights are left out, so the file has the same fill colours as that of the equivalent `Graph`.
+/// ```rust
+/// use petgraph::graphmap::UnGraphMap;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("triangle.dot");
+/// let triangle = UnGraphMap::<&str, ()>::from_edges([("a", "b"), ("b", "c"), ("c", "a")]);
+/// let hash = wl_isomorphism::invariant_dot_graphmap(triangle, &path).unwrap();
+/// let same = petgraph::graph::UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0)]);
+/// assert_eq!(hash, wl_isomorphism::invariant(same));
+/// ```
+#[cfg(feature = "std")]
+pub fn invariant_dot_graphmap<N: NodeTrait, E, Ty: EdgeType>(
+    graph: GraphMap<N, E, Ty>,
+    path: impl AsRef<Path>,
+) -> std::io::Result<u64> {
+    invariant_dot(structure(&graph.into_graph::<DefaultIx>()), path)
+}
+
+/// Like [`invariant_dot`](fn.invariant_dot.html), with [`DotOptions`](struct.DotOptions.html) to choose what is coloured.
+/// ```rust
+/// use petgraph::graph::DiGraph;
+/// use wl_isomorphism::DotOptions;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("star.dot");
+/// let star = DiGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+/// let options = DotOptions { colour_edges: true, ..Default::default() };
+/// wl_isomorphism::invariant_dot_with_options(star, &path, options).unwrap();
+/// assert!(std::fs::read_to_string(path).unwrap().contains("color = \"#"));
+/// ```
+#[cfg(feature = "std")]
+pub fn invariant_dot_with_options<N: Ord, E: Debug, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    path: impl AsRef<Path>,
+    options: DotOptions,
+) -> std::io::Result<u64> {
     let mut wrap = GraphWrapper::new(graph, 42, 0, true, false);
     wrap.run();
-    wrap.write_dot(path);
-    wrap.get_results()
+    wrap.write_dot(path, options)?;
+    Ok(wrap.get_results())
+}
+
+/// Like [`invariant_dot_with_options`](fn.invariant_dot_with_options.html), but every node is labelled with its weight, e.g. its atom symbol, while its fill colour still shows its class. With [`cluster_by_class`](struct.DotOptions.html#structfield.cluster_by_class), the classes are also drawn grouped.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use wl_isomorphism::DotOptions;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("ethanol.dot");
+/// let ethanol = UnGraph::<&str, ()>::from_edges([(0, 1), (1, 2)]).map(|n, _| ["C", "C", "O"][n.index()], |_, _| ());
+/// let options = DotOptions { cluster_by_class: true, ..Default::default() };
+/// wl_isomorphism::invariant_dot_weighted(ethanol, &path, options).unwrap();
+/// let text = std::fs::read_to_string(path).unwrap();
+/// assert!(text.contains("label = \"O\"") && text.contains("subgraph cluster_0"));
+/// ```
+#[cfg(feature = "std")]
+pub fn invariant_dot_weighted<N: Ord + std::fmt::Display, E: Debug, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    path: impl AsRef<Path>,
+    options: DotOptions,
+) -> std::io::Result<u64> {
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false);
+    wrap.run();
+    wrap.write_dot_weighted(path, options)?;
+    Ok(wrap.get_results())
 }
 
-/// Like [`invariant_iters`](fn.invariant_iters.html), but it additionally writes the graph with the final colouring in dot format to `path`.
-pub fn iter_dot<E: Debug, Ty: EdgeType>(
-    graph: Graph<u64, E, Ty>,
+/// Like [`invariant_iters`](fn.invariant_iters.html), but it additionally writes the graph with the final colouring in dot format to `path`, creating missing parent directories.
+#[cfg(feature = "std")]
+pub fn iter_dot<N: Ord, E: Debug, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
     n_iters: usize,
-    path: &str,
-) -> u64 {
+    path: impl AsRef<Path>,
+) -> std::io::Result<u64> {
     let mut wrap = GraphWrapper::new(graph, 42, n_iters, false, false);
     wrap.run();
-    wrap.write_dot(path);
-    wrap.get_results()
+    wrap.write_dot(path, DotOptions::default())?;
+    Ok(wrap.get_results())
+}
+
+/// Build an undirected graph from a list of edges between numbered nodes, without naming petgraph types. As with `from_edges`, the nodes are `0` up to the highest number in `edges`, so skipped numbers become unconnected nodes.
+/// ```rust
+/// let path = wl_isomorphism::ungraph_from_edges([(0, 1), (1, 2)]);
+/// assert_eq!((path.node_count(), path.edge_count()), (3, 2));
+/// ```
+#[cfg(feature = "std")]
+pub fn ungraph_from_edges(edges: impl IntoIterator<Item = (u32, u32)>) -> UnGraph<(), ()> {
+    UnGraph::<(), ()>::from_edges(edges)
 }
 
-/// Read an undirected graph from a text file, as produced by [`Networkx.write_edgelist`](https://networkx.org/documentation/stable/reference/readwrite/generated/networkx.readwrite.edgelist.write_edgelist.html). Note that this does not support weights and that if the edgelist skips certain indices, petgraph will infer unconnected nodes at said indices.
-pub fn ungraph_from_edgelist(path: &str) -> UnGraph<(), ()> {
-    UnGraph::<(), ()>::from_edges(read_edges(path))
+/// Build a directed graph from a list of edges between numbered nodes, like [`ungraph_from_edges`](fn.ungraph_from_edges.html).
+#[cfg(feature = "std")]
+pub fn digraph_from_edges(edges: impl IntoIterator<Item = (u32, u32)>) -> DiGraph<(), ()> {
+    DiGraph::<(), ()>::from_edges(edges)
 }
 
-/// Read a directed graph from a text file, as produced by [`Networkx.write_edgelist`](https://networkx.org/documentation/stable/reference/readwrite/generated/networkx.readwrite.edgelist.write_edgelist.html). Note that this does not support weights and that if the edgelist skips certain indices, petgraph will infer an unconnected node at that index.
-pub fn digraph_from_edgelist(path: &str) -> DiGraph<(), ()> {
-    DiGraph::<(), ()>::from_edges(read_edges(path))
+/// Read an undirected graph from a text file, as produced by [`Networkx.write_edgelist`](https://networkx.org/documentation/stable/reference/readwrite/generated/networkx.readwrite.edgelist.write_edgelist.html). Note that this does not support weights (any columns after the first two are ignored) and that if the edgelist skips certain indices, petgraph will infer unconnected nodes at said indices. Empty lines and lines starting with `#` are skipped.
+///
+/// Returns [`WlError::Io`](enum.WlError.html#variant.Io) if the file cannot be read, [`WlError::Parse`](enum.WlError.html#variant.Parse) with the line number if a line does not start with two node ids, and [`WlError::LimitExceeded`](enum.WlError.html#variant.LimitExceeded) if an id is over the node limit of [`graph_from_file`](fn.graph_from_file.html).
+#[cfg(feature = "std")]
+pub fn ungraph_from_edgelist(path: impl AsRef<Path>) -> Result<UnGraph<(), ()>, WlError> {
+    formats::parse_edgelist(&fs::read_to_string(path)?)
 }
 
-// Read edges from a txt file
-fn read_edges(path: &str) -> impl Iterator<Item = (u32, u32)> {
-    let file = File::open(path).expect("Unable to open file");
-    BufReader::new(file).lines().map(|line| {
-        let line = line.expect("Unable to read line");
-        let nodes: Vec<&str> = line.split_whitespace().collect();
-        (
-            nodes[0].parse::<u32>().expect("Couldn't parse"),
-            nodes[1].parse::<u32>().expect("Couldn't parse"),
-        )
-    })
+/// Read a directed graph from a text file, as produced by [`Networkx.write_edgelist`](https://networkx.org/documentation/stable/reference/readwrite/generated/networkx.readwrite.edgelist.write_edgelist.html). Note that this does not support weights (any columns after the first two are ignored) and that if the edgelist skips certain indices, petgraph will infer an unconnected node at that index. Empty lines and lines starting with `#` are skipped.
+///
+/// Returns the same errors as [`ungraph_from_edgelist`](fn.ungraph_from_edgelist.html).
+#[cfg(feature = "std")]
+pub fn digraph_from_edgelist(path: impl AsRef<Path>) -> Result<DiGraph<(), ()>, WlError> {
+    formats::parse_edgelist(&fs::read_to_string(path)?)
 }
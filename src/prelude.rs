@@ -0,0 +1,20 @@
+//! The petgraph types and the functions of this crate needed for most uses, so that `use wl_isomorphism::prelude::*;` is enough to build graphs and hash them without depending on petgraph directly.
+//! ```rust
+//! use wl_isomorphism::prelude::*;
+//!
+//! let triangle = ungraph_from_edges([(0, 1), (1, 2), (2, 0)]);
+//! let path: UnGraph<(), ()> = ungraph_from_edges([(0, 1), (1, 2)]);
+//! assert_ne!(invariant(triangle), invariant(path.clone()));
+//! assert_eq!(Invariant::of(path.clone()).matches(&Invariant::of(path)), MatchOutcome::Match);
+//! let cycle = digraph_from_edges([(0, 1), (1, 2), (2, 0)]);
+//! let first: NodeIndex = NodeIndex::new(0);
+//! assert_eq!(neighbourhood_stable(cycle)[first.index()].len(), 1);
+//! ```
+
+pub use crate::{
+    compare, digraph_from_edgelist, digraph_from_edges, invariant, invariant_2wl, invariant_iters,
+    iter_2wl, neighbourhood_hash, neighbourhood_stable, ungraph_from_edgelist, ungraph_from_edges,
+    Comparison, Invariant, MatchOutcome, WlConfig, WlError,
+};
+pub use petgraph::graph::{DiGraph, Graph, NodeIndex, UnGraph};
+pub use petgraph::{Directed, Undirected};
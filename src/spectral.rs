@@ -0,0 +1,226 @@
+use crate::csr::{Adjacency, GraphCsr};
+use crate::graphwrapper::GraphWrapper;
+use petgraph::graph::IndexType;
+use petgraph::{Graph, Undirected};
+use twox_hash::XxHash64;
+
+// Eigenvalues and projections closer than this are taken to be equal, as they only differ by rounding errors
+const TOLERANCE: f64 = 1e-8;
+
+/// Calculate a graph invariant using 1-dimensional WL, starting from labels derived from the Fiedler vectors: the eigenvectors of the second smallest distinct eigenvalue of the normalised Laplacian, which describe how the graph is best cut in two. When that eigenvalue is repeated, as on many symmetric graphs, no single Fiedler vector is determined by the graph, and neither is the sign of any of them, so every node is labelled by the squared length of its projection onto the whole eigenspace instead, which does not depend on those choices. The projections are grouped with a tolerance, so that rounding errors do not split nodes that are alike, and every label also holds the multiplicity of the eigenvalue and the number of eigenvalues below it. That tells apart e.g. the two 3-regular graphs on 6 nodes that plain WL cannot. Automatically stabilises.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// // The complete bipartite graph K3,3 and the triangular prism
+/// let k33 = UnGraph::<(), ()>::from_edges([(0, 3), (0, 4), (0, 5), (1, 3), (1, 4), (1, 5), (2, 3), (2, 4), (2, 5)]);
+/// let prism = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (0, 3), (1, 4), (2, 5)]);
+/// assert_eq!(wl_isomorphism::invariant(k33.clone()), wl_isomorphism::invariant(prism.clone()));
+/// assert_ne!(
+///     wl_isomorphism::invariant_spectral_init(k33),
+///     wl_isomorphism::invariant_spectral_init(prism)
+/// );
+/// ```
+///
+/// The eigenvectors are computed with Jacobi rotations on the dense Laplacian until it is diagonal up to rounding, which takes O(n^3) time and O(n^2) memory, so this suits small and medium-sized graphs. Nodes are only told apart by values that differ by more than the tolerance of 1e-8, so isomorphic graphs get the same hash unless two different eigenvalues or projections lie within about that distance of each other.
+pub fn invariant_spectral_init<N: Ord, E, Ix: IndexType>(
+    graph: Graph<N, E, Undirected, Ix>,
+) -> u64 {
+    let seed = 42;
+    let (below, multiplicity, projections) = {
+        let csr = GraphCsr::<Ix>::new(&graph);
+        fiedler_projections(&csr.view())
+    };
+    let groups = group_ranks(&projections);
+    let initial = groups
+        .into_iter()
+        .map(|group| {
+            XxHash64::oneshot(
+                seed,
+                bytemuck::cast_slice(&[below as u64, multiplicity as u64, group as u64]),
+            )
+        })
+        .collect();
+    let mut wrap = GraphWrapper::new(graph, seed, 0, true, false).with_initial_labels(initial);
+    wrap.run();
+    wrap.get_results()
+}
+
+/// Calculate a graph invariant using 1-dimensional WL, starting from a random walk positional encoding (RWPE): the probability that a random walk of `walk_length` steps from a node ends where it started. That is the diagonal of the `walk_length`-th power of the random walk matrix D^-1 A, computed by walking from every node in turn. The probabilities are divided into `n_bins` equal bins on [0, 1], and a node's bin is its initial label. These probabilities count the short cycles through a node, which plain WL never sees: in two triangles a walk of three steps can return, in a hexagon it cannot. Automatically stabilises.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let hexagon = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+/// let triangles = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+/// assert_eq!(wl_isomorphism::invariant(hexagon.clone()), wl_isomorphism::invariant(triangles.clone()));
+/// assert_ne!(
+///     wl_isomorphism::invariant_rwpe(hexagon, 3, 16, 42),
+///     wl_isomorphism::invariant_rwpe(triangles, 3, 16, 42)
+/// );
+/// ```
+///
+/// The probabilities are computed exactly rather than sampled, so `rng_seed` only seeds the hashes of the labels, and results with different seeds cannot be compared. Walking from every node takes O(`walk_length` * n * (n + m)) time. An isolated node stays where it is only for walks of length 0.
+///
+/// Panics if `n_bins` is 0.
+pub fn invariant_rwpe<N: Ord, E, Ix: IndexType>(
+    graph: Graph<N, E, Undirected, Ix>,
+    walk_length: usize,
+    n_bins: usize,
+    rng_seed: u64,
+) -> u64 {
+    assert!(n_bins > 0, "There must be at least one bin");
+    let return_probabilities = {
+        let csr = GraphCsr::<Ix>::new(&graph);
+        return_probabilities(&csr.view(), walk_length)
+    };
+    let initial = return_probabilities
+        .iter()
+        .map(|&p| {
+            // Probabilities on a bin boundary may come out just below it, depending on the order of the sums, so they are nudged up
+            let bin = ((p * n_bins as f64 + 1e-9) as u64).min(n_bins as u64 - 1);
+            XxHash64::oneshot(rng_seed, &bin.to_le_bytes())
+        })
+        .collect();
+    let mut wrap = GraphWrapper::new(graph, rng_seed, 0, true, false).with_initial_labels(initial);
+    wrap.run();
+    wrap.get_results()
+}
+
+// For every node the probability that a random walk of `steps` steps from it returns to it, by moving the distribution of a walk
+// that starts there one step at a time
+fn return_probabilities<Ix: IndexType>(adjacency: &Adjacency<'_, Ix>, steps: usize) -> Vec<f64> {
+    let csr = match adjacency {
+        Adjacency::Undirected(csr) => *csr,
+        Adjacency::Directed { outgoing, .. } => *outgoing,
+    };
+    let n = csr.node_count();
+    let mut x = vec![0.0; n];
+    let mut y = vec![0.0; n];
+    (0..n)
+        .map(|start| {
+            x.iter_mut().for_each(|x| *x = 0.0);
+            x[start] = 1.0;
+            for _ in 0..steps {
+                y.iter_mut().for_each(|y| *y = 0.0);
+                for (node, &p) in x.iter().enumerate() {
+                    let neighbours = csr.neighbours(node);
+                    if p == 0.0 || neighbours.is_empty() {
+                        continue;
+                    }
+                    let share = p / neighbours.len() as f64;
+                    for neighbour in neighbours {
+                        y[neighbour.index()] += share;
+                    }
+                }
+                std::mem::swap(&mut x, &mut y);
+            }
+            x[start]
+        })
+        .collect()
+}
+
+// The eigenspace of the second smallest distinct eigenvalue of the normalised Laplacian L = I - D^-1/2 A D^-1/2: the number of
+// eigenvalues below it, its multiplicity, and for every node the squared length of its projection onto the eigenspace, i.e. the diagonal
+// of the projection matrix, which unlike the eigenvectors themselves does not depend on the node order. Graphs with a single distinct
+// eigenvalue have no such eigenspace, which gives a multiplicity of 0
+fn fiedler_projections<Ix: IndexType>(adjacency: &Adjacency<'_, Ix>) -> (usize, usize, Vec<f64>) {
+    let csr = match adjacency {
+        Adjacency::Undirected(csr) => *csr,
+        Adjacency::Directed { outgoing, .. } => *outgoing,
+    };
+    let n = csr.node_count();
+    let inverse_sqrt_degree: Vec<f64> = (0..n)
+        .map(|node| match csr.neighbours(node).len() {
+            0 => 0.0,
+            degree => 1.0 / (degree as f64).sqrt(),
+        })
+        .collect();
+    let mut laplacian = vec![0.0; n * n];
+    for node in 0..n {
+        laplacian[node * n + node] = 1.0;
+        for neighbour in csr.neighbours(node) {
+            let neighbour = neighbour.index();
+            laplacian[node * n + neighbour] -=
+                inverse_sqrt_degree[node] * inverse_sqrt_degree[neighbour];
+        }
+    }
+    let (values, vectors) = symmetric_eigen(laplacian, n);
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_unstable_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let below = order
+        .iter()
+        .take_while(|&&k| values[k] - values[order[0]] <= TOLERANCE)
+        .count();
+    let fiedler: Vec<usize> = order[below..]
+        .iter()
+        .copied()
+        .take_while(|&k| values[k] - values[order[below]] <= TOLERANCE)
+        .collect();
+    let projections = (0..n)
+        .map(|node| fiedler.iter().map(|&k| vectors[node * n + k].powi(2)).sum())
+        .collect();
+    (below, fiedler.len(), projections)
+}
+
+// The eigenvalues of a symmetric n x n matrix, stored by rows, and its eigenvectors as the columns of another, by cyclic Jacobi rotations
+// until the entries off the diagonal are negligible
+fn symmetric_eigen(mut a: Vec<f64>, n: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut vectors = vec![0.0; n * n];
+    for k in 0..n {
+        vectors[k * n + k] = 1.0;
+    }
+    let total: f64 = a.iter().map(|x| x * x).sum();
+    // Jacobi converges quadratically, so the limit on the sweeps is never reached in practice
+    for _ in 0..100 {
+        let off: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i * n + j] * a[i * n + j])
+            .sum();
+        if off <= f64::EPSILON * f64::EPSILON * total {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                let apq = a[p * n + q];
+                if apq == 0.0 {
+                    continue;
+                }
+                // The rotation by the smaller angle that zeroes a[p][q]
+                let theta = (a[q * n + q] - a[p * n + p]) / (2.0 * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                let rotate = |m: &mut [f64], x: usize, y: usize| {
+                    let (mx, my) = (m[x], m[y]);
+                    m[x] = c * mx - s * my;
+                    m[y] = s * mx + c * my;
+                };
+                for k in 0..n {
+                    rotate(&mut a, k * n + p, k * n + q);
+                    rotate(&mut vectors, k * n + p, k * n + q);
+                }
+                for k in 0..n {
+                    rotate(&mut a, p * n + k, q * n + k);
+                }
+            }
+        }
+    }
+    let values = (0..n).map(|k| a[k * n + k]).collect();
+    (values, vectors)
+}
+
+// Number groups of values that lie within the tolerance of each other in increasing order, giving every value the rank of its group.
+// Only the gaps between the sorted values count, so the ranks do not depend on the order of the values
+fn group_ranks(values: &[f64]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_unstable_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0; values.len()];
+    let mut rank = 0;
+    for pair in order.windows(2) {
+        if values[pair[1]] - values[pair[0]] > TOLERANCE {
+            rank += 1;
+        }
+        ranks[pair[1]] = rank;
+    }
+    ranks
+}
@@ -0,0 +1,213 @@
+use crate::graphwrapper::GraphWrapper;
+use petgraph::graph::IndexType;
+use petgraph::visit::EdgeRef;
+use petgraph::{EdgeType, Graph};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Basic statistics of a graph together with its WL invariant, see [`summarise`](fn.summarise.html). The `Display` output is a single line, meant for logs.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphSummary {
+    pub nodes: usize,
+    pub edges: usize,
+    /// The fraction of possible edges (between distinct nodes) that is present, or 0 for graphs with fewer than two nodes.
+    pub density: f64,
+    /// The highest number of edge endpoints at a node, counting both directions for directed graphs and self-loops twice.
+    pub max_degree: usize,
+    /// The number of colour classes in the stable WL colouring.
+    pub wl_classes: usize,
+    /// The same hash as [`invariant`](fn.invariant.html).
+    pub invariant: u64,
+}
+
+impl fmt::Display for GraphSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} nodes, {} edges, density {:.3}, max degree {}, {} WL classes, invariant {}",
+            self.nodes, self.edges, self.density, self.max_degree, self.wl_classes, self.invariant
+        )
+    }
+}
+
+/// Summarise a graph for logging: its size, density, maximum degree, the number of WL colour classes and its invariant. WL is only run once, for both the class count and the invariant.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let graph = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+/// let summary = wl_isomorphism::summarise(graph.clone());
+/// assert_eq!((summary.nodes, summary.edges, summary.max_degree), (4, 4, 3));
+/// // The tail, the node it hangs from, and the other two triangle nodes
+/// assert_eq!(summary.wl_classes, 3);
+/// assert_eq!(summary.invariant, wl_isomorphism::invariant(graph));
+/// assert!(summary.to_string().starts_with("4 nodes, 4 edges, density 0.667, max degree 3, 3 WL classes"));
+/// ```
+pub fn summarise<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+) -> GraphSummary {
+    let (nodes, edges) = (graph.node_count(), graph.edge_count());
+    let mut degrees = vec![0; nodes];
+    for edge in graph.edge_references() {
+        degrees[edge.source().index()] += 1;
+        degrees[edge.target().index()] += 1;
+    }
+    let max_degree = degrees.into_iter().max().unwrap_or(0);
+    let pairs = nodes * nodes.saturating_sub(1);
+    let density = match (pairs, graph.is_directed()) {
+        (0, _) => 0.0,
+        (_, true) => edges as f64 / pairs as f64,
+        (_, false) => 2.0 * edges as f64 / pairs as f64,
+    };
+
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false);
+    wrap.run();
+    let invariant = wrap.get_results();
+    // get_results leaves the labels sorted
+    let labels = wrap.labels();
+    let wl_classes = labels.len() - labels.windows(2).filter(|w| w[0] == w[1]).count();
+    GraphSummary {
+        nodes,
+        edges,
+        density,
+        max_degree,
+        wl_classes,
+        invariant,
+    }
+}
+
+/// Something about a graph that is allowed, but often unintended, as found by [`validate`](fn.validate.html). Nodes and edges are given by their index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GraphWarning {
+    /// A node without edges, such as the nodes that `from_edges` adds for skipped numbers.
+    IsolatedNode { node: usize },
+    /// An edge from a node to itself.
+    SelfLoop { node: usize, edge: usize },
+    /// Several edges between the same two nodes, in the same direction for directed graphs. `edges` lists all of them, in order.
+    ParallelEdges {
+        source: usize,
+        target: usize,
+        edges: Vec<usize>,
+    },
+    /// Edges in both directions between two nodes of a directed graph, which may have been meant as one undirected edge. `forward` is the first edge from the lower node `source` to `target`, and `backward` the first edge back.
+    ReciprocalEdges {
+        source: usize,
+        target: usize,
+        forward: usize,
+        backward: usize,
+    },
+}
+
+impl fmt::Display for GraphWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphWarning::IsolatedNode { node } => write!(f, "node {} has no edges", node),
+            GraphWarning::SelfLoop { node, edge } => {
+                write!(f, "edge {} is a self-loop at node {}", edge, node)
+            }
+            GraphWarning::ParallelEdges {
+                source,
+                target,
+                edges,
+            } => write!(
+                f,
+                "{} parallel edges between nodes {} and {}: {:?}",
+                edges.len(),
+                source,
+                target,
+                edges
+            ),
+            GraphWarning::ReciprocalEdges {
+                source,
+                target,
+                forward,
+                backward,
+            } => write!(
+                f,
+                "edges {} and {} connect nodes {} and {} in both directions",
+                forward, backward, source, target
+            ),
+        }
+    }
+}
+
+/// Check a graph for things that petgraph allows without notice, but that often come from mistakes in the input: isolated nodes, self-loops, parallel edges and, for directed graphs, pairs of reciprocal edges. Nothing is rejected, as all of these can be intended. The warnings come grouped in that order: the isolated nodes by index, the self-loops and parallel edges in order of their first edge, and the reciprocal pairs in order of their forward edge. Parallel edges are reported between the lower and the higher node for undirected graphs.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+/// use wl_isomorphism::GraphWarning;
+///
+/// // Numbering from 1 leaves node 0 without edges
+/// let graph = UnGraph::<(), ()>::from_edges([(1, 2), (2, 3)]);
+/// assert_eq!(wl_isomorphism::validate(&graph), vec![GraphWarning::IsolatedNode { node: 0 }]);
+/// ```
+pub fn validate<N, E, Ty: EdgeType, Ix: IndexType>(
+    graph: &Graph<N, E, Ty, Ix>,
+) -> Vec<GraphWarning> {
+    let mut degrees = vec![0; graph.node_count()];
+    let mut self_loops = Vec::new();
+    // The edges between every pair of nodes, in order of the first edge
+    let mut pairs: Vec<(usize, usize, Vec<usize>)> = Vec::new();
+    let mut pair_position: HashMap<(usize, usize), usize> = HashMap::new();
+    for edge in graph.edge_references() {
+        let (source, target) = (edge.source().index(), edge.target().index());
+        degrees[source] += 1;
+        degrees[target] += 1;
+        if source == target {
+            self_loops.push(GraphWarning::SelfLoop {
+                node: source,
+                edge: edge.id().index(),
+            });
+        }
+        let key = if graph.is_directed() {
+            (source, target)
+        } else {
+            (source.min(target), source.max(target))
+        };
+        let position = *pair_position.entry(key).or_insert_with(|| {
+            pairs.push((key.0, key.1, Vec::new()));
+            pairs.len() - 1
+        });
+        pairs[position].2.push(edge.id().index());
+    }
+
+    let mut warnings: Vec<GraphWarning> = degrees
+        .iter()
+        .enumerate()
+        .filter(|(_, &degree)| degree == 0)
+        .map(|(node, _)| GraphWarning::IsolatedNode { node })
+        .collect();
+    warnings.extend(self_loops);
+    warnings.extend(pairs.iter().filter(|(_, _, edges)| edges.len() > 1).map(
+        |(source, target, edges)| GraphWarning::ParallelEdges {
+            source: *source,
+            target: *target,
+            edges: edges.clone(),
+        },
+    ));
+    if graph.is_directed() {
+        for (source, target, edges) in &pairs {
+            if source < target {
+                if let Some(&back) = pair_position.get(&(*target, *source)) {
+                    warnings.push(GraphWarning::ReciprocalEdges {
+                        source: *source,
+                        target: *target,
+                        forward: edges[0],
+                        backward: pairs[back].2[0],
+                    });
+                }
+            }
+        }
+    }
+    warnings
+}
+
+/// Calculate the same hash as [`invariant`](fn.invariant.html), together with the warnings of [`validate`](fn.validate.html) for the graph, e.g. to log them next to the hash.
+pub fn invariant_validated<N: Ord, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+) -> (u64, Vec<GraphWarning>) {
+    let warnings = validate(&graph);
+    let mut wrap = GraphWrapper::new(graph, 42, 0, true, false);
+    wrap.run();
+    (wrap.get_results(), warnings)
+}
@@ -0,0 +1,255 @@
+use petgraph::graph::{IndexType, NodeIndex};
+use petgraph::unionfind::UnionFind;
+use petgraph::{Directed, EdgeType, Graph, Undirected};
+use std::collections::{HashMap, VecDeque};
+
+/// Contract the edge between `u` and `v`: `v` is merged into `u`, which inherits all of `v`'s edges. Self-loops that would arise are removed, but parallel edges are kept, so the result may be a multigraph. Nodes after `v` shift down one index to keep the indices contiguous.
+///
+/// The edge itself is not required: if `u` and `v` are not adjacent, they are merged all the same (identified), which gives a graph that is in general not a minor of the input. Check with `graph.contains_edge(u, v)` first where that matters.
+/// ```rust
+/// use petgraph::graph::{NodeIndex, UnGraph};
+///
+/// // Identifying the ends of the path 0 - 1 - 2 gives two nodes joined by two parallel edges
+/// let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+/// let merged = wl_isomorphism::contract_edge(path, NodeIndex::new(0), NodeIndex::new(2));
+/// assert_eq!((merged.node_count(), merged.edge_count()), (2, 2));
+/// ```
+///
+/// Panics if `u` and `v` are the same node or if either is not in the graph.
+pub fn contract_edge<N, E, Ix: IndexType>(
+    graph: Graph<N, E, Undirected, Ix>,
+    u: NodeIndex<Ix>,
+    v: NodeIndex<Ix>,
+) -> Graph<N, E, Undirected, Ix> {
+    assert!(u != v, "Cannot contract a node with itself");
+    assert!(
+        u.index() < graph.node_count() && v.index() < graph.node_count(),
+        "Both endpoints must be nodes of the graph"
+    );
+    let (nodes, edges) = graph.into_nodes_edges();
+    let mut contracted = Graph::with_capacity(nodes.len() - 1, edges.len());
+
+    // Translate old indices to new ones, skipping the merged node
+    let mut new_index = Vec::with_capacity(nodes.len());
+    for (index, node) in nodes.into_iter().enumerate() {
+        if index == v.index() {
+            new_index.push(NodeIndex::end());
+        } else {
+            new_index.push(contracted.add_node(node.weight));
+        }
+    }
+    new_index[v.index()] = new_index[u.index()];
+
+    for edge in edges {
+        let source = new_index[edge.source().index()];
+        let target = new_index[edge.target().index()];
+        if source != target {
+            contracted.add_edge(source, target, edge.weight);
+        }
+    }
+    contracted
+}
+
+/// Renumber the nodes of a graph: the node with index `i` gets index `perm[i]`, with its weight and its edges. The edges keep their order and weights, so the result is isomorphic to the input, which makes this the way to test that a computation does not depend on the node order.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+/// let permuted = wl_isomorphism::apply_node_permutation(path.clone(), &[1, 2, 0]);
+/// assert!(permuted.contains_edge(1.into(), 2.into()) && permuted.contains_edge(2.into(), 0.into()));
+/// assert_eq!(wl_isomorphism::invariant(path), wl_isomorphism::invariant(permuted));
+/// ```
+///
+/// Panics if `perm` is not a permutation of `0..n`.
+pub fn apply_node_permutation<N, E, Ty: EdgeType, Ix: IndexType>(
+    graph: Graph<N, E, Ty, Ix>,
+    perm: &[usize],
+) -> Graph<N, E, Ty, Ix> {
+    let n = graph.node_count();
+    assert_eq!(
+        perm.len(),
+        n,
+        "The permutation must have an entry for every node"
+    );
+    let (nodes, edges) = graph.into_nodes_edges();
+    let mut placed: Vec<Option<N>> = (0..n).map(|_| None).collect();
+    for (node, &index) in nodes.into_iter().zip(perm) {
+        assert!(
+            index < n && placed[index].is_none(),
+            "The permutation must map the nodes to 0..n one to one"
+        );
+        placed[index] = Some(node.weight);
+    }
+    let mut permuted = Graph::with_capacity(n, edges.len());
+    for weight in placed {
+        permuted.add_node(weight.unwrap());
+    }
+    for edge in edges {
+        permuted.add_edge(
+            NodeIndex::new(perm[edge.source().index()]),
+            NodeIndex::new(perm[edge.target().index()]),
+            edge.weight,
+        );
+    }
+    permuted
+}
+
+/// What [`to_undirected_skeleton`](fn.to_undirected_skeleton.html) does with a pair of reciprocal edges, an edge from `a` to `b` and one from `b` to `a`.
+#[derive(Debug, Clone, Copy)]
+pub enum ReciprocalPolicy<E> {
+    /// Both become an undirected edge, so the skeleton has two parallel edges between `a` and `b`.
+    KeepBoth,
+    /// They become one undirected edge, with the weight of the edge that comes first in the digraph.
+    KeepFirst,
+    /// They become one undirected edge, with the weights combined by the function, the first edge's weight first.
+    Merge(fn(E, E) -> E),
+}
+
+/// The undirected skeleton of a directed graph: the same nodes, with every edge made undirected. Reciprocal pairs of edges are merged or kept according to `policy`. When there are several edges in each direction, they are paired up in the order of the digraph, and the edges left without a partner stay as they are, as do parallel edges in the same direction and self-loops. The nodes keep their indices, and every edge takes the place of the first edge it came from.
+/// ```rust
+/// use petgraph::graph::{DiGraph, UnGraph};
+/// use wl_isomorphism::ReciprocalPolicy;
+///
+/// // A road network with a one-way street and a two-way street, whose lengths are the weights
+/// let roads = DiGraph::<(), u32>::from_edges([(0, 1, 5), (1, 2, 3), (2, 1, 3)]);
+/// let skeleton = wl_isomorphism::to_undirected_skeleton(roads.clone(), ReciprocalPolicy::KeepFirst);
+/// assert_eq!(wl_isomorphism::invariant(skeleton), wl_isomorphism::invariant(UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)])));
+/// let skeleton = wl_isomorphism::to_undirected_skeleton(roads, ReciprocalPolicy::Merge(|a, b| a.min(b)));
+/// assert_eq!(skeleton.edge_weights().copied().collect::<Vec<_>>(), [5, 3]);
+/// ```
+pub fn to_undirected_skeleton<N, E, Ix: IndexType>(
+    graph: Graph<N, E, Directed, Ix>,
+    policy: ReciprocalPolicy<E>,
+) -> Graph<N, E, Undirected, Ix> {
+    let (nodes, edges) = graph.into_nodes_edges();
+    // The edges of the skeleton in order, with the edges still waiting for a partner by their direction
+    let mut merged: Vec<(NodeIndex<Ix>, NodeIndex<Ix>, Option<E>)> =
+        Vec::with_capacity(edges.len());
+    let mut unpaired: HashMap<(usize, usize), VecDeque<usize>> = HashMap::new();
+    for edge in edges {
+        let (source, target) = (edge.source(), edge.target());
+        if source != target {
+            let partner = match policy {
+                ReciprocalPolicy::KeepBoth => None,
+                _ => unpaired
+                    .get_mut(&(target.index(), source.index()))
+                    .and_then(VecDeque::pop_front),
+            };
+            if let Some(partner) = partner {
+                if let ReciprocalPolicy::Merge(merge) = policy {
+                    let first = merged[partner].2.take().unwrap();
+                    merged[partner].2 = Some(merge(first, edge.weight));
+                }
+                continue;
+            }
+            unpaired
+                .entry((source.index(), target.index()))
+                .or_default()
+                .push_back(merged.len());
+        }
+        merged.push((source, target, Some(edge.weight)));
+    }
+
+    let mut skeleton = Graph::with_capacity(nodes.len(), merged.len());
+    for node in nodes {
+        skeleton.add_node(node.weight);
+    }
+    for (source, target, weight) in merged {
+        skeleton.add_edge(source, target, weight.unwrap());
+    }
+    skeleton
+}
+
+/// An undirected graph as a directed one, with every edge replaced by a pair of reciprocal edges with the same weight, so that it does not matter in which direction the undirected edges were written down. A self-loop becomes a single directed loop, as it already points both ways. The nodes keep their indices, and the pairs follow the order of the undirected edges.
+/// ```rust
+/// use petgraph::graph::UnGraph;
+///
+/// let forwards = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+/// let backwards = UnGraph::<(), ()>::from_edges([(1, 0), (2, 1)]);
+/// assert_eq!(
+///     wl_isomorphism::invariant(wl_isomorphism::to_symmetric_digraph(forwards)),
+///     wl_isomorphism::invariant(wl_isomorphism::to_symmetric_digraph(backwards))
+/// );
+/// ```
+pub fn to_symmetric_digraph<N, E: Clone, Ix: IndexType>(
+    graph: Graph<N, E, Undirected, Ix>,
+) -> Graph<N, E, Directed, Ix> {
+    let (nodes, edges) = graph.into_nodes_edges();
+    let mut digraph = Graph::with_capacity(nodes.len(), 2 * edges.len());
+    for node in nodes {
+        digraph.add_node(node.weight);
+    }
+    for edge in edges {
+        let (source, target) = (edge.source(), edge.target());
+        digraph.add_edge(source, target, edge.weight.clone());
+        if source != target {
+            digraph.add_edge(target, source, edge.weight);
+        }
+    }
+    digraph
+}
+
+// Split the graph into its connected components, each numbered from 0 in the original order of its nodes
+pub(crate) fn connected_component_graphs<N: Clone, E: Clone, Ix: IndexType>(
+    graph: &Graph<N, E, Undirected, Ix>,
+) -> Vec<Graph<N, E, Undirected, Ix>> {
+    let mut sets = UnionFind::new(graph.node_count());
+    for edge in graph.raw_edges() {
+        sets.union(edge.source().index(), edge.target().index());
+    }
+    // The component of every representative, and the index of every node within its component
+    let mut component_of = vec![usize::MAX; graph.node_count()];
+    let mut local_index = Vec::with_capacity(graph.node_count());
+    let mut components: Vec<Graph<N, E, Undirected, Ix>> = Vec::new();
+    for node in graph.node_indices() {
+        let root = sets.find(node.index());
+        if component_of[root] == usize::MAX {
+            component_of[root] = components.len();
+            components.push(Graph::default());
+        }
+        local_index.push(components[component_of[root]].add_node(graph[node].clone()));
+    }
+    for edge in graph.raw_edges() {
+        let component = component_of[sets.find(edge.source().index())];
+        components[component].add_edge(
+            local_index[edge.source().index()],
+            local_index[edge.target().index()],
+            edge.weight.clone(),
+        );
+    }
+    components
+}
+
+// A copy of the graph with the same nodes and edges but without weights. WL only looks at the structure, so this lets functions run
+// it on weighted graphs without requiring the weights to be `Clone` or `Ord`.
+pub(crate) fn structure<N, E, Ty: EdgeType, Ix: IndexType>(
+    graph: &Graph<N, E, Ty, Ix>,
+) -> Graph<(), (), Ty, Ix> {
+    graph.map(|_, _| (), |_, _| ())
+}
+
+// The disjoint union of two graphs without their weights: the nodes of `first` keep their indices, and those of `second` follow after them.
+// WL on the union gives colours that are comparable between the two graphs.
+pub(crate) fn disjoint_union<N, E, Ty: EdgeType, Ix: IndexType>(
+    first: &Graph<N, E, Ty, Ix>,
+    second: &Graph<N, E, Ty, Ix>,
+) -> Graph<(), (), Ty> {
+    let offset = first.node_count();
+    let mut union = Graph::with_capacity(
+        offset + second.node_count(),
+        first.edge_count() + second.edge_count(),
+    );
+    for _ in 0..offset + second.node_count() {
+        union.add_node(());
+    }
+    for (graph, shift) in [(first, 0), (second, offset)] {
+        for edge in graph.raw_edges() {
+            union.add_edge(
+                NodeIndex::new(edge.source().index() + shift),
+                NodeIndex::new(edge.target().index() + shift),
+                (),
+            );
+        }
+    }
+    union
+}
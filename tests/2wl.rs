@@ -69,3 +69,45 @@ fn extra_expressive() {
         wl_isomorphism::invariant_2wl(big_cycle)
     );
 }
+
+#[test]
+fn pair_classes_cycle() {
+    let cycle = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 0)]);
+    let classes = wl_isomorphism::pair_classes_2wl(cycle);
+    // The diagonal, the pairs at distance 1 and the pairs at distance 2
+    assert_eq!(classes.len(), 3);
+    let distance = |a: usize, b: usize| {
+        let d = a.abs_diff(b);
+        d.min(5 - d)
+    };
+    let mut distances: Vec<usize> = classes
+        .values()
+        .map(|pairs| {
+            let (a, b) = pairs[0];
+            let d = distance(a.index(), b.index());
+            assert!(pairs
+                .iter()
+                .all(|&(a, b)| a >= b && distance(a.index(), b.index()) == d));
+            assert_eq!(pairs.len(), 5);
+            d
+        })
+        .collect();
+    distances.sort_unstable();
+    assert_eq!(distances, [0, 1, 2]);
+}
+
+#[test]
+fn pair_classes_refine_distance() {
+    for seed in 0..8 {
+        // Sparse enough to have several components, so that unreachable pairs are checked too
+        let g = wl_isomorphism::generators::gnp(14, 0.15, seed);
+        let distances = wl_isomorphism::distance_matrix_via_bfs(&g);
+        for pairs in wl_isomorphism::pair_classes_2wl(g).values() {
+            let (a, b) = pairs[0];
+            let distance = distances[a.index()][b.index()];
+            for &(c, d) in pairs {
+                assert_eq!(distances[c.index()][d.index()], distance, "seed {}", seed);
+            }
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use petgraph::graph::UnGraph;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Counts the allocations of the whole test binary, which is why it holds only this test
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+#[test]
+fn no_allocations_per_iteration() {
+    // A path takes n / 2 iterations to stabilise, a star only one
+    let n = 2000;
+    let path = UnGraph::<(), ()>::from_edges((0..n - 1).map(|node| (node, node + 1)));
+    let star = UnGraph::<(), ()>::from_edges((1..n).map(|leaf| (0, leaf)));
+    let count = |graph: UnGraph<(), ()>| {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        wl_isomorphism::invariant(graph);
+        ALLOCATIONS.load(Ordering::Relaxed) - before
+    };
+    let long = count(path);
+    let short = count(star);
+    // The path needs about n / 2 iterations. Each allocates the buffer for the neighbour labels once, but checking for stability
+    // allocates nothing, where a hash map would allocate several times as it grows
+    assert!(
+        long - short <= n as usize / 2 + 16,
+        "{} allocations for a long run against {} for a short one",
+        long,
+        short
+    );
+}
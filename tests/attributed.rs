@@ -0,0 +1,60 @@
+use petgraph::graph::{DiGraph, UnGraph};
+use wl_isomorphism::invariant_full_attr;
+
+// A molecule as a graph of element symbols with bond orders
+fn molecule(atoms: &[&'static str], bonds: &[(u32, u32, u8)]) -> UnGraph<&'static str, u8> {
+    UnGraph::<(), u8>::from_edges(bonds).map(|node, _| atoms[node.index()], |_, &order| order)
+}
+
+#[test]
+fn atoms_and_bonds() {
+    // Acetic acid, CH3-C(=O)-OH without the hydrogens
+    let acetic = molecule(&["C", "C", "O", "O"], &[(0, 1, 1), (1, 2, 2), (1, 3, 1)]);
+    // Both oxygens have degree 1, but one has a double bond: swapping the bond orders gives the same molecule
+    let swapped = molecule(&["C", "C", "O", "O"], &[(0, 1, 1), (1, 2, 1), (1, 3, 2)]);
+    assert_eq!(
+        invariant_full_attr(acetic.clone()),
+        invariant_full_attr(swapped)
+    );
+    // The methyl carbon and the oxygens all have degree 1; putting the double bond on the carbon is a different molecule
+    let moved = molecule(&["C", "C", "O", "O"], &[(0, 1, 2), (1, 2, 1), (1, 3, 1)]);
+    assert_ne!(
+        invariant_full_attr(acetic.clone()),
+        invariant_full_attr(moved)
+    );
+    // The same structure with a nitrogen in place of an oxygen
+    let amide = molecule(&["C", "C", "O", "N"], &[(0, 1, 1), (1, 2, 2), (1, 3, 1)]);
+    assert_ne!(
+        invariant_full_attr(acetic.clone()),
+        invariant_full_attr(amide)
+    );
+    // Plain WL sees only the star
+    let plain = |g: UnGraph<&str, u8>| wl_isomorphism::invariant(g.map(|_, _| (), |_, _| ()));
+    assert_eq!(
+        plain(acetic.clone()),
+        plain(molecule(
+            &["C", "C", "O", "N"],
+            &[(0, 1, 2), (1, 2, 1), (1, 3, 1)]
+        ))
+    );
+}
+
+#[test]
+fn relabelled_and_directed() {
+    let atoms = ["C", "N", "C", "O", "C"];
+    let bonds = [(0, 1, 1), (1, 2, 2), (2, 3, 1), (3, 4, 1), (4, 0, 2)];
+    let ring = molecule(&atoms, &bonds);
+    // The same ring with the atoms numbered backwards
+    let reversed_atoms: Vec<&str> = atoms.iter().rev().copied().collect();
+    let reversed_bonds: Vec<(u32, u32, u8)> = bonds
+        .iter()
+        .map(|&(a, b, order)| (4 - b, 4 - a, order))
+        .collect();
+    let reversed = molecule(&reversed_atoms, &reversed_bonds);
+    assert_eq!(invariant_full_attr(ring), invariant_full_attr(reversed));
+
+    // Edge weights keep their direction
+    let forward = DiGraph::<u8, u8>::from_edges([(0, 1, 1), (1, 2, 2)]);
+    let backward = DiGraph::<u8, u8>::from_edges([(0, 1, 2), (1, 2, 1)]);
+    assert_ne!(invariant_full_attr(forward), invariant_full_attr(backward));
+}
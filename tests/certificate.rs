@@ -0,0 +1,98 @@
+use petgraph::graph::{DiGraph, UnGraph};
+use wl_isomorphism::generators::barabasi_albert;
+use wl_isomorphism::{certificate, invariant_counted, Certificate, WlError};
+
+#[test]
+fn round_trip() {
+    let graphs = [
+        certificate(UnGraph::<(), ()>::from_edges([
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (2, 3),
+        ])),
+        certificate(DiGraph::<(), ()>::from_edges([
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (2, 3),
+        ])),
+        certificate(barabasi_albert(500, 2, 42)),
+        // A single colour with a count that needs several varint bytes
+        certificate(UnGraph::<(), ()>::from_edges(
+            (0..20_000).map(|i| (i, (i + 1) % 20_000)),
+        )),
+    ];
+    for cert in &graphs {
+        let decoded = Certificate::from_bytes(&cert.to_bytes()).unwrap();
+        assert_eq!(&decoded, cert);
+        assert_eq!(decoded.digest(), cert.digest());
+    }
+    assert_eq!(graphs[3].to_bytes().len(), 2 + 8 + 3);
+    assert_eq!(
+        graphs[2].digest(),
+        invariant_counted(barabasi_albert(500, 2, 42))
+    );
+
+    // Corrupt encodings are rejected rather than decoded into something else
+    let bytes = graphs[0].to_bytes();
+    assert!(matches!(
+        Certificate::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(WlError::Decode { .. })
+    ));
+    let mut longer = bytes.clone();
+    longer.push(0);
+    assert!(matches!(
+        Certificate::from_bytes(&longer),
+        Err(WlError::Decode { offset, .. }) if offset == bytes.len()
+    ));
+}
+
+#[test]
+fn stored_certificate() {
+    // The encoding of the tailed triangle 0-1-2-0, 2-3, as written by an earlier run
+    let stored = [
+        0x02, 0x03, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+    ];
+    let stored = Certificate::from_bytes(&stored).unwrap();
+    let copy = certificate(UnGraph::<(), ()>::from_edges([
+        (3, 1),
+        (1, 2),
+        (2, 3),
+        (3, 0),
+    ]));
+    assert!(stored.matches(&copy));
+    assert_eq!(stored.digest(), copy.digest());
+    let path = certificate(UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]));
+    assert!(!stored.matches(&path));
+
+    // The same colours from the first labelling scheme no longer match
+    let mut first_scheme = stored.to_bytes();
+    first_scheme[0] = 0x01;
+    let first_scheme = Certificate::from_bytes(&first_scheme).unwrap();
+    assert_eq!(first_scheme.colours(), copy.colours());
+    assert!(!first_scheme.matches(&copy));
+
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_string(&stored).unwrap();
+        assert!(serde_json::from_str::<Certificate>(&json)
+            .unwrap()
+            .matches(&copy));
+    }
+}
+
+#[test]
+fn empty_graph() {
+    let empty = certificate(UnGraph::<(), ()>::default());
+    assert!(empty.colours().is_empty());
+    assert_eq!(
+        empty.digest(),
+        invariant_counted(UnGraph::<(), ()>::default())
+    );
+    assert_eq!(Certificate::from_bytes(&empty.to_bytes()).unwrap(), empty);
+    let mut single = UnGraph::<(), ()>::default();
+    single.add_node(());
+    assert!(!empty.matches(&certificate(single)));
+}
@@ -0,0 +1,82 @@
+use petgraph::graph::UnGraph;
+
+#[test]
+fn levels_refine() {
+    let mut graphs: Vec<UnGraph<(), ()>> = (0..20)
+        .map(|seed| wl_isomorphism::generators::gnp(10, 0.3, seed))
+        .collect();
+    graphs.extend((0..10).map(|seed| wl_isomorphism::generators::barabasi_albert(12, 1, seed)));
+    graphs.push(graphs[3].clone());
+    let dendrogram = wl_isomorphism::wl_hierarchical_cluster(&graphs);
+    assert!(!dendrogram.levels.is_empty());
+    for level in &dendrogram.levels {
+        let mut members: Vec<usize> = level.iter().flatten().copied().collect();
+        members.sort_unstable();
+        assert_eq!(members, (0..graphs.len()).collect::<Vec<_>>());
+    }
+    // Every group of a level lies within one group of the level before
+    for pair in dendrogram.levels.windows(2) {
+        for group in &pair[1] {
+            assert!(pair[0]
+                .iter()
+                .any(|coarse| group.iter().all(|graph| coarse.contains(graph))));
+        }
+    }
+    // Level k groups the graphs by their hashes after k + 1 labellings
+    for (k, level) in dendrogram.levels.iter().enumerate() {
+        for group in level {
+            let first = wl_isomorphism::invariant_iters(graphs[group[0]].clone(), k + 1);
+            for &graph in group {
+                assert_eq!(
+                    wl_isomorphism::invariant_iters(graphs[graph].clone(), k + 1),
+                    first
+                );
+            }
+        }
+    }
+    // The last level groups the graphs by their invariants
+    let invariants: Vec<u64> = graphs
+        .iter()
+        .map(|graph| wl_isomorphism::invariant(graph.clone()))
+        .collect();
+    for group in dendrogram.levels.last().unwrap() {
+        assert!(group
+            .iter()
+            .all(|&graph| invariants[graph] == invariants[group[0]]));
+    }
+    let last = dendrogram.levels.last().unwrap();
+    let mut distinct = invariants.clone();
+    distinct.sort_unstable();
+    distinct.dedup();
+    assert_eq!(last.len(), distinct.len());
+    assert!(last.iter().any(|group| group == &vec![3, graphs.len() - 1]));
+
+    let empty: &[UnGraph<(), ()>] = &[];
+    assert!(wl_isomorphism::wl_hierarchical_cluster(empty)
+        .levels
+        .is_empty());
+}
+
+#[test]
+fn mixed_sizes() {
+    // The path needs more iterations than the small graphs have nodes
+    let path = UnGraph::<(), ()>::from_edges((0..11).map(|i| (i, i + 1)));
+    let triangle = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0)]);
+    let edge = UnGraph::<(), ()>::from_edges([(0, 1)]);
+    let graphs = [path, triangle.clone(), edge, triangle];
+    let dendrogram = wl_isomorphism::wl_hierarchical_cluster(&graphs);
+    assert!(dendrogram.levels.len() > 3);
+    for (k, level) in dendrogram.levels.iter().enumerate() {
+        assert_eq!(level.len(), 3);
+        assert!(level.contains(&vec![1, 3]));
+        for group in level {
+            let first = wl_isomorphism::invariant_iters(graphs[group[0]].clone(), k + 1);
+            for &graph in group {
+                assert_eq!(
+                    wl_isomorphism::invariant_iters(graphs[graph].clone(), k + 1),
+                    first
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use std::collections::HashSet;
+use wl_isomorphism::{colour_assignment, stable_colour_for_class};
+
+#[test]
+fn assignment_is_deterministic() {
+    let labels = [7, 3, 7, 11, 3];
+    let first = colour_assignment(&labels);
+    assert_eq!(first.len(), 3);
+    // Only the set of labels matters, not their order or multiplicity
+    assert_eq!(colour_assignment(&[11, 3, 7]), first);
+    assert_eq!(colour_assignment(&labels), first);
+}
+
+#[test]
+fn distinct_labels_get_distinct_colours() {
+    for n in 1..=32u64 {
+        let labels: Vec<u64> = (0..n)
+            .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+            .collect();
+        let colours: HashSet<_> = colour_assignment(&labels).into_values().collect();
+        assert_eq!(colours.len(), n as usize);
+        let stable: HashSet<_> = labels
+            .iter()
+            .map(|&label| stable_colour_for_class(label))
+            .collect();
+        assert_eq!(stable.len(), n as usize);
+    }
+}
+
+#[test]
+fn stable_colours() {
+    assert_eq!(stable_colour_for_class(42), stable_colour_for_class(42));
+    assert_eq!(stable_colour_for_class(42).hex().len(), 7);
+    // Unlike here, the assigned colour of a label depends on which other labels are present
+    let alone = colour_assignment(&[42]);
+    let together = colour_assignment(&[1, 42]);
+    assert_ne!(alone[&42], together[&42]);
+}
@@ -0,0 +1,215 @@
+use petgraph::graph::UnGraph;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use wl_isomorphism::{
+    compare, distinguishing_dimension, invariant_combined, search_corpus, search_corpus_2wl,
+    wl_color_intersection, CompareOptions, Comparison, Decider, WlBudget,
+};
+
+fn two_triangles() -> UnGraph<(), ()> {
+    UnGraph::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)])
+}
+
+fn hexagon() -> UnGraph<(), ()> {
+    UnGraph::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)])
+}
+
+#[test]
+fn exact_catches_wl_failure() {
+    // Both graphs are 2-regular, so 1-WL cannot tell them apart
+    let opts = CompareOptions {
+        escalate_2wl: false,
+        exact_max_nodes: 10,
+    };
+    assert_eq!(
+        compare(two_triangles(), hexagon(), opts),
+        Comparison::WlEqualButNotIsomorphic
+    );
+    let opts = CompareOptions {
+        escalate_2wl: false,
+        exact_max_nodes: 0,
+    };
+    assert_eq!(
+        compare(two_triangles(), hexagon(), opts),
+        Comparison::PossiblyIsomorphic { by: Decider::OneWl }
+    );
+}
+
+#[test]
+fn escalation_to_2wl() {
+    assert_eq!(
+        compare(two_triangles(), hexagon(), CompareOptions::default()),
+        Comparison::NotIsomorphic { by: Decider::TwoWl }
+    );
+}
+
+#[test]
+fn permuted_copy() {
+    let permuted = UnGraph::<(), ()>::from_edges([(3, 1), (1, 5), (5, 0), (0, 2), (2, 4), (4, 3)]);
+    assert_eq!(
+        compare(hexagon(), permuted.clone(), CompareOptions::default()),
+        Comparison::Equal
+    );
+    let opts = CompareOptions {
+        escalate_2wl: true,
+        exact_max_nodes: 5,
+    };
+    assert_eq!(
+        compare(hexagon(), permuted, opts),
+        Comparison::PossiblyIsomorphic { by: Decider::TwoWl }
+    );
+}
+
+#[test]
+fn distinguishing_dimensions() {
+    assert_eq!(
+        distinguishing_dimension(two_triangles(), hexagon(), 2).unwrap(),
+        Some(2)
+    );
+    assert_eq!(
+        distinguishing_dimension(two_triangles(), hexagon(), 1).unwrap(),
+        None
+    );
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+    let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+    assert_eq!(distinguishing_dimension(path, star, 3).unwrap(), Some(1));
+    let permuted = UnGraph::<(), ()>::from_edges([(3, 1), (1, 5), (5, 0), (0, 2), (2, 4), (4, 3)]);
+    assert_eq!(
+        distinguishing_dimension(hexagon(), permuted, 3).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn distinguishing_dimension_too_large() {
+    // 2-dimensional WL would need about 144 TB for the labels of these edgeless graphs, more than a 64-bit process can address
+    let mut empty = UnGraph::<(), ()>::with_capacity(3_000_000, 0);
+    for _ in 0..3_000_000 {
+        empty.add_node(());
+    }
+    assert!(matches!(
+        distinguishing_dimension(empty.clone(), empty.clone(), 1),
+        Ok(None)
+    ));
+    assert!(matches!(
+        distinguishing_dimension(empty.clone(), empty, 2),
+        Err(wl_isomorphism::WlError::TooLarge {
+            nodes: 3_000_000,
+            dimension: 2
+        })
+    ));
+}
+
+#[test]
+fn color_intersection() {
+    // Isomorphic graphs share every class, with equal counts
+    let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3), (3, 4)]);
+    let h = UnGraph::<(), ()>::from_edges([(4, 3), (3, 2), (2, 4), (2, 1), (1, 0)]);
+    let shared = wl_color_intersection(g.clone(), h);
+    assert_eq!(shared.len(), 4);
+    assert!(shared.values().all(|(left, right)| left == right));
+    assert_eq!(shared.values().map(|(left, _)| left).sum::<usize>(), 5);
+
+    // The leaves of the path and the star already differ in the degree of their neighbour
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+    let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+    assert!(wl_color_intersection(path, star).is_empty());
+
+    // Graphs that stabilise after a different number of iterations still share the classes they have in common
+    let two_paths = UnGraph::<(), ()>::from_edges([(0, 1), (2, 3), (3, 4), (4, 5), (5, 6)]);
+    let shared = wl_color_intersection(UnGraph::<(), ()>::from_edges([(0, 1)]), two_paths);
+    assert_eq!(shared.len(), 1);
+    assert_eq!(shared.into_values().next(), Some((2, 2)));
+}
+
+#[test]
+fn corpus_search() {
+    let permuted = UnGraph::<(), ()>::from_edges([(3, 1), (1, 5), (5, 0), (0, 2), (2, 4), (4, 3)]);
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)]);
+    let corpus = [two_triangles(), path.clone(), permuted, hexagon()];
+    // 1-WL cannot tell the triangles from the hexagons, 2-WL can
+    assert_eq!(search_corpus(hexagon(), &corpus), vec![0, 2, 3]);
+    assert_eq!(search_corpus_2wl(hexagon(), &corpus), vec![2, 3]);
+    assert_eq!(search_corpus_2wl(two_triangles(), &corpus), vec![0]);
+    assert_eq!(search_corpus_2wl(path.clone(), &corpus), vec![1]);
+    assert!(search_corpus(path, &corpus[2..4]).is_empty());
+}
+
+#[test]
+fn combined_invariant() {
+    let full = WlBudget::default();
+    let one_wl_only = WlBudget { max_nodes_2wl: 0 };
+    let triangles = invariant_combined(two_triangles(), full);
+    let ring = invariant_combined(hexagon(), full);
+    assert!(triangles.two_wl.is_some());
+    assert_ne!(triangles, ring);
+    let triangles_cheap = invariant_combined(two_triangles(), one_wl_only);
+    let hexagon_cheap = invariant_combined(hexagon(), one_wl_only);
+    assert_eq!(triangles_cheap.two_wl, None);
+    assert_eq!(triangles_cheap, hexagon_cheap);
+
+    // Equality is strict, so that equal invariants hash equal, while compatible only compares what both sides computed
+    let hash = |invariant: &wl_isomorphism::CombinedInvariant| {
+        let mut hasher = DefaultHasher::new();
+        invariant.hash(&mut hasher);
+        hasher.finish()
+    };
+    assert_ne!(triangles, triangles_cheap);
+    assert!(triangles.compatible(&triangles_cheap));
+    assert!(ring.compatible(&triangles_cheap));
+    assert!(!triangles.compatible(&ring));
+    assert_eq!(hash(&triangles_cheap), hash(&hexagon_cheap));
+    let deduplicated: HashSet<_> = [triangles, ring, triangles, triangles_cheap]
+        .into_iter()
+        .collect();
+    assert_eq!(deduplicated.len(), 3);
+}
+
+#[test]
+fn empty_graphs() {
+    let empty = UnGraph::<(), ()>::default();
+    let mut single = UnGraph::<(), ()>::default();
+    single.add_node(());
+    assert_eq!(
+        compare(empty.clone(), empty.clone(), CompareOptions::default()),
+        Comparison::Equal
+    );
+    let without_exact = CompareOptions {
+        escalate_2wl: true,
+        exact_max_nodes: 0,
+    };
+    assert_eq!(
+        compare(empty.clone(), empty.clone(), without_exact),
+        Comparison::Equal
+    );
+    assert_eq!(
+        compare(empty.clone(), single.clone(), CompareOptions::default()),
+        Comparison::NotIsomorphic { by: Decider::OneWl }
+    );
+    assert_eq!(
+        distinguishing_dimension(empty.clone(), empty.clone(), 2).unwrap(),
+        None
+    );
+    assert_eq!(
+        distinguishing_dimension(empty.clone(), single.clone(), 2).unwrap(),
+        Some(1)
+    );
+
+    let corpus = [single.clone(), empty.clone(), hexagon(), empty.clone()];
+    assert_eq!(search_corpus(empty.clone(), &corpus), vec![1, 3]);
+    assert_eq!(search_corpus_2wl(empty.clone(), &corpus), vec![1, 3]);
+    assert_eq!(search_corpus_2wl(single.clone(), &corpus), vec![0]);
+
+    let combined = invariant_combined(empty.clone(), WlBudget::default());
+    assert_eq!(combined.one_wl, wl_isomorphism::invariant(empty.clone()));
+    assert_eq!(
+        combined.two_wl,
+        Some(wl_isomorphism::invariant_2wl(empty.clone()))
+    );
+    assert_ne!(combined, invariant_combined(single, WlBudget::default()));
+    assert_eq!(
+        invariant_combined(empty, WlBudget { max_nodes_2wl: 0 }).two_wl,
+        combined.two_wl
+    );
+}
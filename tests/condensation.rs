@@ -0,0 +1,37 @@
+use petgraph::graph::DiGraph;
+
+#[test]
+fn condensation() {
+    // A source pointing into every node of a directed 6-cycle, or of two directed triangles
+    let with_source = |edges: &[(u32, u32)]| {
+        let mut graph = DiGraph::<(), ()>::from_edges(edges);
+        let source = graph.add_node(());
+        for node in 0..6 {
+            graph.add_edge(source, node.into(), ());
+        }
+        graph
+    };
+    let cycle = with_source(&[(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+    let triangles = with_source(&[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+    // Plain WL sees one edge in and out of every cycle node, and one more from the source
+    assert_eq!(
+        wl_isomorphism::invariant(cycle.clone()),
+        wl_isomorphism::invariant(triangles.clone())
+    );
+    let two_components = wl_isomorphism::invariant_condensation(cycle);
+    let three_components = wl_isomorphism::invariant_condensation(triangles.clone());
+    assert_ne!(two_components, three_components);
+    let permuted = wl_isomorphism::apply_node_permutation(triangles, &[6, 3, 0, 5, 1, 4, 2]);
+    assert_eq!(
+        wl_isomorphism::invariant_condensation(permuted),
+        three_components
+    );
+
+    // The direction of the edges between the components counts: from the pair to the single node, or the other way
+    let backwards = DiGraph::<(), ()>::from_edges([(0, 1), (1, 0), (2, 0)]);
+    let forwards = DiGraph::<(), ()>::from_edges([(0, 1), (1, 0), (0, 2)]);
+    assert_ne!(
+        wl_isomorphism::invariant_condensation(backwards),
+        wl_isomorphism::invariant_condensation(forwards)
+    );
+}
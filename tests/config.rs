@@ -0,0 +1,377 @@
+use petgraph::graph::{DiGraph, UnGraph};
+use wl_isomorphism::{
+    AggregationMode, DirectedMode, LimitKind, MultigraphPolicy, SelfLoopPolicy, WlConfig, WlError,
+    WlLimits,
+};
+
+#[test]
+fn multigraph_policies() {
+    let doubled = UnGraph::<(), ()>::from_edges([(0, 1), (1, 0)]);
+    let single = UnGraph::<(), ()>::from_edges([(0, 1)]);
+    let with = |policy| WlConfig::new().multigraph_policy(policy);
+
+    let counted = with(MultigraphPolicy::CountEdges)
+        .invariant(doubled.clone())
+        .unwrap();
+    assert_eq!(counted, wl_isomorphism::invariant(doubled.clone()));
+    assert_ne!(counted, wl_isomorphism::invariant(single.clone()));
+
+    let distinct = with(MultigraphPolicy::CountDistinctNeighbours)
+        .invariant(doubled.clone())
+        .unwrap();
+    assert_eq!(distinct, wl_isomorphism::invariant(single.clone()));
+
+    match with(MultigraphPolicy::Reject).invariant(doubled) {
+        Err(WlError::ParallelEdges { source, target }) => assert_eq!((source, target), (0, 1)),
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(
+        with(MultigraphPolicy::Reject)
+            .invariant(single.clone())
+            .unwrap(),
+        wl_isomorphism::invariant(single)
+    );
+}
+
+#[test]
+fn directed_multigraphs() {
+    // Opposite arcs are not parallel
+    let cycle = DiGraph::<(), ()>::from_edges([(0, 1), (1, 0)]);
+    let reject = WlConfig::new().multigraph_policy(MultigraphPolicy::Reject);
+    assert!(reject.invariant(cycle).is_ok());
+    let doubled = DiGraph::<(), ()>::from_edges([(0, 1), (0, 1)]);
+    assert!(reject.invariant(doubled.clone()).is_err());
+    assert_eq!(
+        WlConfig::new()
+            .multigraph_policy(MultigraphPolicy::CountDistinctNeighbours)
+            .invariant(doubled)
+            .unwrap(),
+        wl_isomorphism::invariant(DiGraph::<(), ()>::from_edges([(0, 1)]))
+    );
+}
+
+#[test]
+fn seed() {
+    let graph = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+    assert_eq!(
+        WlConfig::new().invariant(graph.clone()).unwrap(),
+        wl_isomorphism::invariant(graph.clone())
+    );
+    assert_ne!(
+        WlConfig::new().seed(7).invariant(graph.clone()).unwrap(),
+        wl_isomorphism::invariant(graph)
+    );
+}
+
+#[test]
+fn empty_graph() {
+    let empty = UnGraph::<(), ()>::default();
+    assert_eq!(
+        WlConfig::new().invariant(empty.clone()).unwrap(),
+        wl_isomorphism::invariant(empty.clone())
+    );
+    assert_eq!(
+        WlConfig::new().invariant_2wl(empty.clone()).unwrap(),
+        wl_isomorphism::invariant_2wl(empty)
+    );
+}
+
+#[test]
+fn self_loop_policies() {
+    // A triangle with a loop at node 0
+    let looped = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (0, 0)]);
+    let triangle = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0)]);
+    let with = |policy| WlConfig::new().self_loop_policy(policy);
+
+    let ignored = with(SelfLoopPolicy::Ignore)
+        .invariant(looped.clone())
+        .unwrap();
+    assert_eq!(ignored, wl_isomorphism::invariant(triangle.clone()));
+
+    let once = with(SelfLoopPolicy::CountOnce)
+        .invariant(looped.clone())
+        .unwrap();
+    assert_eq!(once, wl_isomorphism::invariant(looped.clone()));
+    assert_ne!(once, ignored);
+
+    // Counted twice, the looped node has degree 4
+    let twice = with(SelfLoopPolicy::CountTwice)
+        .invariant(looped.clone())
+        .unwrap();
+    assert_ne!(twice, once);
+    assert_ne!(twice, ignored);
+
+    match with(SelfLoopPolicy::Reject).invariant(looped.clone()) {
+        Err(WlError::SelfLoop { node }) => assert_eq!(node, 0),
+        other => panic!("{:?}", other),
+    }
+    assert!(with(SelfLoopPolicy::Reject).invariant(triangle).is_ok());
+
+    // 2-dimensional WL counts the loop in the label of the node paired with itself
+    let hashes: Vec<u64> = [
+        SelfLoopPolicy::Ignore,
+        SelfLoopPolicy::CountOnce,
+        SelfLoopPolicy::CountTwice,
+    ]
+    .into_iter()
+    .map(|policy| with(policy).invariant_2wl(looped.clone()).unwrap())
+    .collect();
+    assert_eq!(hashes[1], wl_isomorphism::invariant_2wl(looped.clone()));
+    assert!(hashes[0] != hashes[1] && hashes[1] != hashes[2] && hashes[0] != hashes[2]);
+    assert!(with(SelfLoopPolicy::Reject).invariant_2wl(looped).is_err());
+}
+
+#[test]
+fn directed_self_loops() {
+    let looped = DiGraph::<(), ()>::from_edges([(0, 1), (1, 0), (0, 0)]);
+    let ignored = WlConfig::new()
+        .self_loop_policy(SelfLoopPolicy::Ignore)
+        .invariant(looped)
+        .unwrap();
+    assert_eq!(
+        ignored,
+        wl_isomorphism::invariant(DiGraph::<(), ()>::from_edges([(0, 1), (1, 0)]))
+    );
+}
+
+#[test]
+fn aggregation_modes() {
+    let modes = [
+        AggregationMode::SortedHash,
+        AggregationMode::XorFold,
+        AggregationMode::SumFold,
+    ];
+    let hash =
+        |mode, graph: UnGraph<(), ()>| WlConfig::new().aggregation(mode).invariant(graph).unwrap();
+    assert_eq!(
+        hash(
+            AggregationMode::SortedHash,
+            wl_isomorphism::generators::gnp(30, 0.2, 1)
+        ),
+        wl_isomorphism::invariant(wl_isomorphism::generators::gnp(30, 0.2, 1))
+    );
+    assert_eq!(
+        hash(
+            AggregationMode::SumFold,
+            wl_isomorphism::generators::gnp(30, 0.2, 1)
+        ),
+        wl_isomorphism::invariant_sum_agg(wl_isomorphism::generators::gnp(30, 0.2, 1))
+    );
+
+    // Pairs of isomorphic graphs from the other tests, which every mode must hash alike
+    let isomorphic = [
+        (
+            vec![(0, 1), (1, 2), (2, 0), (2, 3)],
+            vec![(0, 1), (1, 2), (2, 0), (0, 3)],
+        ),
+        (
+            vec![(0, 1), (1, 2), (2, 3), (3, 4)],
+            vec![(1, 0), (2, 1), (2, 3), (4, 3)],
+        ),
+    ];
+    // Pairs of non-isomorphic graphs from the other tests, which every mode tells apart
+    let different = [
+        (
+            vec![(0, 1), (1, 2), (2, 0), (2, 3)],
+            vec![(0, 1), (1, 2), (2, 3), (0, 3)],
+        ),
+        (
+            vec![(0, 1), (1, 2), (2, 3), (3, 4)],
+            vec![(0, 1), (0, 2), (0, 3), (0, 4)],
+        ),
+        (
+            vec![(0, 1), (1, 2), (2, 3), (3, 4), (1, 5)],
+            vec![(0, 1), (1, 2), (2, 3), (3, 4), (2, 5)],
+        ),
+    ];
+    for mode in modes {
+        for (g, h) in &isomorphic {
+            let (g, h) = (UnGraph::from_edges(g), UnGraph::from_edges(h));
+            assert_eq!(hash(mode, g), hash(mode, h), "{:?}", mode);
+        }
+        for (g, h) in &different {
+            let (g, h) = (UnGraph::from_edges(g), UnGraph::from_edges(h));
+            assert_ne!(hash(mode, g), hash(mode, h), "{:?}", mode);
+        }
+        for seed in 0..5 {
+            let g = wl_isomorphism::generators::gnp(30, 0.2, seed);
+            let h = wl_isomorphism::generators::rewire_edges(g.clone(), 1, seed);
+            let relabelled = wl_isomorphism::generators::rewire_edges(g.clone(), 0, seed);
+            assert_eq!(hash(mode, g.clone()), hash(mode, relabelled), "{:?}", mode);
+            if wl_isomorphism::invariant(g.clone()) != wl_isomorphism::invariant(h.clone()) {
+                assert_ne!(hash(mode, g), hash(mode, h), "{:?}", mode);
+            }
+        }
+    }
+
+    // XOR cancels pairs of equal labels, which loses the difference between these two graphs with the same degrees
+    let g = UnGraph::<(), ()>::from_edges([(1, 3), (1, 4), (3, 4), (0, 5), (2, 5), (4, 5)]);
+    let h = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (2, 3), (1, 4), (2, 4), (0, 5)]);
+    assert_eq!(
+        hash(AggregationMode::XorFold, g.clone()),
+        hash(AggregationMode::XorFold, h.clone())
+    );
+    for mode in [AggregationMode::SortedHash, AggregationMode::SumFold] {
+        assert_ne!(hash(mode, g.clone()), hash(mode, h.clone()), "{:?}", mode);
+    }
+}
+
+#[test]
+fn limits() {
+    let path = UnGraph::<(), ()>::from_edges((0..19u32).map(|node| (node, node + 1)));
+    let limited = |limits| WlConfig::new().limits(limits);
+
+    // Too many nodes or edges are rejected before any refinement
+    let error = limited(WlLimits {
+        max_nodes: Some(10),
+        ..WlLimits::default()
+    })
+    .invariant(path.clone())
+    .unwrap_err();
+    assert!(matches!(
+        error,
+        WlError::LimitExceeded {
+            which: LimitKind::Nodes,
+            limit: 10,
+            observed: 20,
+            iterations: 0
+        }
+    ));
+    let error = limited(WlLimits {
+        max_edges: Some(18),
+        ..WlLimits::default()
+    })
+    .invariant_2wl(path.clone())
+    .unwrap_err();
+    assert!(matches!(
+        error,
+        WlError::LimitExceeded {
+            which: LimitKind::Edges,
+            observed: 19,
+            ..
+        }
+    ));
+
+    // A path of 20 nodes needs 8 iterations to split, and a 9th to confirm, each computing 20 labels after the 20 initial ones
+    let enough = WlLimits {
+        max_nodes: Some(20),
+        max_edges: Some(19),
+        max_total_label_hashes: Some(200),
+    };
+    assert_eq!(
+        limited(enough).invariant(path.clone()).unwrap(),
+        wl_isomorphism::invariant(path.clone())
+    );
+    let error = limited(WlLimits {
+        max_total_label_hashes: Some(100),
+        ..enough
+    })
+    .invariant(path.clone())
+    .unwrap_err();
+    assert!(matches!(
+        error,
+        WlError::LimitExceeded {
+            which: LimitKind::LabelHashes,
+            limit: 100,
+            observed: 120,
+            iterations: 4
+        }
+    ));
+    assert_eq!(
+        error.to_string(),
+        "label hash limit of 100 exceeded with 120 after 4 iterations"
+    );
+
+    // An iteration of 2-dimensional WL computes a label for each of the 210 pairs
+    let error = limited(WlLimits {
+        max_total_label_hashes: Some(500),
+        ..WlLimits::default()
+    })
+    .invariant_2wl(path)
+    .unwrap_err();
+    assert!(matches!(
+        error,
+        WlError::LimitExceeded {
+            which: LimitKind::LabelHashes,
+            observed: 630,
+            iterations: 1,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn directed_modes() {
+    let hash = |mode, graph: DiGraph<(), ()>| {
+        WlConfig::new()
+            .directed_mode(mode)
+            .invariant(graph)
+            .unwrap()
+    };
+    // A path with an edge next to the middle flipped, and the same with every edge reversed. On a shorter path every node would start
+    // with a label of its own, and the refinement would stop at the initial labels, which the two graphs share
+    let flipped = DiGraph::<(), ()>::from_edges([(0, 1), (1, 2), (3, 2), (3, 4), (4, 5), (5, 6)]);
+    let reversed = DiGraph::<(), ()>::from_edges([(1, 0), (2, 1), (2, 3), (4, 3), (5, 4), (6, 5)]);
+    let relabelled =
+        DiGraph::<(), ()>::from_edges([(6, 5), (5, 4), (3, 4), (3, 2), (2, 1), (1, 0)]);
+    assert_eq!(
+        hash(DirectedMode::InOutSeparate, flipped.clone()),
+        wl_isomorphism::invariant(flipped.clone())
+    );
+    for mode in [
+        DirectedMode::InOutSeparate,
+        DirectedMode::InOutPlusUndirected,
+    ] {
+        assert_ne!(
+            hash(mode, flipped.clone()),
+            hash(mode, reversed.clone()),
+            "{:?}",
+            mode
+        );
+        assert_eq!(
+            hash(mode, flipped.clone()),
+            hash(mode, relabelled.clone()),
+            "{:?}",
+            mode
+        );
+    }
+    assert_ne!(
+        hash(DirectedMode::InOutSeparate, flipped.clone()),
+        hash(DirectedMode::InOutPlusUndirected, flipped.clone())
+    );
+
+    // Without direction both are paths
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 6)]);
+    assert_eq!(
+        hash(DirectedMode::IgnoreDirection, flipped),
+        wl_isomorphism::invariant(path.clone())
+    );
+    assert_eq!(
+        hash(DirectedMode::IgnoreDirection, reversed),
+        wl_isomorphism::invariant(path)
+    );
+    // Edges in both directions become parallel in the skeleton, which the policies then see
+    let cycle = DiGraph::<(), ()>::from_edges([(0, 1), (1, 0), (1, 2), (2, 1)]);
+    let config = WlConfig::new()
+        .directed_mode(DirectedMode::IgnoreDirection)
+        .multigraph_policy(MultigraphPolicy::CountDistinctNeighbours);
+    assert_eq!(
+        config.invariant(cycle).unwrap(),
+        wl_isomorphism::invariant(UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]))
+    );
+
+    // Undirected graphs are not affected
+    for mode in [
+        DirectedMode::InOutPlusUndirected,
+        DirectedMode::IgnoreDirection,
+    ] {
+        let graph = wl_isomorphism::generators::gnp(30, 0.2, 1);
+        assert_eq!(
+            WlConfig::new()
+                .directed_mode(mode)
+                .invariant(graph.clone())
+                .unwrap(),
+            wl_isomorphism::invariant(graph)
+        );
+    }
+}
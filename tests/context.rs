@@ -0,0 +1,49 @@
+use petgraph::graph::UnGraph;
+use wl_isomorphism::{ColourId, WlContext, WlError};
+
+#[test]
+fn kernel_within_context() {
+    let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+    let h = UnGraph::<(), ()>::from_edges([(3, 1), (1, 2), (2, 3), (3, 0)]);
+    let context = WlContext::new(42);
+    let (hg, hh) = (context.wl_histogram(&g, 3), context.wl_histogram(&h, 3));
+    // Isomorphic graphs have the same histogram
+    assert_eq!(hg, hh);
+    let squared: usize = hg.iter().map(|(_, count)| count * count).sum();
+    assert_eq!(context.wl_kernel(&hg, &hh).unwrap(), squared as f64);
+
+    // The colours are the same as the plain labels with the same seed
+    let colours = context.neighbourhood_colours(g.clone(), 3);
+    let labels = wl_isomorphism::neighbourhood_hash(g, 3);
+    assert_eq!(colours[2][1], ColourId(labels[2][1]));
+}
+
+#[test]
+fn contexts_do_not_mix() {
+    let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+    let first = WlContext::new(42);
+    let second = WlContext::new(7);
+    let (h1, h2) = (first.wl_histogram(&g, 3), second.wl_histogram(&g, 3));
+    assert_ne!(h1, h2);
+    match first.wl_kernel(&h1, &h2) {
+        Err(WlError::ContextMismatch { left, right }) => {
+            assert_eq!(left, first);
+            assert_eq!(right, second);
+        }
+        other => panic!("expected a context mismatch, got {:?}", other),
+    }
+    // Using a third context for two histograms of the same other context is also an error
+    assert!(matches!(
+        first.wl_kernel(&h2, &h2),
+        Err(WlError::ContextMismatch { .. })
+    ));
+}
+
+#[test]
+fn empty_graph() {
+    let context = WlContext::new(42);
+    let empty = UnGraph::<(), ()>::default();
+    let histogram = context.wl_histogram(&empty, 3);
+    assert_eq!(context.wl_kernel(&histogram, &histogram).unwrap(), 0.0);
+    assert!(context.neighbourhood_colours(empty, 3).is_empty());
+}
@@ -0,0 +1,164 @@
+use petgraph::graph::{DiGraph, UnGraph};
+use petgraph::Direction::{Incoming, Outgoing};
+use petgraph::{EdgeType, Graph};
+use wl_isomorphism::{generators, refine_csr, refine_csr_directed, WlOptions};
+
+// Convert a petgraph graph to CSR lists for the given direction
+fn csr<Ty: EdgeType>(
+    graph: &Graph<(), (), Ty>,
+    direction: petgraph::Direction,
+) -> (Vec<usize>, Vec<u32>) {
+    let mut offsets = vec![0];
+    let mut neighbours = Vec::new();
+    for node in graph.node_indices() {
+        neighbours.extend(
+            graph
+                .neighbors_directed(node, direction)
+                .map(|n| n.index() as u32),
+        );
+        offsets.push(neighbours.len());
+    }
+    (offsets, neighbours)
+}
+
+fn directed(seed: u64) -> DiGraph<(), ()> {
+    let undirected = generators::gnp(12, 0.25, seed);
+    // Orient every edge by a bit of the seed, so both directions occur
+    let mut graph = DiGraph::with_capacity(12, undirected.edge_count());
+    for _ in 0..12 {
+        graph.add_node(());
+    }
+    for (i, edge) in undirected.raw_edges().iter().enumerate() {
+        let (a, b) = (edge.source(), edge.target());
+        if (seed >> (i % 64)) & 1 == 0 {
+            graph.add_edge(a, b, ());
+        } else {
+            graph.add_edge(b, a, ());
+        }
+    }
+    graph
+}
+
+#[test]
+fn undirected_matches_invariant() {
+    for seed in 0..50 {
+        let graph: UnGraph<(), ()> = generators::gnp(12, 0.3, seed);
+        let (offsets, neighbours) = csr(&graph, Outgoing);
+        let (labels, hash) = refine_csr(&offsets, &neighbours, None, WlOptions::default());
+        assert_eq!(labels.len(), 12);
+        assert_eq!(hash, wl_isomorphism::invariant(graph.clone()));
+
+        let opts = WlOptions {
+            n_iters: 3,
+            check_stable: false,
+            ..WlOptions::default()
+        };
+        let (_, hash) = refine_csr(&offsets, &neighbours, None, opts);
+        assert_eq!(hash, wl_isomorphism::invariant_iters(graph.clone(), 3));
+
+        let init: Vec<u64> = (0..12).map(|i| i % 3).collect();
+        let (_, hash) = refine_csr(&offsets, &neighbours, Some(&init), WlOptions::default());
+        assert_eq!(hash, wl_isomorphism::invariant_with_initial(graph, &init));
+    }
+}
+
+#[test]
+fn directed_matches_invariant() {
+    for seed in 0..50 {
+        let graph = directed(seed);
+        let (out_offsets, out_neighbours) = csr(&graph, Outgoing);
+        let (in_offsets, in_neighbours) = csr(&graph, Incoming);
+        let (_, hash) = refine_csr_directed(
+            &out_offsets,
+            &out_neighbours,
+            &in_offsets,
+            &in_neighbours,
+            None,
+            WlOptions::default(),
+        );
+        assert_eq!(hash, wl_isomorphism::invariant(graph));
+    }
+}
+
+#[test]
+fn directed_edge_order() {
+    // The same directed graphs, with the edges inserted in reverse order
+    for seed in 0..50 {
+        let graph = directed(seed);
+        let mut reversed = DiGraph::<(), ()>::with_capacity(12, graph.edge_count());
+        for _ in 0..12 {
+            reversed.add_node(());
+        }
+        for edge in graph.raw_edges().iter().rev() {
+            reversed.add_edge(edge.source(), edge.target(), ());
+        }
+        assert_eq!(
+            wl_isomorphism::invariant(graph),
+            wl_isomorphism::invariant(reversed)
+        );
+    }
+}
+
+// Convert a petgraph graph to adjacency lists of its neighbours in the given direction
+fn adj_list<Ty: EdgeType>(
+    graph: &Graph<(), (), Ty>,
+    direction: petgraph::Direction,
+) -> Vec<Vec<usize>> {
+    graph
+        .node_indices()
+        .map(|node| {
+            let mut neighbours: Vec<usize> = graph
+                .neighbors_directed(node, direction)
+                .map(|n| n.index())
+                .collect();
+            neighbours.reverse();
+            neighbours
+        })
+        .collect()
+}
+
+#[test]
+fn adj_list_matches_invariant() {
+    for seed in 0..50 {
+        let graph: UnGraph<(), ()> = generators::gnp(12, 0.3, seed);
+        assert_eq!(
+            wl_isomorphism::invariant_from_adj_list(adj_list(&graph, Outgoing)),
+            wl_isomorphism::invariant(graph)
+        );
+        let graph = directed(seed);
+        assert_eq!(
+            wl_isomorphism::invariant_from_adj_list_directed(adj_list(&graph, Outgoing)),
+            wl_isomorphism::invariant(graph)
+        );
+    }
+}
+
+#[test]
+fn filtered_views() {
+    use petgraph::visit::{EdgeFiltered, EdgeRef, NodeFiltered};
+    for seed in 0..50 {
+        // Every third node hidden
+        let graph: UnGraph<(), ()> = generators::gnp(15, 0.3, seed);
+        let view = NodeFiltered::from_fn(&graph, |node| node.index() % 3 != 0);
+        let extracted = graph.filter_map(
+            |node, _| (node.index() % 3 != 0).then_some(()),
+            |_, _| Some(()),
+        );
+        assert_eq!(
+            wl_isomorphism::invariant_of_view(&view),
+            wl_isomorphism::invariant(extracted)
+        );
+
+        // Every other edge hidden, in a directed graph
+        let graph = directed(seed);
+        let view = EdgeFiltered::from_fn(&graph, |edge| edge.id().index() % 2 == 0);
+        let extracted = graph.filter_map(
+            |_, _| Some(()),
+            |edge, _| (edge.index() % 2 == 0).then_some(()),
+        );
+        assert_eq!(
+            wl_isomorphism::invariant_of_view(&view),
+            wl_isomorphism::invariant(extracted)
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use petgraph::graph::UnGraph;
+
+#[test]
+fn path_splits() {
+    let g = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
+    let (hash, diffs) = wl_isomorphism::invariant_with_diffs(g.clone());
+    assert_eq!(hash, wl_isomorphism::invariant(g));
+    assert_eq!(diffs.len(), 2);
+    // The inner nodes (degree 2) split into the centre and its neighbours, the ends stay together
+    assert_eq!(diffs[0].splits.len(), 1);
+    assert_eq!(diffs[0].splits[0].0, 2);
+    assert_eq!(diffs[0].splits[0].1.len(), 2);
+    assert_eq!(diffs[0].unchanged, 1);
+    assert!(diffs[1].splits.is_empty());
+    assert_eq!(diffs[1].unchanged, 3);
+}
+
+#[test]
+fn diff_of_labels() {
+    let diff = wl_isomorphism::wl_diff(&[1, 1, 1, 2, 3, 3], &[5, 6, 5, 7, 8, 9]);
+    assert_eq!(diff.splits, vec![(1, vec![5, 6]), (3, vec![8, 9])]);
+    assert_eq!(diff.unchanged, 1);
+}
+
+#[test]
+fn stats_on_path() {
+    // The path 0-1-2-3 is stable after one iteration. Per iteration, every node hashes its neighbours and itself: 6 + 4 elements
+    let g = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+    let (hash, stats) = wl_isomorphism::invariant_with_stats(g.clone());
+    assert_eq!(hash, wl_isomorphism::invariant(g));
+    assert_eq!(
+        stats,
+        wl_isomorphism::WlStats {
+            iterations: 1,
+            label_computations: 4,
+            multiset_elements_hashed: 10,
+            bytes_hashed: 80,
+            ..stats.clone()
+        }
+    );
+    // Two iterations on the 5-node path
+    let g = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
+    let (_, stats) = wl_isomorphism::invariant_with_stats(g);
+    assert_eq!(stats.iterations, 2);
+    assert_eq!(stats.label_computations, 10);
+    assert_eq!(stats.multiset_elements_hashed, 2 * (8 + 5));
+}
+
+#[test]
+fn stats_timings() {
+    let g = UnGraph::<u64, ()>::from_edges((0..200).map(|i| (i, i + 1)));
+    let (_, stats) = wl_isomorphism::invariant_with_stats(g);
+    // One time per iteration, each of which also checked for stability
+    assert_eq!(stats.iteration_times.len(), stats.iterations);
+    assert_eq!(stats.iterations, 100);
+    let refining: std::time::Duration = stats.iteration_times.iter().sum();
+    assert!(refining > std::time::Duration::ZERO);
+    assert!(stats.init_time > std::time::Duration::ZERO);
+    assert!(stats.stability_check_time > std::time::Duration::ZERO);
+    assert!(stats.digest_time > std::time::Duration::ZERO);
+
+    // Without the stats option nothing is timed
+    let mut wrap = wl_isomorphism::GraphWrapper::new(
+        UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]),
+        42,
+        0,
+        true,
+        false,
+    );
+    wrap.run();
+    assert!(wrap.stats().is_none());
+}
+
+#[test]
+fn trace() {
+    let g = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
+    let (hash, trace) = wl_isomorphism::invariant_with_trace(g.clone());
+    assert_eq!(hash, wl_isomorphism::invariant(g));
+    // The degrees, then the middle node splits off
+    assert_eq!(trace.per_iter_labels.len(), 2);
+    assert_eq!(trace.stable_at, Some(1));
+    assert_eq!(trace.n_refinements_at(0), 0);
+    assert_eq!(trace.n_refinements_at(1), 1);
+    assert_eq!(trace.n_refinements_at(2), 1);
+
+    let mut last = trace.per_iter_labels.last().unwrap().clone();
+    last.sort_unstable();
+    assert_eq!(
+        twox_hash::XxHash64::oneshot(42, bytemuck::cast_slice(&last)),
+        hash
+    );
+}
+
+#[test]
+fn diagnose_path_and_star() {
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+    let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+    let report = wl_isomorphism::diagnose(path, star);
+    assert_eq!(report.node_counts, (4, 4));
+    assert_eq!(report.degree_histograms.0, [(1, 2), (2, 2)]);
+    assert_eq!(report.degree_histograms.1, [(1, 3), (3, 1)]);
+    assert_eq!(report.first_divergence, Some(0));
+    // The initial labels are the degrees, and the leaves have the smallest label
+    let class = report.differing_class.clone().unwrap();
+    assert_eq!((class.iteration, class.degree, class.sizes), (0, 1, (2, 3)));
+    assert_eq!(class.examples, (vec![0, 3], vec![1, 2, 3]));
+    let text = report.to_string();
+    assert!(text.contains("first divergence: iteration 0"), "{}", text);
+    assert!(text.contains("nodes of degree 1"), "{}", text);
+}
+
+#[test]
+fn diagnose_isomorphic() {
+    let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+    let h = UnGraph::<(), ()>::from_edges([(3, 1), (1, 2), (2, 3), (3, 0)]);
+    let report = wl_isomorphism::diagnose(g, h);
+    assert_eq!(report.first_divergence, None);
+    assert_eq!(report.differing_class, None);
+    assert_eq!(report.class_counts.0, report.class_counts.1);
+    assert!(report.to_string().contains("no divergence"));
+}
+
+#[test]
+fn diagnose_later_divergence() {
+    // The same degrees, but the neighbourhoods of the degree 2 nodes differ
+    let spider = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (2, 4), (0, 5), (5, 6)]);
+    let caterpillar =
+        UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (2, 6)]);
+    let report = wl_isomorphism::diagnose(spider, caterpillar);
+    assert_eq!(report.degree_histograms.0, report.degree_histograms.1);
+    assert_eq!(report.first_divergence, Some(1));
+    assert_eq!(report.differing_class.unwrap().iteration, 1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn diagnosis_persistence() {
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+    let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+    let report = wl_isomorphism::diagnose(path, star);
+    let json = serde_json::to_string(&report).unwrap();
+    let loaded: wl_isomorphism::DiagnosisReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded, report);
+}
+
+#[test]
+fn refinement_steps_of_trees() {
+    let trees: Vec<_> = (0..30)
+        .map(|seed| wl_isomorphism::generators::barabasi_albert(5 + seed as usize, 1, seed))
+        .collect();
+    let histogram = wl_isomorphism::refinement_steps_distribution(&trees);
+    assert_eq!(histogram.values().sum::<usize>(), trees.len());
+    let max = wl_isomorphism::max_refinement_steps(&trees);
+    assert_eq!(histogram.keys().max(), Some(&max));
+    for tree in trees {
+        let diameter = wl_isomorphism::distance_matrix_via_bfs(&tree)
+            .into_iter()
+            .flatten()
+            .max()
+            .unwrap() as usize;
+        let steps = wl_isomorphism::max_refinement_steps(std::slice::from_ref(&tree));
+        assert!(
+            steps <= diameter / 2 + 1,
+            "{} steps, diameter {}",
+            steps,
+            diameter
+        );
+        // With that many iterations, the invariant is the stable one
+        assert_eq!(
+            wl_isomorphism::invariant_iters(tree.clone(), steps + 1),
+            wl_isomorphism::invariant(tree)
+        );
+    }
+    assert_eq!(
+        wl_isomorphism::max_refinement_steps::<(), (), petgraph::Undirected, u32>(&[]),
+        0
+    );
+}
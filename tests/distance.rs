@@ -0,0 +1,42 @@
+use petgraph::graph::{DiGraph, UnGraph};
+use wl_isomorphism::invariant_distance_encoded;
+
+fn hexagon() -> UnGraph<(), ()> {
+    UnGraph::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)])
+}
+
+fn triangles() -> UnGraph<(), ()> {
+    UnGraph::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)])
+}
+
+#[test]
+fn beyond_1wl() {
+    // Both are 2-regular, so plain WL (and one hop) cannot tell them apart
+    assert_eq!(
+        invariant_distance_encoded(hexagon(), 1),
+        invariant_distance_encoded(triangles(), 1)
+    );
+    for k in 2..=4 {
+        assert_ne!(
+            invariant_distance_encoded(hexagon(), k),
+            invariant_distance_encoded(triangles(), k)
+        );
+    }
+}
+
+#[test]
+fn relabelling() {
+    let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 0), (3, 4)]);
+    let h = UnGraph::<(), ()>::from_edges([(4, 3), (3, 2), (2, 1), (1, 4), (1, 0)]);
+    assert_eq!(
+        invariant_distance_encoded(g, 3),
+        invariant_distance_encoded(h, 3)
+    );
+
+    let g = DiGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+    let h = DiGraph::<(), ()>::from_edges([(3, 2), (2, 1), (1, 0)]);
+    assert_eq!(
+        invariant_distance_encoded(g, 2),
+        invariant_distance_encoded(h, 2)
+    );
+}
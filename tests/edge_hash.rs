@@ -0,0 +1,45 @@
+use petgraph::graph::{DiGraph, UnGraph};
+use wl_isomorphism::edge_neighbourhood_hash;
+
+#[test]
+fn weights_split_classes() {
+    // A star with three leaves: the edges have the same endpoints up to symmetry
+    let star = UnGraph::<(), &str>::from_edges([(0, 1, "a"), (0, 2, "a"), (0, 3, "b")]);
+    let hashes = edge_neighbourhood_hash(star, 3);
+    assert_eq!(hashes.len(), 3);
+    assert!(hashes.iter().all(|row| row.len() == 3));
+    assert_eq!(hashes[0], hashes[1]);
+    assert_ne!(hashes[0][0], hashes[2][0]);
+    // The edges of equal weight see one edge of each weight next to them, the other one two of weight "a"
+    assert_ne!(hashes[0][1], hashes[2][1]);
+}
+
+#[test]
+fn invariant_under_relabelling() {
+    let edges = [(0, 1, 1), (1, 2, 2), (2, 3, 1), (3, 0, 2), (0, 4, 3)];
+    let relabel = |node: u32| (node * 2 + 1) % 5;
+    let g = UnGraph::<(), i32>::from_edges(edges);
+    let h = UnGraph::<(), i32>::from_edges(
+        edges
+            .iter()
+            .rev()
+            .map(|&(a, b, weight)| (relabel(b), relabel(a), weight)),
+    );
+    let mut g_rows = edge_neighbourhood_hash(g, 4);
+    let mut h_rows = edge_neighbourhood_hash(h, 4);
+    g_rows.sort_unstable();
+    h_rows.sort_unstable();
+    assert_eq!(g_rows, h_rows);
+}
+
+#[test]
+fn directions_count() {
+    // In a directed path both edges meet at the middle node, once entering and once leaving it; in the other graph both leave it
+    let path = DiGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+    let out_star = DiGraph::<(), ()>::from_edges([(1, 0), (1, 2)]);
+    let path_hashes = edge_neighbourhood_hash(path, 2);
+    let star_hashes = edge_neighbourhood_hash(out_star, 2);
+    assert_eq!(star_hashes[0], star_hashes[1]);
+    assert_ne!(path_hashes[0], path_hashes[1]);
+    assert!(edge_neighbourhood_hash(DiGraph::<(), ()>::new(), 3).is_empty());
+}
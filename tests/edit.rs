@@ -0,0 +1,79 @@
+use petgraph::algo::is_isomorphic;
+use petgraph::graph::{NodeIndex, UnGraph};
+use wl_isomorphism::{wl_edit_script, EditOp};
+
+// For a script of edge edits only, g1 without the deleted edges and g2 without the added edges must be the same graph
+fn common_part(g1: &UnGraph<(), ()>, g2: &UnGraph<(), ()>, script: &[EditOp]) -> bool {
+    let mut g1 = g1.clone();
+    let mut g2 = g2.clone();
+    for op in script {
+        let (graph, a, b) = match *op {
+            EditOp::DeleteEdge(a, b) => (&mut g1, a, b),
+            EditOp::AddEdge(a, b) => (&mut g2, a, b),
+            _ => panic!("Unexpected node edit {:?}", op),
+        };
+        let edge = graph
+            .find_edge(NodeIndex::new(a as usize), NodeIndex::new(b as usize))
+            .unwrap();
+        graph.remove_edge(edge);
+    }
+    is_isomorphic(&g1, &g2)
+}
+
+#[test]
+fn one_edge_apart() {
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
+    let cycle = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 0)]);
+    let script = wl_edit_script(path.clone(), cycle.clone());
+    assert!(matches!(script[..], [EditOp::AddEdge(..)]));
+    assert!(common_part(&path, &cycle, &script));
+    let script = wl_edit_script(cycle.clone(), path.clone());
+    assert!(matches!(script[..], [EditOp::DeleteEdge(..)]));
+    assert!(common_part(&cycle, &path, &script));
+
+    // A random graph, and the same graph relabelled and with one more edge
+    let graph = wl_isomorphism::generators::gnp(20, 0.2, 3);
+    let n = graph.node_count();
+    let relabel = |node: NodeIndex| ((node.index() * 7 + 3) % n) as u32;
+    let mut edges: Vec<(u32, u32)> = graph
+        .raw_edges()
+        .iter()
+        .map(|edge| (relabel(edge.source()), relabel(edge.target())))
+        .collect();
+    let missing = (0..n as u32)
+        .flat_map(|a| (a + 1..n as u32).map(move |b| (a, b)))
+        .find(|&(a, b)| !edges.contains(&(a, b)) && !edges.contains(&(b, a)))
+        .unwrap();
+    edges.push(missing);
+    let mut denser = UnGraph::<(), ()>::from_edges(edges);
+    while denser.node_count() < n {
+        denser.add_node(());
+    }
+    let script = wl_edit_script(graph.clone(), denser.clone());
+    assert_eq!(script.len(), 1, "{:?}", script);
+    assert!(common_part(&graph, &denser, &script));
+    let script = wl_edit_script(denser.clone(), graph.clone());
+    assert_eq!(script.len(), 1, "{:?}", script);
+    assert!(common_part(&denser, &graph, &script));
+}
+
+#[test]
+fn isomorphic_and_node_edits() {
+    let g = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+    let h = UnGraph::<(), ()>::from_edges([(3, 1), (1, 2), (2, 3), (3, 0)]);
+    assert!(wl_edit_script(g.clone(), h).is_empty());
+
+    // A pendant node more: the node and its edge are added
+    let mut pendant = g.clone();
+    let extra = pendant.add_node(());
+    pendant.add_edge(NodeIndex::new(0), extra, ());
+    assert_eq!(
+        wl_edit_script(g.clone(), pendant.clone()),
+        [EditOp::AddNode(4), EditOp::AddEdge(0, 4)]
+    );
+    assert_eq!(
+        wl_edit_script(pendant, g),
+        [EditOp::DeleteEdge(0, 4), EditOp::DeleteNode(4)]
+    );
+    assert!(wl_edit_script(UnGraph::<(), ()>::default(), UnGraph::default()).is_empty());
+}
@@ -0,0 +1,54 @@
+use petgraph::graph::{DiGraph, UnGraph};
+use wl_isomorphism::ego_invariants;
+
+#[test]
+fn cycles_and_paths() {
+    let cycle = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+    for radius in 0..4 {
+        let egos = ego_invariants(cycle.clone(), radius);
+        assert!(egos.iter().all(|&ego| ego == egos[0]), "radius {}", radius);
+    }
+
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)]);
+    let egos = ego_invariants(path.clone(), 1);
+    assert_eq!(egos[0], egos[5]);
+    assert_ne!(egos[0], egos[1]);
+    assert!(egos[1..5].iter().all(|&ego| ego == egos[1]));
+    // Beyond the radius a path and a cycle look the same
+    assert_eq!(egos[2], ego_invariants(cycle, 1)[0]);
+    // With the whole path in sight every node sees a different position, up to the mirror image
+    let egos = ego_invariants(path, 5);
+    assert_eq!(egos[0], egos[5]);
+    assert_eq!(egos[1], egos[4]);
+    assert_ne!(egos[0], egos[1]);
+    assert_ne!(egos[1], egos[2]);
+}
+
+#[test]
+fn centre_is_marked() {
+    // Both egos are the whole path, but centred on an end or in the middle
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+    let egos = ego_invariants(path, 2);
+    assert_ne!(egos[0], egos[1]);
+    // Only the subgraph counts: the extra edge between the leaves of a star is seen from the centre, not from a leaf with radius 0
+    let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+    let closed = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3), (1, 2)]);
+    assert_ne!(
+        ego_invariants(star.clone(), 1)[0],
+        ego_invariants(closed.clone(), 1)[0]
+    );
+    assert_eq!(ego_invariants(star, 0)[1], ego_invariants(closed, 0)[1]);
+}
+
+#[test]
+fn directed() {
+    // Along the outgoing edges, the start of a directed path sees the rest, the end sees only itself
+    let path = DiGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+    let egos = ego_invariants(path, 2);
+    assert_ne!(egos[0], egos[2]);
+    assert_eq!(
+        egos[2],
+        ego_invariants(DiGraph::<(), ()>::from_edges([(0, 1)]), 2)[1]
+    );
+    assert!(ego_invariants(DiGraph::<(), ()>::new(), 1).is_empty());
+}
@@ -0,0 +1,172 @@
+use petgraph::graph::UnGraph;
+
+fn cosine(a: &[f64], b: &[f64]) -> f64 {
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f64]| v.iter().map(|x| x * x).sum::<f64>().sqrt();
+    dot / (norm(a) * norm(b))
+}
+
+fn example_edges() -> Vec<(u32, u32)> {
+    vec![
+        (0, 1),
+        (1, 2),
+        (2, 3),
+        (3, 4),
+        (4, 5),
+        (5, 0),
+        (0, 6),
+        (6, 7),
+        (7, 8),
+        (8, 9),
+        (9, 10),
+        (10, 11),
+        (11, 6),
+        (2, 12),
+        (12, 13),
+        (13, 14),
+        (14, 15),
+        (15, 16),
+        (16, 17),
+        (17, 18),
+        (18, 19),
+        (19, 12),
+        (3, 9),
+        (5, 15),
+    ]
+}
+
+#[test]
+fn hashed_features_isomorphic() {
+    let g = UnGraph::<(), ()>::from_edges(example_edges());
+    let reversed: Vec<_> = example_edges()
+        .into_iter()
+        .map(|(a, b)| (19 - b, 19 - a))
+        .collect();
+    let g2 = UnGraph::<(), ()>::from_edges(reversed);
+    assert_eq!(
+        wl_isomorphism::hashed_feature_vector(g.clone(), 3, 32),
+        wl_isomorphism::hashed_feature_vector(g2.clone(), 3, 32)
+    );
+    assert_eq!(
+        wl_isomorphism::signed_hashed_feature_vector(g, 3, 32),
+        wl_isomorphism::signed_hashed_feature_vector(g2, 3, 32)
+    );
+}
+
+#[test]
+fn hashed_features_length() {
+    let small = UnGraph::<(), ()>::from_edges([(0, 1)]);
+    let large = UnGraph::<(), ()>::from_edges(example_edges());
+    assert_eq!(
+        wl_isomorphism::hashed_feature_vector(small, 3, 17).len(),
+        17
+    );
+    assert_eq!(
+        wl_isomorphism::hashed_feature_vector(large, 3, 17).len(),
+        17
+    );
+}
+
+#[test]
+fn hashed_features_perturbed() {
+    let g = UnGraph::<(), ()>::from_edges(example_edges());
+    let mut perturbed = example_edges();
+    perturbed.pop();
+    let g2 = UnGraph::<(), ()>::from_edges(perturbed);
+    let similarity = cosine(
+        &wl_isomorphism::hashed_feature_vector(g, 3, 64),
+        &wl_isomorphism::hashed_feature_vector(g2, 3, 64),
+    );
+    assert!(similarity > 0.8, "similarity was {}", similarity);
+    assert!(similarity < 1.0);
+}
+
+#[test]
+fn distance_matrix() {
+    let corpus = vec![
+        UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]),
+        UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3), (0, 4)]),
+        UnGraph::<(), ()>::from_edges([(1, 0), (2, 1), (2, 3), (4, 3)]),
+        UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]),
+        UnGraph::<(), ()>::from_edges(example_edges()),
+    ];
+    let matrix = wl_isomorphism::wl_distance_matrix(&corpus, 3);
+    assert_eq!(matrix.len(), corpus.len());
+    for i in 0..corpus.len() {
+        assert_eq!(matrix[i][i], 0.0);
+        for j in 0..corpus.len() {
+            assert_eq!(matrix[i][j], matrix[j][i]);
+            for k in 0..corpus.len() {
+                assert!(matrix[i][k] <= matrix[i][j] + matrix[j][k]);
+            }
+        }
+    }
+    // Isomorphic graphs have identical histograms, different ones do not
+    assert_eq!(matrix[0][2], 0.0);
+    assert!(matrix[0][1] > 0.0);
+}
+
+#[test]
+fn neighbourhood_hash_batch() {
+    let g = UnGraph::<u64, ()>::from_edges(example_edges());
+    assert_eq!(
+        wl_isomorphism::neighbourhood_hash_batch(vec![g.clone()], 3)[0],
+        wl_isomorphism::neighbourhood_hash(g, 3)
+    );
+
+    // Enough graphs of different sizes to be split over threads when rayon is enabled, and in the same order as sequential calls
+    let corpus: Vec<UnGraph<u64, ()>> = (2..40)
+        .map(|n| UnGraph::from_edges((1..n).map(|i| (i / 2, i))))
+        .collect();
+    let batch = wl_isomorphism::neighbourhood_hash_batch(corpus.clone(), 4);
+    assert_eq!(batch.len(), corpus.len());
+    for (graph, hashes) in corpus.into_iter().zip(batch) {
+        assert_eq!(hashes, wl_isomorphism::neighbourhood_hash(graph, 4));
+    }
+    assert!(wl_isomorphism::neighbourhood_hash_batch(Vec::<UnGraph<u64, ()>>::new(), 2).is_empty());
+}
+
+#[test]
+fn lsh_near_duplicates() {
+    let graph = wl_isomorphism::generators::gnp(200, 0.02, 0);
+    let hashes = wl_isomorphism::invariant_lsh(graph.clone(), 128, 0);
+    // Reversing the node order gives an isomorphic graph
+    let n = graph.node_count() as u32;
+    let reversed = UnGraph::<(), ()>::from_edges(graph.edge_indices().map(|edge| {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+        (n - 1 - a.index() as u32, n - 1 - b.index() as u32)
+    }));
+    let reversed = wl_isomorphism::invariant_lsh(reversed, 128, 0);
+    assert_eq!(wl_isomorphism::lsh_similarity(&hashes, &reversed), 1.0);
+
+    // Removing one edge keeps most of the features
+    let mut edited = graph.clone();
+    edited.remove_edge(edited.edge_indices().next().unwrap());
+    let edited = wl_isomorphism::invariant_lsh(edited, 128, 0);
+    assert!(wl_isomorphism::lsh_similarity(&hashes, &edited) > 0.5);
+
+    // Another random graph shares almost nothing, and a star and a cycle share nothing at all
+    let other =
+        wl_isomorphism::invariant_lsh(wl_isomorphism::generators::gnp(200, 0.02, 1), 128, 0);
+    assert!(wl_isomorphism::lsh_similarity(&hashes, &other) < 0.2);
+    let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3), (0, 4)]);
+    let cycle = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 0)]);
+    assert_eq!(
+        wl_isomorphism::lsh_similarity(
+            &wl_isomorphism::invariant_lsh(star, 128, 0),
+            &wl_isomorphism::invariant_lsh(cycle, 128, 0)
+        ),
+        0.0
+    );
+}
+
+#[test]
+fn lsh_empty_graph() {
+    let empty = wl_isomorphism::invariant_lsh(UnGraph::<(), ()>::default(), 16, 0);
+    assert_eq!(empty, vec![u64::MAX; 16]);
+    let single = UnGraph::<(), ()>::from_edges([(0, 0)]);
+    assert_eq!(
+        wl_isomorphism::lsh_similarity(&empty, &wl_isomorphism::invariant_lsh(single, 16, 0)),
+        0.0
+    );
+}
@@ -0,0 +1,15 @@
+﻿# Triangle with a tail
+  graph [
+  directed 0
+  node [
+    id 10
+    label "a b"
+  ]
+  node [ id 11 label "c" ]
+  node [ id 12 ]
+  node [ id 13 graphics [ x 1.0 y 2.0 ] ]
+  edge [ source 10 target 11 ]
+  edge [ source 11 target 12 ]
+  edge [ source 12 target 10 ]
+  edge [ source 12 target 13 weight 2.5 ]
+]
@@ -0,0 +1,250 @@
+use petgraph::graph::UnGraph;
+
+#[test]
+fn detects_every_format() {
+    let expected = wl_isomorphism::invariant(UnGraph::<(), ()>::from_edges([
+        (0, 1),
+        (1, 2),
+        (2, 0),
+        (2, 3),
+    ]));
+    for extension in ["gml", "dimacs", "graphml", "edgelist"] {
+        let path = format!("tests/fixtures/tailed_triangle.{}", extension);
+        let graph = wl_isomorphism::graph_from_file(&path).unwrap();
+        assert_eq!(graph.node_count(), 4, "{}", path);
+        assert_eq!(graph.edge_count(), 4, "{}", path);
+        assert_eq!(wl_isomorphism::invariant(graph), expected, "{}", path);
+    }
+}
+
+#[test]
+fn missing_file() {
+    assert!(matches!(
+        wl_isomorphism::graph_from_file("tests/fixtures/missing.gml"),
+        Err(wl_isomorphism::WlError::Io(_))
+    ));
+}
+
+#[test]
+fn adjacency_matrix() {
+    // The upper triangle is a star around node 1
+    let graph =
+        wl_isomorphism::ungraph_from_adjacency_matrix("tests/fixtures/asymmetric.matrix").unwrap();
+    let mut edges: Vec<_> = graph
+        .raw_edges()
+        .iter()
+        .map(|e| (e.source().index(), e.target().index()))
+        .collect();
+    edges.sort();
+    assert_eq!(graph.node_count(), 4);
+    assert_eq!(edges, [(0, 1), (1, 2), (1, 3)]);
+
+    // All off-diagonal entries, here separated by commas
+    let graph =
+        wl_isomorphism::digraph_from_adjacency_matrix("tests/fixtures/asymmetric.csv").unwrap();
+    let mut edges: Vec<_> = graph
+        .raw_edges()
+        .iter()
+        .map(|e| (e.source().index(), e.target().index()))
+        .collect();
+    edges.sort();
+    assert_eq!(edges, [(0, 1), (1, 2), (1, 3), (2, 1), (3, 0), (3, 2)]);
+}
+
+#[test]
+fn adjacency_matrix_not_square() {
+    assert!(matches!(
+        wl_isomorphism::ungraph_from_adjacency_matrix("tests/fixtures/tailed_triangle.edgelist"),
+        Err(wl_isomorphism::WlError::Parse { .. })
+    ));
+}
+
+#[test]
+fn pajek() {
+    let graph = wl_isomorphism::ungraph_from_pajek("tests/fixtures/weighted.net").unwrap();
+    assert_eq!(graph.node_count(), 5);
+    assert_eq!(graph.edge_count(), 5);
+    assert_eq!(
+        graph.node_weights().copied().collect::<Vec<_>>(),
+        [0.1, 0.9, 0.9, 0.1, 0.0]
+    );
+    assert_eq!(graph.edge_weights().sum::<f64>(), 6.5);
+
+    // Undirected edges become arcs both ways
+    let digraph = wl_isomorphism::digraph_from_pajek("tests/fixtures/weighted.net").unwrap();
+    assert_eq!(digraph.node_count(), 5);
+    assert_eq!(digraph.edge_count(), 10);
+
+    // The square with one diagonal
+    let structure = graph.map(|_, _| (), |_, _| ());
+    let mut expected = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 0), (0, 2)]);
+    expected.add_node(());
+    assert_eq!(
+        wl_isomorphism::invariant(structure),
+        wl_isomorphism::invariant(expected)
+    );
+}
+
+#[test]
+fn pajek_labels() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("labels.net");
+    let text = [
+        "*Vertices 6",
+        // Quoted labels that look like numbers are labels, not coordinates
+        "1 \"2\" 0.25 0.5",
+        "2 \"3.5\"",
+        "3 \"with spaces\" 0.75 0.5 0.5 ic Red",
+        // Unquoted labels, and a vertex without one
+        "4 four 0.125 0.5",
+        "5 0.375 0.5",
+        "6 \"\" 1",
+        "*Edges",
+        "1 2",
+    ]
+    .join("\n");
+    std::fs::write(&path, text).unwrap();
+    let graph = wl_isomorphism::ungraph_from_pajek(&path).unwrap();
+    assert_eq!(
+        graph.node_weights().copied().collect::<Vec<_>>(),
+        [0.25, 0.0, 0.75, 0.125, 0.375, 1.0]
+    );
+
+    std::fs::write(&path, "*Vertices 1\n1 \"open 0.5\n").unwrap();
+    assert!(matches!(
+        wl_isomorphism::ungraph_from_pajek(&path),
+        Err(wl_isomorphism::WlError::Parse { line: 2, .. })
+    ));
+}
+
+#[test]
+fn edgelist_extra_columns() {
+    let graph = wl_isomorphism::ungraph_from_edgelist("tests/fixtures/weighted.edgelist").unwrap();
+    assert_eq!(graph.node_count(), 4);
+    assert_eq!(graph.edge_count(), 4);
+}
+
+#[test]
+fn edgelist_comments_and_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("commented.edgelist");
+    std::fs::write(
+        &path,
+        "# written by hand
+0 1
+
+  # indented
+1 2
+2 0
+",
+    )
+    .unwrap();
+    let graph = wl_isomorphism::ungraph_from_edgelist(&path).unwrap();
+    assert_eq!((graph.node_count(), graph.edge_count()), (3, 3));
+    let graph = wl_isomorphism::digraph_from_edgelist(&path).unwrap();
+    assert_eq!((graph.node_count(), graph.edge_count()), (3, 3));
+    assert!(graph.is_directed());
+
+    for (text, expected) in [("0 1\n1\n", 2), ("# header\n0 1\n1 x\n", 3)] {
+        std::fs::write(&path, text).unwrap();
+        match wl_isomorphism::ungraph_from_edgelist(&path) {
+            Err(wl_isomorphism::WlError::Parse { line, .. }) => assert_eq!(line, expected),
+            other => panic!("{:?}", other),
+        }
+        assert!(matches!(
+            wl_isomorphism::digraph_from_edgelist(&path),
+            Err(wl_isomorphism::WlError::Parse { .. })
+        ));
+    }
+    assert!(matches!(
+        wl_isomorphism::ungraph_from_edgelist(dir.path().join("missing.edgelist")),
+        Err(wl_isomorphism::WlError::Io(_))
+    ));
+}
+
+#[test]
+fn weighted_edgelist_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("weighted.edgelist");
+    let graph =
+        UnGraph::<(), f64>::from_edges([(0, 1, 1.5), (1, 2, -2.0), (2, 0, 0.25), (2, 3, 1e-3)]);
+    wl_isomorphism::write_edgelist_weighted(&graph, &path).unwrap();
+    assert!(std::fs::read_to_string(&path)
+        .unwrap()
+        .starts_with("0 1 1.5\n"));
+
+    let read = wl_isomorphism::weighted_ungraph_from_edgelist(&path).unwrap();
+    let weights = |graph: &UnGraph<(), f64>| graph.edge_weights().copied().collect::<Vec<_>>();
+    assert_eq!(weights(&read), weights(&graph));
+    let strip = |graph: UnGraph<(), f64>| graph.map(|_, _| (), |_, _| ());
+    assert_eq!(
+        wl_isomorphism::invariant(strip(read)),
+        wl_isomorphism::invariant(strip(graph))
+    );
+}
+
+#[test]
+fn weighted_edgelist_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bad.edgelist");
+    std::fs::write(&path, "# comment\n0 1\n\n1 2 heavy\n").unwrap();
+    match wl_isomorphism::weighted_ungraph_from_edgelist(&path) {
+        Err(wl_isomorphism::WlError::Parse { line, .. }) => assert_eq!(line, 4),
+        other => panic!("{:?}", other),
+    }
+    std::fs::write(&path, "0 1\n").unwrap();
+    let graph = wl_isomorphism::weighted_ungraph_from_edgelist(&path).unwrap();
+    assert_eq!(graph.edge_weights().collect::<Vec<_>>(), [&1.0]);
+}
+
+#[test]
+fn node_limit() {
+    use wl_isomorphism::{LimitKind, WlError};
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("huge");
+    type NodeCount = fn(&std::path::Path) -> Result<usize, WlError>;
+    let files: [(&str, NodeCount); 5] = [
+        ("p edge 4000000000 1\ne 1 2\n", |path| {
+            wl_isomorphism::graph_from_file(path).map(|g| g.node_count())
+        }),
+        ("*Vertices 4000000000\n", |path| {
+            wl_isomorphism::ungraph_from_pajek(path).map(|g| g.node_count())
+        }),
+        ("0 1\n1 4294967295\n", |path| {
+            wl_isomorphism::ungraph_from_edgelist(path).map(|g| g.node_count())
+        }),
+        ("0 1\n1 4294967295\n", |path| {
+            wl_isomorphism::digraph_from_edgelist(path).map(|g| g.node_count())
+        }),
+        ("0 1 0.5\n1 4294967295 0.5\n", |path| {
+            wl_isomorphism::weighted_ungraph_from_edgelist(path).map(|g| g.node_count())
+        }),
+    ];
+    for (text, read) in files {
+        std::fs::write(&path, text).unwrap();
+        match read(&path) {
+            Err(WlError::LimitExceeded {
+                which: LimitKind::Nodes,
+                limit,
+                observed,
+                ..
+            }) => {
+                assert_eq!(limit, 1 << 20, "{}", text);
+                assert!(observed >= 4_000_000_000, "{}", text);
+            }
+            other => panic!("{}: {:?}", text, other),
+        }
+    }
+
+    // Small files may still declare up to 2^20 nodes, and the edge count of a header is not trusted for allocation
+    std::fs::write(&path, "p edge 100000 99999999999999\ne 1 100000\n").unwrap();
+    let graph = wl_isomorphism::graph_from_file(&path).unwrap();
+    assert_eq!((graph.node_count(), graph.edge_count()), (100_000, 1));
+    std::fs::write(&path, "0 1048575\n").unwrap();
+    assert_eq!(
+        wl_isomorphism::ungraph_from_edgelist(&path)
+            .unwrap()
+            .node_count(),
+        1 << 20
+    );
+}
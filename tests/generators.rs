@@ -0,0 +1,192 @@
+use petgraph::graph::{DiGraph, UnGraph};
+use petgraph::visit::EdgeRef;
+use petgraph::{Direction, EdgeType, Graph};
+use wl_isomorphism::generators;
+
+fn example() -> UnGraph<(), ()> {
+    UnGraph::from_edges([
+        (0, 1),
+        (1, 2),
+        (2, 3),
+        (3, 4),
+        (4, 5),
+        (5, 0),
+        (0, 6),
+        (6, 7),
+        (7, 8),
+        (8, 9),
+        (9, 10),
+        (10, 11),
+        (11, 6),
+        (2, 12),
+        (12, 13),
+        (13, 14),
+        (3, 9),
+        (5, 14),
+    ])
+}
+
+fn degrees<Ty: EdgeType>(graph: &Graph<(), (), Ty>, direction: Direction) -> Vec<usize> {
+    graph
+        .node_indices()
+        .map(|n| graph.neighbors_directed(n, direction).count())
+        .collect()
+}
+
+fn edge_list<Ty: EdgeType>(graph: &Graph<(), (), Ty>) -> Vec<(usize, usize)> {
+    graph
+        .edge_references()
+        .map(|e| (e.source().index(), e.target().index()))
+        .collect()
+}
+
+#[test]
+fn rewiring_keeps_degrees() {
+    let g = example();
+    let rewired = generators::rewire_edges(g.clone(), 20, 7);
+    assert_eq!(
+        degrees(&g, Direction::Outgoing),
+        degrees(&rewired, Direction::Outgoing)
+    );
+    assert_ne!(edge_list(&g), edge_list(&rewired));
+
+    let d =
+        DiGraph::<(), ()>::from_edges(edge_list(&g).into_iter().map(|(a, b)| (a as u32, b as u32)));
+    let rewired = generators::rewire_edges(d.clone(), 20, 7);
+    for direction in [Direction::Outgoing, Direction::Incoming] {
+        assert_eq!(degrees(&d, direction), degrees(&rewired, direction));
+    }
+}
+
+#[test]
+fn rewiring_changes_invariant() {
+    let g = example();
+    let original = wl_isomorphism::invariant(g.clone());
+    let changed = (0..10)
+        .filter(|&seed| {
+            wl_isomorphism::invariant(generators::rewire_edges(g.clone(), 1, seed)) != original
+        })
+        .count();
+    assert!(changed > 5);
+}
+
+#[test]
+fn rewiring_keeps_graph_simple() {
+    let rewired = generators::rewire_edges(example(), 50, 3);
+    let mut edges: Vec<_> = edge_list(&rewired)
+        .into_iter()
+        .map(|(a, b)| (a.min(b), a.max(b)))
+        .collect();
+    assert!(edges.iter().all(|(a, b)| a != b));
+    edges.sort_unstable();
+    edges.dedup();
+    assert_eq!(edges.len(), rewired.edge_count());
+}
+
+#[test]
+fn zero_swaps() {
+    let g = example();
+    assert_eq!(
+        edge_list(&g),
+        edge_list(&generators::rewire_edges(g.clone(), 0, 1))
+    );
+    assert_eq!(
+        edge_list(&g),
+        edge_list(&generators::drop_edges(g.clone(), 0.0, 1))
+    );
+}
+
+#[test]
+fn dropping_edges() {
+    let g = example();
+    let dropped = generators::drop_edges(g.clone(), 0.5, 11);
+    assert_eq!(dropped.node_count(), g.node_count());
+    assert_eq!(dropped.edge_count(), 9);
+    assert_eq!(generators::drop_edges(g, 1.0, 11).edge_count(), 0);
+}
+
+#[test]
+fn erdos_renyi() {
+    let g = generators::gnp(30, 0.2, 5);
+    assert_eq!(g.node_count(), 30);
+    assert!(g.edge_count() > 40 && g.edge_count() < 130);
+    assert_eq!(generators::gnp(30, 0.0, 5).edge_count(), 0);
+    assert_eq!(generators::gnp(30, 1.0, 5).edge_count(), 30 * 29 / 2);
+    assert_eq!(edge_list(&g), edge_list(&generators::gnp(30, 0.2, 5)));
+    assert_ne!(edge_list(&g), edge_list(&generators::gnp(30, 0.2, 6)));
+}
+
+#[test]
+fn preferential_attachment() {
+    let g = generators::barabasi_albert(50, 3, 1);
+    assert_eq!(g.node_count(), 50);
+    assert_eq!(g.edge_count(), 3 * 47);
+    assert!(degrees(&g, Direction::Outgoing)[3..]
+        .iter()
+        .all(|&d| d >= 3));
+    assert_eq!(
+        edge_list(&g),
+        edge_list(&generators::barabasi_albert(50, 3, 1))
+    );
+}
+
+#[test]
+fn regular() {
+    for (n, d) in [(10, 3), (16, 4), (21, 6), (8, 7)] {
+        let g = generators::random_regular(n, d, 3);
+        assert_eq!(g.node_count(), n);
+        assert_eq!(g.edge_count(), n * d / 2);
+        assert!(degrees(&g, Direction::Outgoing)
+            .iter()
+            .all(|&degree| degree == d));
+        assert_eq!(
+            edge_list(&g),
+            edge_list(&generators::random_regular(n, d, 3))
+        );
+    }
+    // 1-WL cannot tell regular graphs apart, 2-WL usually can
+    let g1 = generators::random_regular(16, 3, 1);
+    let g2 = generators::random_regular(16, 3, 2);
+    assert_eq!(
+        wl_isomorphism::invariant(g1.clone()),
+        wl_isomorphism::invariant(g2.clone())
+    );
+    assert_ne!(
+        wl_isomorphism::invariant_2wl(g1),
+        wl_isomorphism::invariant_2wl(g2)
+    );
+}
+
+#[test]
+fn colour_structure() {
+    // Every node of a class has the same degree into every class: a pair of centres, a 4-cycle joined to one centre each and a
+    // triangle joined to everything
+    let sizes = [2, 4, 3];
+    let graph = generators::graph_from_color_structure(
+        &sizes,
+        &[(0, 1, 4), (1, 1, 4), (1, 2, 12), (2, 2, 3), (2, 0, 6)],
+    );
+    assert_eq!(graph.node_count(), 9);
+    assert_eq!(graph.edge_count(), 29);
+    let classes = wl_isomorphism::colour_classes(graph.clone());
+    let mut class_sizes = vec![0; sizes.len()];
+    for (node, &class) in classes.iter().enumerate() {
+        // Nodes share a stable colour exactly when they were placed in the same class
+        let placed = graph[petgraph::graph::NodeIndex::new(node)];
+        assert!(classes
+            .iter()
+            .zip(graph.node_weights())
+            .all(|(&other, &other_placed)| (other == class) == (other_placed == placed)));
+        class_sizes[placed as usize] += 1;
+    }
+    assert_eq!(class_sizes, sizes);
+    // No self-loops or parallel edges
+    let mut simple: Vec<_> = edge_list(&graph.map(|_, _| (), |_, _| ()))
+        .into_iter()
+        .map(|(a, b)| (a.min(b), a.max(b)))
+        .collect();
+    simple.sort_unstable();
+    simple.dedup();
+    assert_eq!(simple.len(), graph.edge_count());
+    assert!(simple.iter().all(|&(a, b)| a != b));
+}
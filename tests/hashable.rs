@@ -0,0 +1,43 @@
+use petgraph::graph::UnGraph;
+use std::collections::HashSet;
+use wl_isomorphism::WlHashable;
+
+#[test]
+fn deduplicate() {
+    let graphs = [
+        UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]),
+        UnGraph::<(), ()>::from_edges([(3, 1), (1, 2), (2, 3), (3, 0)]),
+        UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]),
+        UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]),
+        UnGraph::<(), ()>::from_edges([(3, 2), (2, 1), (1, 0)]),
+    ];
+    let set: HashSet<_> = graphs.into_iter().map(WlHashable).collect();
+    assert_eq!(set.len(), 3);
+}
+
+#[test]
+fn wl_fooling_pair_is_merged() {
+    // A hexagon and two triangles are both 2-regular on 6 nodes, so 1-WL cannot tell them apart, and the set keeps only one of these non-isomorphic graphs
+    let hexagon = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+    let triangles = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+    assert!(!petgraph::algo::is_isomorphic(&hexagon, &triangles));
+    let set: HashSet<_> = [WlHashable(hexagon), WlHashable(triangles)]
+        .into_iter()
+        .collect();
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn empty_graph() {
+    let mut single = UnGraph::<(), ()>::default();
+    single.add_node(());
+    let set: HashSet<_> = [
+        WlHashable(UnGraph::<(), ()>::default()),
+        WlHashable(single),
+        WlHashable(UnGraph::<(), ()>::default()),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&WlHashable(UnGraph::default())));
+}
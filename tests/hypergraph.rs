@@ -0,0 +1,68 @@
+use wl_isomorphism::{invariant_hypergraph, Hypergraph};
+
+#[test]
+fn two_hyperedges() {
+    // A 3-edge and a 4-edge sharing node 2
+    let hg = Hypergraph {
+        n: 6,
+        hyperedges: vec![vec![0, 1, 2], vec![2, 3, 4, 5]],
+    };
+    // The same, relabelled and with the hyperedges and members in another order
+    let relabelled = Hypergraph {
+        n: 6,
+        hyperedges: vec![vec![5, 0, 1, 3], vec![4, 2, 5]],
+    };
+    assert_eq!(
+        invariant_hypergraph(hg.clone()),
+        invariant_hypergraph(relabelled)
+    );
+
+    // Disjoint hyperedges, and the shared node in another place, are not isomorphic
+    let disjoint = Hypergraph {
+        n: 7,
+        hyperedges: vec![vec![0, 1, 2], vec![3, 4, 5, 6]],
+    };
+    assert_ne!(
+        invariant_hypergraph(hg.clone()),
+        invariant_hypergraph(disjoint)
+    );
+    let isolated = Hypergraph {
+        n: 7,
+        hyperedges: hg.hyperedges.clone(),
+    };
+    assert_ne!(
+        invariant_hypergraph(hg.clone()),
+        invariant_hypergraph(isolated)
+    );
+    // The 4-edge listed first is still the same hypergraph
+    let swapped = Hypergraph {
+        n: 6,
+        hyperedges: vec![vec![0, 1, 2, 3], vec![3, 4, 5]],
+    };
+    assert_eq!(invariant_hypergraph(hg), invariant_hypergraph(swapped));
+}
+
+#[test]
+fn hyperedges_of_two() {
+    // With only 2-edges, the hypergraph is a graph, and the 6-cycle differs from a path with a triangle
+    let cycle = Hypergraph {
+        n: 6,
+        hyperedges: (0..6).map(|node| vec![node, (node + 1) % 6]).collect(),
+    };
+    let other = Hypergraph {
+        n: 6,
+        hyperedges: vec![
+            vec![0, 1],
+            vec![1, 2],
+            vec![2, 0],
+            vec![2, 3],
+            vec![3, 4],
+            vec![4, 5],
+        ],
+    };
+    assert_ne!(invariant_hypergraph(cycle), invariant_hypergraph(other));
+    assert_eq!(
+        invariant_hypergraph(Hypergraph::default()),
+        invariant_hypergraph(Hypergraph::default())
+    );
+}
@@ -0,0 +1,49 @@
+use petgraph::graph::UnGraph;
+use twox_hash::XxHash64;
+use wl_isomorphism::InvariantIndex;
+
+fn random_u64(stream: u64, i: u64) -> u64 {
+    XxHash64::oneshot(stream, &i.to_ne_bytes())
+}
+
+#[test]
+fn false_positive_rate() {
+    let mut index = InvariantIndex::new(100_000, 0.01);
+    for i in 0..100_000 {
+        index.insert(random_u64(1, i));
+    }
+    assert_eq!(index.len(), 100_000);
+    // No false negatives
+    assert!((0..100_000).all(|i| index.contains(random_u64(1, i))));
+    let false_positives = (0..100_000)
+        .filter(|&i| index.contains(random_u64(2, i)))
+        .count();
+    let rate = false_positives as f64 / 100_000.0;
+    assert!((0.005..0.015).contains(&rate), "rate {}", rate);
+}
+
+#[test]
+fn graphs_with_seed() {
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+    let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+    let mut index = InvariantIndex::with_seed(100, 0.001, 7);
+    assert!(index.is_empty());
+    index.insert_graph(path.clone());
+    assert!(index.contains_graph(path.clone()));
+    assert!(!index.contains_graph(star));
+    // The stored hash was computed with seed 7, not with the default seed
+    assert!(!index.contains(wl_isomorphism::invariant(path)));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn persistence() {
+    let mut index = InvariantIndex::new(1000, 0.01);
+    for i in 0..1000 {
+        index.insert(random_u64(3, i));
+    }
+    let json = serde_json::to_string(&index).unwrap();
+    let restored: InvariantIndex = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, index);
+    assert!((0..1000).all(|i| restored.contains(random_u64(3, i))));
+}
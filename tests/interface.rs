@@ -1,4 +1,6 @@
-use petgraph::graph::UnGraph;
+use petgraph::graph::{DiGraph, Graph, UnGraph};
+use petgraph::Undirected;
+use std::collections::HashSet;
 
 #[test]
 fn equal() {
@@ -50,14 +52,950 @@ fn equal_versions() {
 }
 
 #[test]
-#[ignore]
 fn write_dot() {
+    // The output directory does not exist yet
+    let dir = tempfile::tempdir().unwrap();
+    let outputs = dir.path().join("outputs");
     let g = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
-    let a = wl_isomorphism::invariant_dot(g.clone(), "outputs/stable_dot");
-    let b = wl_isomorphism::iter_dot(g.clone(), 2, "outputs/iters.dot");
-    let c = wl_isomorphism::iter_dot(g.clone(), 3, "outputs/iters_longer.dot");
+    let a = wl_isomorphism::invariant_dot(g.clone(), outputs.join("stable_dot")).unwrap();
+    let b = wl_isomorphism::iter_dot(g.clone(), 2, outputs.join("iters.dot")).unwrap();
+    let c = wl_isomorphism::iter_dot(g.clone(), 3, outputs.join("iters_longer.dot")).unwrap();
+    assert!(std::fs::read_to_string(outputs.join("stable_dot"))
+        .unwrap()
+        .starts_with("graph {"));
     let canon = wl_isomorphism::invariant(g);
     assert_eq!(a, b);
     assert_ne!(b, c);
     assert_eq!(a, canon);
 }
+
+#[test]
+fn dot_edge_colours() {
+    let dir = tempfile::tempdir().unwrap();
+    let edge_colours = |graph: DiGraph<(), ()>, name: &str| {
+        let path = dir.path().join(name);
+        let options = wl_isomorphism::DotOptions {
+            colour_edges: true,
+            ..Default::default()
+        };
+        wl_isomorphism::invariant_dot_with_options(graph, &path, options).unwrap();
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .filter(|line| line.contains("->"))
+            .map(|line| line.split('"').nth(1).unwrap().to_string())
+            .collect::<HashSet<_>>()
+    };
+    let star_in = edge_colours(DiGraph::from_edges([(1, 0), (2, 0), (3, 0)]), "in.dot");
+    let star_out = edge_colours(DiGraph::from_edges([(0, 1), (0, 2), (0, 3)]), "out.dot");
+    assert_eq!((star_in.len(), star_out.len()), (1, 1));
+    assert!(star_in.is_disjoint(&star_out));
+
+    // Without the option, edges have no attributes
+    let path = dir.path().join("plain.dot");
+    wl_isomorphism::invariant_dot(DiGraph::<(), ()>::from_edges([(0, 1)]), &path).unwrap();
+    assert!(!std::fs::read_to_string(path).unwrap().contains("color ="));
+}
+
+#[test]
+fn dot_canonical_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let write = |edges: &[(u32, u32)], name: &str| {
+        let path = dir.path().join(name);
+        let options = wl_isomorphism::DotOptions {
+            colour_edges: true,
+            ..Default::default()
+        };
+        wl_isomorphism::invariant_dot_with_options(
+            UnGraph::<(), ()>::from_edges(edges),
+            &path,
+            options,
+        )
+        .unwrap();
+        std::fs::read(path).unwrap()
+    };
+    // The same graph, with the edges added in another order and with their endpoints swapped
+    let first = write(&[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4)], "first.dot");
+    let second = write(&[(4, 3), (0, 2), (3, 2), (1, 0), (2, 1)], "second.dot");
+    assert_eq!(first, second);
+}
+
+#[test]
+fn dot_clusters() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("clusters.dot");
+    // A tailed triangle with atom symbols, so that the weights do not give the classes away
+    let atoms = ["C", "C", "N", "O\"H"];
+    let graph = UnGraph::<&str, ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)])
+        .map(|node, _| atoms[node.index()], |_, _| ());
+    let options = wl_isomorphism::DotOptions {
+        cluster_by_class: true,
+        ..Default::default()
+    };
+    wl_isomorphism::invariant_dot_weighted(graph.clone(), &path, options).unwrap();
+    let text = std::fs::read_to_string(path).unwrap();
+    assert!(text.contains("label = \"O\\\"H\""));
+    assert!(text.contains("fillcolor"));
+
+    // Read the cluster blocks back as a class per node
+    let mut classes = vec![usize::MAX; graph.node_count()];
+    let mut cluster = None;
+    for line in text.lines().map(str::trim) {
+        if let Some(k) = line.strip_prefix("subgraph cluster_") {
+            cluster = Some(k.trim_end_matches(" {").parse().unwrap());
+        } else if line == "}" {
+            cluster = None;
+        } else if let Some(k) = cluster {
+            classes[line.trim_end_matches(';').parse::<usize>().unwrap()] = k;
+        }
+    }
+    assert_eq!(classes, wl_isomorphism::colour_classes(graph));
+}
+
+#[test]
+fn early_exit_agrees() {
+    let g = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
+    let g2 = UnGraph::<u64, ()>::from_edges([(1, 0), (2, 1), (2, 3), (4, 3)]);
+    let g3 = UnGraph::<u64, ()>::from_edges([(0, 1), (0, 2), (0, 3), (0, 4)]);
+    let two_cycles =
+        UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+    let big_cycle =
+        UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+    for (a, b) in [(g.clone(), g2), (g, g3), (two_cycles, big_cycle)] {
+        assert_eq!(
+            wl_isomorphism::possibly_isomorphic_early_exit(a.clone(), b.clone()),
+            wl_isomorphism::invariant(a) == wl_isomorphism::invariant(b)
+        );
+    }
+}
+
+#[test]
+fn prefix_match() {
+    // A path of 12 nodes with a leaf attached three nodes from one end, and four nodes from it: the nodes around the branch look
+    // alike until the end of the path is in sight
+    let near =
+        UnGraph::<u64, ()>::from_edges((0..11).map(|node| (node, node + 1)).chain([(3, 12)]));
+    let far = UnGraph::<u64, ()>::from_edges((0..11).map(|node| (node, node + 1)).chain([(4, 12)]));
+    let pairs = [
+        (
+            UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]),
+            UnGraph::<u64, ()>::from_edges([(1, 0), (2, 1), (2, 3), (4, 3)]),
+        ),
+        (
+            UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]),
+            UnGraph::<u64, ()>::from_edges([(0, 1), (0, 2), (0, 3), (0, 4)]),
+        ),
+        (
+            UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (1, 5)]),
+            UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (2, 5)]),
+        ),
+        (near, far),
+    ];
+    let expected = [3, 0, 1, 2];
+    for ((g, h), expected) in pairs.into_iter().zip(expected) {
+        let matching = wl_isomorphism::prefix_match_iters(g.clone(), h.clone());
+        assert_eq!(matching, expected);
+        for k in 1..=matching.min(g.node_count()) {
+            assert_eq!(
+                wl_isomorphism::invariant_iters(g.clone(), k),
+                wl_isomorphism::invariant_iters(h.clone(), k)
+            );
+        }
+        if wl_isomorphism::invariant(g.clone()) != wl_isomorphism::invariant(h.clone()) {
+            assert_ne!(
+                wl_isomorphism::invariant_iters(g, matching + 1),
+                wl_isomorphism::invariant_iters(h, matching + 1)
+            );
+        }
+    }
+    // A graph agrees with itself until it is stable: a path of 10 nodes gets one more class in each of 4 labellings, and a fifth shows
+    // that it is stable
+    let path = UnGraph::<u64, ()>::from_edges((0..9).map(|node| (node, node + 1)));
+    assert_eq!(wl_isomorphism::prefix_match_iters(path.clone(), path), 5);
+}
+
+#[test]
+fn consensus_seed_order() {
+    let g = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
+    let g2 = UnGraph::<u64, ()>::from_edges([(1, 0), (2, 1), (2, 3), (4, 3)]);
+    let a = wl_isomorphism::invariant_consensus(g.clone(), &[1, 2, 3]);
+    let b = wl_isomorphism::invariant_consensus(g2.clone(), &[3, 1, 2]);
+    let c = wl_isomorphism::invariant_consensus(g.clone(), &[2, 3, 1]);
+    assert_eq!(a, b);
+    assert_eq!(a, c);
+    assert_eq!(
+        wl_isomorphism::invariant_consensus(g.clone(), &[]),
+        wl_isomorphism::invariant_consensus(g2, &wl_isomorphism::DEFAULT_CONSENSUS_SEEDS)
+    );
+    assert_ne!(a, wl_isomorphism::invariant_consensus(g, &[1, 2]));
+}
+
+#[test]
+fn arbitrary_node_weights() {
+    let mut g = UnGraph::<String, ()>::new_undirected();
+    let a = g.add_node("a".to_string());
+    let b = g.add_node("b".to_string());
+    let c = g.add_node("c".to_string());
+    g.extend_with_edges([(a, b), (b, c)]);
+    let path = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2)]);
+    assert_eq!(
+        wl_isomorphism::neighbourhood_hash(g.clone(), 2),
+        wl_isomorphism::neighbourhood_hash(path, 2)
+    );
+    // Only checks that `iter_dot` accepts these weights, the path doesn't matter as it is never written
+    let _ = |g: UnGraph<String, ()>| wl_isomorphism::iter_dot(g, 2, "outputs/unused.dot");
+}
+
+// A pseudo-random graph on 5 to 24 nodes, with each edge present with probability 1/4
+fn random_graph(id: u64) -> UnGraph<(), ()> {
+    let n = 5 + (twox_hash::XxHash64::oneshot(id, b"size") % 20) as u32;
+    let mut edges = Vec::new();
+    for i in 0..n {
+        for j in 0..i {
+            if twox_hash::XxHash64::oneshot(id, bytemuck::cast_slice(&[i, j])).is_multiple_of(4) {
+                edges.push((i, j));
+            }
+        }
+    }
+    let mut graph = UnGraph::from_edges(edges);
+    while graph.node_count() < n as usize {
+        graph.add_node(());
+    }
+    graph
+}
+
+#[test]
+fn partitions_agree_across_seeds() {
+    for id in 0..100 {
+        assert_eq!(
+            wl_isomorphism::partition_agreement(random_graph(id), &[42, 137, 0xDEAD_BEEF]),
+            Ok(())
+        );
+    }
+}
+
+#[test]
+fn motif_lower_bound() {
+    use wl_isomorphism::wl_motif_lower_bound;
+    let k3 = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0)]);
+    let k4 = UnGraph::<u64, ()>::from_edges([(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+    let two_triangles =
+        UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+    let hexagon = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+    // In a clique every colour-consistent injection is an embedding
+    assert_eq!(wl_motif_lower_bound(k3.clone(), k3.clone()), 6);
+    assert_eq!(wl_motif_lower_bound(k4.clone(), k4.clone()), 24);
+    // All six nodes have the colour of the triangle nodes, but only 12 of the 6 * 5 * 4 injections map the triangle onto a triangle
+    assert_eq!(wl_motif_lower_bound(two_triangles.clone(), k3.clone()), 12);
+    // The hexagon has the same colours, but no triangle
+    assert_eq!(wl_motif_lower_bound(hexagon.clone(), k3.clone()), 0);
+    // Degrees differ, so no triangle node matches a node of K4, even though K4 contains 24 embeddings of a triangle
+    assert_eq!(wl_motif_lower_bound(k4, k3.clone()), 0);
+
+    // The bound never exceeds the number of embeddings, checked by brute force, and is not trivial
+    let path = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)]);
+    let six_nodes = [two_triangles, hexagon.clone(), path.clone()];
+    for (i, host) in six_nodes.iter().enumerate() {
+        for (j, motif) in six_nodes.iter().enumerate() {
+            let bound = wl_motif_lower_bound(host.clone(), motif.clone());
+            assert!(bound <= embeddings(host, motif));
+            if i == j {
+                assert!(bound > 0);
+            }
+        }
+    }
+    // A path of six nodes goes around the hexagon in 12 ways, but its ends have another colour than any hexagon node
+    assert_eq!(embeddings(&hexagon, &path), 12);
+    assert_eq!(wl_motif_lower_bound(hexagon, path), 0);
+}
+
+// The number of injective maps from the nodes of `motif` to those of `host` that send edges to edges
+fn embeddings(host: &UnGraph<u64, ()>, motif: &UnGraph<u64, ()>) -> usize {
+    use petgraph::graph::NodeIndex;
+    fn extend(host: &UnGraph<u64, ()>, motif: &UnGraph<u64, ()>, placed: &mut Vec<usize>) -> usize {
+        if placed.len() == motif.node_count() {
+            return 1;
+        }
+        let node = placed.len();
+        let mut count = 0;
+        for target in 0..host.node_count() {
+            if placed.contains(&target) {
+                continue;
+            }
+            placed.push(target);
+            let fits = motif.raw_edges().iter().all(|edge| {
+                let (a, b) = (edge.source().index(), edge.target().index());
+                a.max(b) != node
+                    || host.contains_edge(NodeIndex::new(placed[a]), NodeIndex::new(placed[b]))
+            });
+            if fits {
+                count += extend(host, motif, placed);
+            }
+            placed.pop();
+        }
+        count
+    }
+    extend(host, motif, &mut Vec::new())
+}
+
+#[test]
+fn initial_labels() {
+    let g = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
+    let degrees: Vec<u64> = g
+        .node_indices()
+        .map(|n| g.neighbors(n).count() as u64)
+        .collect();
+    assert_eq!(
+        wl_isomorphism::invariant_with_initial(g.clone(), &degrees),
+        wl_isomorphism::invariant(g.clone())
+    );
+    // Marking one end of the path breaks its symmetry
+    assert_ne!(
+        wl_isomorphism::invariant_with_initial(g.clone(), &[0, 0, 0, 0, 1]),
+        wl_isomorphism::invariant_with_initial(g, &[0, 0, 1, 0, 0])
+    );
+
+    // A constant label is stable on both graphs from the start, but the edges still count
+    let k4 = UnGraph::<u64, ()>::from_edges([(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+    let mut isolated = UnGraph::<u64, ()>::default();
+    for _ in 0..4 {
+        isolated.add_node(0);
+    }
+    assert_ne!(
+        wl_isomorphism::invariant_with_initial(k4, &[7; 4]),
+        wl_isomorphism::invariant_with_initial(isolated, &[7; 4])
+    );
+}
+
+#[test]
+fn engine_reuse() {
+    let mut engine = wl_isomorphism::WlEngine::new();
+    let graphs = [
+        UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]),
+        UnGraph::<u64, ()>::from_edges([(0, 1)]),
+        UnGraph::<u64, ()>::from_edges([(0, 1), (0, 2), (0, 3), (0, 4), (4, 5), (5, 6)]),
+    ];
+    for g in graphs.iter().chain(graphs.iter().rev()) {
+        assert_eq!(
+            engine.compute(g.clone()),
+            wl_isomorphism::invariant(g.clone())
+        );
+        assert_eq!(
+            engine.neighbourhood_hash(g.clone(), 3),
+            wl_isomorphism::neighbourhood_hash(g.clone(), 3)
+        );
+    }
+}
+
+#[test]
+fn cache_hits_and_eviction() {
+    let mut cache = wl_isomorphism::WlCache::new(std::num::NonZeroUsize::new(2).unwrap());
+    let path = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
+    let star = UnGraph::<u64, ()>::from_edges([(0, 1), (0, 2), (0, 3), (0, 4)]);
+    let triangle = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0)]);
+    assert_eq!(
+        cache.get_or_compute(path.clone()),
+        wl_isomorphism::invariant(path.clone())
+    );
+    assert_eq!(
+        cache.get_or_compute(path.clone()),
+        wl_isomorphism::invariant(path.clone())
+    );
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+    assert_eq!(
+        cache.get_or_compute(star.clone()),
+        wl_isomorphism::invariant(star.clone())
+    );
+    // The path was used less recently than the star, so it is evicted
+    cache.get_or_compute(triangle);
+    assert_eq!(cache.len(), 2);
+    cache.get_or_compute(star);
+    assert_eq!((cache.hits(), cache.misses()), (2, 3));
+    cache.get_or_compute(path);
+    assert_eq!((cache.hits(), cache.misses()), (2, 4));
+}
+
+#[test]
+fn engine_and_cache_empty_graph() {
+    let empty = UnGraph::<u64, ()>::default();
+    let hash = wl_isomorphism::invariant(empty.clone());
+    let mut engine = wl_isomorphism::WlEngine::new();
+    assert_eq!(engine.compute(empty.clone()), hash);
+    assert!(engine.neighbourhood_hash(empty.clone(), 3).is_empty());
+    // The engine is still usable afterwards
+    let path = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2)]);
+    assert_eq!(
+        engine.compute(path.clone()),
+        wl_isomorphism::invariant(path)
+    );
+
+    let mut cache = wl_isomorphism::WlCache::new(std::num::NonZeroUsize::new(2).unwrap());
+    assert_eq!(cache.get_or_compute(empty.clone()), hash);
+    assert_eq!(cache.get_or_compute(empty), hash);
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+}
+
+#[test]
+fn cache_same_degrees() {
+    // Both have degree sequence [1, 1, 2, 2, 2, 2], but they are not isomorphic
+    let mut cache = wl_isomorphism::WlCache::new(std::num::NonZeroUsize::new(4).unwrap());
+    let path = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)]);
+    let triangle_edge =
+        UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+    assert_ne!(
+        cache.get_or_compute(path),
+        cache.get_or_compute(triangle_edge)
+    );
+}
+
+#[test]
+fn index_types() {
+    // The same triangle with a tail, stored with three different index types
+    let edges: [(u32, u32); 4] = [(0, 1), (1, 2), (2, 0), (2, 3)];
+    let default = UnGraph::<(), ()>::from_edges(edges);
+    let small =
+        Graph::<(), (), Undirected, u16>::from_edges(edges.map(|(a, b)| (a as u16, b as u16)));
+    let large = Graph::<(), (), Undirected, usize>::from_edges(
+        edges.map(|(a, b)| (a as usize, b as usize)),
+    );
+    let expected = wl_isomorphism::invariant(default.clone());
+    assert_eq!(wl_isomorphism::invariant(small.clone()), expected);
+    assert_eq!(wl_isomorphism::invariant(large.clone()), expected);
+
+    let expected = wl_isomorphism::invariant_2wl(default.clone());
+    assert_eq!(wl_isomorphism::invariant_2wl(small.clone()), expected);
+    assert_eq!(wl_isomorphism::invariant_2wl(large.clone()), expected);
+
+    let expected = wl_isomorphism::neighbourhood_hash(default, 3);
+    assert_eq!(wl_isomorphism::neighbourhood_hash(small, 3), expected);
+    assert_eq!(wl_isomorphism::neighbourhood_hash(large, 3), expected);
+}
+
+#[test]
+fn bipartite() {
+    use petgraph::graph::NodeIndex;
+    // K_{2,3} with the parts {0, 1} and {2, 3, 4}
+    let k23 = UnGraph::<(), ()>::from_edges([(0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4)]);
+    let small: Vec<_> = (0..2).map(NodeIndex::new).collect();
+    let large: Vec<_> = (2..5).map(NodeIndex::new).collect();
+    assert_ne!(
+        wl_isomorphism::invariant_bipartite(k23.clone(), &small),
+        wl_isomorphism::invariant_bipartite(k23.clone(), &large)
+    );
+
+    // The same graph with the parts numbered the other way round
+    let k32 = UnGraph::<(), ()>::from_edges([(3, 0), (3, 1), (3, 2), (4, 0), (4, 1), (4, 2)]);
+    let small_k32: Vec<_> = (3..5).map(NodeIndex::new).collect();
+    assert_eq!(
+        wl_isomorphism::invariant_bipartite(k23.clone(), &small),
+        wl_isomorphism::invariant_bipartite(k32, &small_k32)
+    );
+    // The side labels replace the degrees, so the plain invariant differs
+    assert_ne!(
+        wl_isomorphism::invariant_bipartite(k23.clone(), &small),
+        wl_isomorphism::invariant(k23)
+    );
+
+    // The sides are stable on all three from the start, but the edges still count
+    let left: Vec<_> = (0..2).map(NodeIndex::new).collect();
+    let k22 = UnGraph::<(), ()>::from_edges([(0, 2), (0, 3), (1, 2), (1, 3)]);
+    let matching = UnGraph::<(), ()>::from_edges([(0, 2), (1, 3)]);
+    let mut empty = UnGraph::<(), ()>::default();
+    for _ in 0..4 {
+        empty.add_node(());
+    }
+    let hashes: HashSet<u64> = [k22, matching, empty]
+        .into_iter()
+        .map(|graph| wl_isomorphism::invariant_bipartite(graph, &left))
+        .collect();
+    assert_eq!(hashes.len(), 3);
+}
+
+#[test]
+fn streamed_hashes() {
+    for id in 0..20 {
+        let g = random_graph(id);
+        let expected = wl_isomorphism::neighbourhood_hash(g.clone(), 4);
+        let mut columns: Vec<Vec<u64>> = Vec::new();
+        wl_isomorphism::for_each_node_hashes(g, 4, |iteration, hashes| {
+            assert_eq!(iteration, columns.len());
+            columns.push(hashes.to_vec());
+        });
+        for (node, row) in expected.iter().enumerate() {
+            let streamed: Vec<u64> = columns.iter().map(|column| column[node]).collect();
+            assert_eq!(*row, streamed);
+        }
+    }
+}
+
+#[test]
+fn labels_per_iteration() {
+    for id in 0..20 {
+        let g = random_graph(id);
+        let rows = wl_isomorphism::neighbourhood_hash(g.clone(), 5);
+        let columns = wl_isomorphism::labels_per_iteration(g.clone(), 5);
+        assert_eq!(columns.len(), rows[0].len());
+        for (iteration, column) in columns.iter().enumerate() {
+            assert_eq!(column.len(), g.node_count());
+            for (node, &label) in column.iter().enumerate() {
+                assert_eq!(label, rows[node][iteration]);
+            }
+        }
+    }
+}
+
+// Relabel the nodes of a graph by a pseudo-random permutation, and list its edges in reverse
+fn permuted<Ty: petgraph::EdgeType>(graph: &Graph<(), (), Ty>, id: u64) -> Graph<(), (), Ty> {
+    let mut order: Vec<u32> = (0..graph.node_count() as u32).collect();
+    order.sort_unstable_by_key(|&node| twox_hash::XxHash64::oneshot(id, &node.to_le_bytes()));
+    let mut edges: Vec<(u32, u32)> = graph
+        .raw_edges()
+        .iter()
+        .map(|edge| (order[edge.source().index()], order[edge.target().index()]))
+        .collect();
+    edges.reverse();
+    let mut permuted = Graph::from_edges(edges);
+    while permuted.node_count() < graph.node_count() {
+        permuted.add_node(());
+    }
+    permuted
+}
+
+#[test]
+fn sum_aggregation_permutation_invariant() {
+    for id in 0..100 {
+        let graph = random_graph(id);
+        let expected = wl_isomorphism::invariant_sum_agg(graph.clone());
+        assert_eq!(
+            wl_isomorphism::invariant_sum_agg(permuted(&graph, id)),
+            expected
+        );
+
+        let directed = graph.into_edge_type::<petgraph::Directed>();
+        let expected = wl_isomorphism::invariant_sum_agg(directed.clone());
+        assert_eq!(
+            wl_isomorphism::invariant_sum_agg(permuted(&directed, id)),
+            expected
+        );
+    }
+}
+
+#[test]
+fn sum_aggregation_distinguishes() {
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+    let star = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+    assert_ne!(
+        wl_isomorphism::invariant_sum_agg(path),
+        wl_isomorphism::invariant_sum_agg(star)
+    );
+    // The middle nodes see neighbour degrees {1, 3} and {2, 2}, which sum to the same value unless the labels are scrambled first
+    let spider = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (2, 4), (0, 5), (5, 6)]);
+    let caterpillar =
+        UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (2, 6)]);
+    assert_ne!(
+        wl_isomorphism::invariant(spider.clone()),
+        wl_isomorphism::invariant(caterpillar.clone())
+    );
+    assert_ne!(
+        wl_isomorphism::invariant_sum_agg(spider),
+        wl_isomorphism::invariant_sum_agg(caterpillar)
+    );
+}
+
+fn path(n: u32) -> UnGraph<(), ()> {
+    UnGraph::from_edges((1..n).map(|i| (i - 1, i)))
+}
+
+#[test]
+fn until_k_colors() {
+    // The degrees separate the ends, and the first refinement separates the nodes next to them
+    for n in 5..30 {
+        assert_eq!(
+            wl_isomorphism::invariant_until_k_colors(path(n), 2),
+            Some(wl_isomorphism::invariant_iters(path(n), 1)),
+            "{}",
+            n
+        );
+        assert_eq!(
+            wl_isomorphism::invariant_until_k_colors(path(n), 3),
+            Some(wl_isomorphism::invariant_iters(path(n), 2)),
+            "{}",
+            n
+        );
+    }
+    // Too short to ever have three colours
+    assert_eq!(wl_isomorphism::invariant_until_k_colors(path(4), 3), None);
+    // A path on n nodes ends with ceil(n / 2) colours
+    assert!(wl_isomorphism::invariant_until_k_colors(path(9), 5).is_some());
+    assert_eq!(wl_isomorphism::invariant_until_k_colors(path(9), 6), None);
+}
+
+// The colour of every node in a dot file written by the crate, grouped into the sorted sizes of the colour classes, and the set of colours used
+fn dot_colours(path: &std::path::Path) -> (Vec<usize>, std::collections::BTreeSet<String>) {
+    let dot = std::fs::read_to_string(path).unwrap();
+    let mut classes: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    for line in dot
+        .lines()
+        .filter(|line| !line.contains("->") && !line.contains("--"))
+    {
+        if let Some((_, colour)) = line.split_once("fillcolor= \"") {
+            *classes.entry(colour[..7].to_string()).or_default() += 1;
+        }
+    }
+    let mut sizes: Vec<usize> = classes.values().copied().collect();
+    sizes.sort_unstable();
+    (sizes, classes.into_keys().collect())
+}
+
+#[test]
+fn write_dot_graphmap() {
+    use petgraph::graphmap::UnGraphMap;
+    let dir = tempfile::tempdir().unwrap();
+    let (graph_path, map_path) = (
+        dir.path().join("graph.dot"),
+        dir.path().join("graphmap.dot"),
+    );
+    let graph = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+    // The same graph, with other node names inserted in another order
+    let map = UnGraphMap::<u32, ()>::from_edges([(30, 20), (20, 10), (20, 0), (10, 0)]);
+    let a = wl_isomorphism::invariant_dot(graph, &graph_path).unwrap();
+    let b = wl_isomorphism::invariant_dot_graphmap(map.clone(), &map_path).unwrap();
+    assert_eq!(a, b);
+    let (graph_sizes, graph_colours) = dot_colours(&graph_path);
+    let (map_sizes, map_colours) = dot_colours(&map_path);
+    assert_eq!(graph_sizes, [1, 1, 2]);
+    assert_eq!(graph_sizes, map_sizes);
+    assert_eq!(graph_colours, map_colours);
+    // Converting the map by hand gives the same file
+    let converted_path = dir.path().join("converted.dot");
+    wl_isomorphism::invariant_dot(map.into_graph::<u32>(), &converted_path).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&converted_path).unwrap(),
+        std::fs::read_to_string(&map_path).unwrap()
+    );
+
+    // Directed maps and weighted edges work too
+    let directed = petgraph::graphmap::DiGraphMap::<char, f64>::from_edges([
+        ('x', 'y', 0.5),
+        ('y', 'z', 1.5),
+        ('x', 'z', 2.5),
+    ]);
+    let directed_path = dir.path().join("directed.dot");
+    let hash = wl_isomorphism::invariant_dot_graphmap(directed, &directed_path).unwrap();
+    assert_eq!(
+        hash,
+        wl_isomorphism::invariant(petgraph::graph::DiGraph::<(), ()>::from_edges([
+            (0, 1),
+            (1, 2),
+            (0, 2)
+        ]))
+    );
+    assert_eq!(dot_colours(&directed_path).0, [1, 1, 1]);
+    assert!(std::fs::read_to_string(&directed_path)
+        .unwrap()
+        .starts_with("digraph {"));
+}
+
+#[test]
+fn init_closure() {
+    // A path with one heavy node, at an end or next to it
+    let weighted = |weights: [u32; 4]| {
+        UnGraph::<u32, ()>::from_edges([(0, 1), (1, 2), (2, 3)])
+            .map(|n, _| weights[n.index()], |_, _| ())
+    };
+    let (g, h) = (weighted([1, 0, 0, 0]), weighted([0, 1, 0, 0]));
+    let weight = |g: &UnGraph<u32, ()>, n| *g.node_weight(n).unwrap() as u64;
+    let weights: Vec<u64> = g.node_weights().map(|&w| w as u64).collect();
+    assert_eq!(
+        wl_isomorphism::invariant_with_init(g.clone(), weight),
+        wl_isomorphism::invariant_with_initial(g.clone(), &weights)
+    );
+    assert_eq!(
+        wl_isomorphism::invariant(g.clone()),
+        wl_isomorphism::invariant(h.clone())
+    );
+    assert_ne!(
+        wl_isomorphism::invariant_with_init(g, weight),
+        wl_isomorphism::invariant_with_init(h, weight)
+    );
+
+    // A constant closure still lets the edges count
+    let constant = |_: &UnGraph<(), ()>, _| 1;
+    let k4 = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+    let mut isolated = UnGraph::<(), ()>::default();
+    for _ in 0..4 {
+        isolated.add_node(());
+    }
+    assert_ne!(
+        wl_isomorphism::invariant_with_init(k4, constant),
+        wl_isomorphism::invariant_with_init(isolated, constant)
+    );
+}
+
+#[test]
+fn insertion_order() {
+    // A small shuffle, so that the test needs no random number generator
+    fn shuffle<T>(items: &mut [T], mut state: u64) {
+        for i in (1..items.len()).rev() {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            items.swap(i, (state % (i as u64 + 1)) as usize);
+        }
+    }
+    let edges = [
+        (0, 1),
+        (1, 2),
+        (2, 0),
+        (2, 3),
+        (3, 4),
+        (4, 5),
+        (5, 3),
+        (5, 6),
+    ];
+    let original = UnGraph::<usize, ()>::from_edges(edges).map(|node, _| node.index(), |_, e| *e);
+    let expected = wl_isomorphism::neighbourhood_hash(original.clone(), 3);
+
+    for seed in 1..=5 {
+        // Add the nodes in a shuffled order, weighted by their original index, and the edges in a shuffled order and orientation
+        let mut nodes: Vec<usize> = (0..original.node_count()).collect();
+        shuffle(&mut nodes, seed);
+        let mut shuffled = UnGraph::<usize, ()>::default();
+        let mut index_of = vec![petgraph::graph::NodeIndex::end(); nodes.len()];
+        for &node in &nodes {
+            index_of[node] = shuffled.add_node(node);
+        }
+        let mut edges = edges.to_vec();
+        shuffle(&mut edges, seed + 100);
+        for (i, &(a, b)) in edges.iter().enumerate() {
+            let (a, b) = if i % 2 == 0 { (a, b) } else { (b, a) };
+            shuffled.add_edge(index_of[a as usize], index_of[b as usize], ());
+        }
+
+        assert_eq!(
+            wl_isomorphism::invariant(shuffled.clone()),
+            wl_isomorphism::invariant(original.clone())
+        );
+        let rows = wl_isomorphism::neighbourhood_hash(shuffled.clone(), 3);
+        for node in shuffled.node_indices() {
+            assert_eq!(rows[node.index()], expected[shuffled[node]]);
+        }
+    }
+}
+
+#[test]
+fn invariant_with_neighbourhood_hashes() {
+    let graphs = [
+        UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)]),
+        UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]),
+    ];
+    for g in graphs {
+        for n_iters in [1, 2, 4] {
+            let (hash, hashes) =
+                wl_isomorphism::invariant_and_neighbourhood_hash(g.clone(), n_iters);
+            assert_eq!(hash, wl_isomorphism::invariant_iters(g.clone(), n_iters));
+            assert_eq!(
+                hashes,
+                wl_isomorphism::neighbourhood_hash(g.clone(), n_iters)
+            );
+        }
+        let (hash, hashes) = wl_isomorphism::invariant_and_neighbourhood_stable(g.clone());
+        assert_eq!(hash, wl_isomorphism::invariant(g.clone()));
+        assert_eq!(hashes, wl_isomorphism::neighbourhood_stable(g));
+    }
+    let digraph = DiGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+    let (hash, hashes) = wl_isomorphism::invariant_and_neighbourhood_stable(digraph.clone());
+    assert_eq!(hash, wl_isomorphism::invariant(digraph.clone()));
+    assert_eq!(hashes, wl_isomorphism::neighbourhood_stable(digraph));
+}
+
+#[test]
+fn boundary_edges() {
+    // In K_{2,3} the two sides have different degrees, so every edge crosses
+    let k23 = UnGraph::<u64, ()>::from_edges([(0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4)]);
+    assert_eq!(wl_isomorphism::wl_boundary_edges(k23), 6);
+    // The triangular prism is 3-regular on 6 nodes, so WL does not split it and no edge crosses
+    let prism = UnGraph::<u64, ()>::from_edges([
+        (0, 1),
+        (1, 2),
+        (2, 0),
+        (3, 4),
+        (4, 5),
+        (5, 3),
+        (0, 3),
+        (1, 4),
+        (2, 5),
+    ]);
+    assert_eq!(wl_isomorphism::wl_boundary_edges(prism), 0);
+    // A spider with legs of lengths 1, 2 and 3 has no automorphisms: every node is a class of its own, so every edge crosses
+    let spider = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (2, 6)]);
+    assert_eq!(wl_isomorphism::wl_boundary_edges(spider), 6);
+}
+
+#[test]
+fn colour_adjacency() {
+    let g = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3), (3, 4)]);
+    let h = UnGraph::<u64, ()>::from_edges([(4, 3), (3, 2), (2, 4), (2, 1), (1, 0)]);
+    let counts = wl_isomorphism::colour_adjacency_counts(g.clone());
+    assert_eq!(counts, wl_isomorphism::colour_adjacency_counts(h));
+    assert_eq!(counts.values().sum::<usize>(), g.edge_count());
+    assert!(counts.keys().all(|&(first, second)| first <= second));
+    // The two nodes of the triangle that only lie on it are one class, joined by an edge
+    assert_eq!(counts.len(), 4);
+
+    // A star has one entry, between the centre and the leaves
+    let star = UnGraph::<u64, ()>::from_edges([(0, 1), (0, 2), (0, 3), (0, 4), (0, 5)]);
+    let counts = wl_isomorphism::colour_adjacency_counts(star);
+    assert_eq!(counts.len(), 1);
+    let (&(first, second), &count) = counts.iter().next().unwrap();
+    assert_ne!(first, second);
+    assert_eq!(count, 5);
+
+    // Directed edges keep the colour of the source first
+    let forward = DiGraph::<u64, ()>::from_edges([(0, 1), (0, 2)]);
+    let backward = DiGraph::<u64, ()>::from_edges([(1, 0), (2, 0)]);
+    assert_ne!(
+        wl_isomorphism::colour_adjacency_counts(forward),
+        wl_isomorphism::colour_adjacency_counts(backward)
+    );
+}
+
+#[test]
+fn dense_colours_isomorphic() {
+    // Relabel the nodes with a permutation, so that node v of the graph is node perm[v] of the copy
+    let graph = wl_isomorphism::generators::gnp(30, 0.1, 3);
+    let n = graph.node_count();
+    let perm: Vec<usize> = (0..n).map(|node| (node * 7 + 11) % n).collect();
+    let copy = UnGraph::<(), ()>::from_edges(graph.edge_indices().map(|edge| {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+        (perm[a.index()] as u32, perm[b.index()] as u32)
+    }));
+    let ids = wl_isomorphism::dense_colours(graph.clone());
+    let copy_ids = wl_isomorphism::dense_colours(copy);
+    for node in 0..n {
+        assert_eq!(ids[node], copy_ids[perm[node]]);
+    }
+    let mut sorted = ids.clone();
+    sorted.sort_unstable();
+    let mut copy_sorted = copy_ids;
+    copy_sorted.sort_unstable();
+    assert_eq!(sorted, copy_sorted);
+
+    // The classes are those of the hashed refinement, numbered 0..k
+    let classes = wl_isomorphism::colour_classes(graph);
+    let count = *ids.iter().max().unwrap() as usize + 1;
+    assert_eq!(count, *classes.iter().max().unwrap() + 1);
+    for a in 0..n {
+        for b in 0..n {
+            assert_eq!(ids[a] == ids[b], classes[a] == classes[b]);
+        }
+    }
+
+    // The incoming edges count first, so a node without them comes before one with them
+    let out_star = DiGraph::<u64, ()>::from_edges([(0, 1), (0, 2)]);
+    let in_star = DiGraph::<u64, ()>::from_edges([(1, 0), (2, 0)]);
+    assert_eq!(wl_isomorphism::dense_colours(out_star), vec![0, 1, 1]);
+    assert_eq!(wl_isomorphism::dense_colours(in_star), vec![1, 0, 0]);
+    assert!(wl_isomorphism::dense_colours(UnGraph::<u64, ()>::default()).is_empty());
+}
+
+#[test]
+fn counted_digest_equivalence() {
+    // Small random graphs repeat up to isomorphism, random regular graphs are all the same to WL, and trees are mostly told apart
+    let mut graphs: Vec<UnGraph<(), ()>> = (0..60)
+        .map(|seed| wl_isomorphism::generators::gnp(5, 0.4, seed))
+        .collect();
+    graphs.extend((0..10).map(|seed| wl_isomorphism::generators::random_regular(12, 3, seed)));
+    graphs.extend((0..20).map(|seed| wl_isomorphism::generators::barabasi_albert(9, 1, seed)));
+    graphs.extend((0..10).map(|seed| wl_isomorphism::generators::gnp(40, 0.1, seed)));
+    let plain: Vec<u64> = graphs
+        .iter()
+        .map(|graph| wl_isomorphism::invariant(graph.clone()))
+        .collect();
+    let counted: Vec<u64> = graphs
+        .iter()
+        .map(|graph| wl_isomorphism::invariant_counted(graph.clone()))
+        .collect();
+    let mut equal_pairs = 0;
+    for a in 0..graphs.len() {
+        for b in 0..graphs.len() {
+            assert_eq!(
+                plain[a] == plain[b],
+                counted[a] == counted[b],
+                "graphs {} and {}",
+                a,
+                b
+            );
+            equal_pairs += (a != b && plain[a] == plain[b]) as usize;
+        }
+    }
+    assert!(equal_pairs > 0);
+
+    // The same holds for directed graphs
+    let out_star = DiGraph::<u64, ()>::from_edges([(0, 1), (0, 2)]);
+    let in_star = DiGraph::<u64, ()>::from_edges([(1, 0), (2, 0)]);
+    let relabelled = DiGraph::<u64, ()>::from_edges([(2, 0), (2, 1)]);
+    assert_ne!(
+        wl_isomorphism::invariant_counted(out_star.clone()),
+        wl_isomorphism::invariant_counted(in_star)
+    );
+    assert_eq!(
+        wl_isomorphism::invariant_counted(out_star),
+        wl_isomorphism::invariant_counted(relabelled)
+    );
+}
+
+#[test]
+fn hash_widths() {
+    use wl_isomorphism::{invariant_width, HashWidth};
+    let g = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+    let h = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 0)]);
+    let hash = wl_isomorphism::invariant(g.clone());
+    assert_eq!(invariant_width(g.clone(), HashWidth::W64) as u64, hash);
+    assert_eq!(invariant_width(g.clone(), HashWidth::W64) >> 64, 0);
+    assert_eq!(
+        invariant_width(g.clone(), HashWidth::W32),
+        (hash & 0xFFFF_FFFF) as u128
+    );
+    let wide = invariant_width(g.clone(), HashWidth::W128);
+    assert_eq!(wide as u64, hash);
+    assert_ne!((wide >> 64) as u64, hash);
+    // The upper half is an invariant too
+    let relabelled = UnGraph::<u64, ()>::from_edges([(3, 1), (1, 2), (2, 3), (3, 0)]);
+    assert_eq!(invariant_width(relabelled, HashWidth::W128), wide);
+    assert_ne!(invariant_width(h, HashWidth::W128) >> 64, wide >> 64);
+}
+
+#[test]
+fn empty_graphs() {
+    let empty = UnGraph::<u64, ()>::default();
+    let mut single = UnGraph::<u64, ()>::default();
+    single.add_node(0);
+    let hash = wl_isomorphism::invariant(empty.clone());
+    assert_eq!(
+        wl_isomorphism::invariant(DiGraph::<u64, ()>::default()),
+        hash
+    );
+    assert_ne!(wl_isomorphism::invariant(single.clone()), hash);
+    assert_eq!(wl_isomorphism::invariant_counted(empty.clone()), hash);
+    assert!(wl_isomorphism::neighbourhood_hash(empty.clone(), 3).is_empty());
+    assert!(wl_isomorphism::neighbourhood_stable(empty.clone()).is_empty());
+    assert!(wl_isomorphism::possibly_isomorphic_early_exit(
+        empty.clone(),
+        empty.clone()
+    ));
+    assert!(!wl_isomorphism::possibly_isomorphic_early_exit(
+        empty.clone(),
+        single.clone()
+    ));
+    assert_eq!(
+        wl_isomorphism::prefix_match_iters(empty.clone(), empty.clone()),
+        wl_isomorphism::prefix_match_iters(single.clone(), single.clone())
+    );
+    assert_eq!(
+        wl_isomorphism::prefix_match_iters(empty.clone(), single.clone()),
+        0
+    );
+    assert!(wl_isomorphism::wl_color_intersection(empty.clone(), empty.clone()).is_empty());
+    assert!(wl_isomorphism::wl_color_intersection(empty, single).is_empty());
+}
@@ -0,0 +1,85 @@
+use petgraph::graph::UnGraph;
+use wl_isomorphism::{interval_graph, invariant_interval};
+
+// Meetings as (start, end) in minutes since midnight
+fn meetings() -> Vec<(u32, u32)> {
+    vec![
+        (540, 600),
+        (570, 660),
+        (600, 630),
+        (660, 720),
+        (700, 780),
+        (480, 900),
+        (800, 830),
+        (830, 840),
+    ]
+}
+
+// Connect every pair of overlapping intervals
+fn brute_force(intervals: &[(u32, u32)]) -> Vec<(usize, usize)> {
+    let mut edges = Vec::new();
+    for (i, &(a, b)) in intervals.iter().enumerate() {
+        for (j, &(c, d)) in intervals.iter().enumerate().take(i) {
+            if a <= d && c <= b {
+                edges.push((j, i));
+            }
+        }
+    }
+    edges
+}
+
+fn sorted_edges(graph: &UnGraph<(), ()>) -> Vec<(usize, usize)> {
+    let mut edges: Vec<_> = graph
+        .raw_edges()
+        .iter()
+        .map(|e| {
+            let (a, b) = (e.source().index(), e.target().index());
+            (a.min(b), a.max(b))
+        })
+        .collect();
+    edges.sort();
+    edges
+}
+
+#[test]
+fn sweep_matches_brute_force() {
+    let intervals = meetings();
+    let mut expected = brute_force(&intervals);
+    expected.sort();
+    let graph = interval_graph(&intervals);
+    assert_eq!(graph.node_count(), intervals.len());
+    assert_eq!(sorted_edges(&graph), expected);
+
+    // Random intervals, with many shared endpoints
+    for seed in 0..20u64 {
+        let intervals: Vec<(u32, u32)> = (0..30u64)
+            .map(|i| {
+                let hash = twox_hash::XxHash64::oneshot(seed, &i.to_ne_bytes());
+                let start = (hash % 50) as u32;
+                (start, start + ((hash >> 8) % 10) as u32)
+            })
+            .collect();
+        let mut expected = brute_force(&intervals);
+        expected.sort();
+        assert_eq!(sorted_edges(&interval_graph(&intervals)), expected);
+    }
+}
+
+#[test]
+fn invariant_of_the_model() {
+    let intervals = meetings();
+    let hash = invariant_interval(&intervals);
+    // Reordering the intervals or moving them in an order-preserving way does not matter
+    let mut reordered = intervals.clone();
+    reordered.reverse();
+    assert_eq!(invariant_interval(&reordered), hash);
+    let shifted: Vec<(u64, u64)> = intervals
+        .iter()
+        .map(|&(a, b)| (3 * a as u64 + 7, 3 * b as u64 + 7))
+        .collect();
+    assert_eq!(invariant_interval(&shifted), hash);
+    // Changing which meetings overlap does
+    let mut moved = intervals;
+    moved[7] = (845, 850);
+    assert_ne!(invariant_interval(&moved), hash);
+}
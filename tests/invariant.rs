@@ -0,0 +1,129 @@
+use petgraph::graph::UnGraph;
+use wl_isomorphism::{Invariant, MatchOutcome, WlError, HASH_VERSION};
+
+fn tailed_triangle() -> UnGraph<(), ()> {
+    UnGraph::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)])
+}
+
+#[test]
+fn format_and_parse() {
+    let one = Invariant::of(tailed_triangle());
+    let two = Invariant::of_2wl(tailed_triangle());
+    for invariant in [one, two] {
+        let text = invariant.to_string();
+        assert_eq!(text.len(), 6 + 16, "{}", text);
+        assert_eq!(text.parse::<Invariant>().unwrap(), invariant);
+    }
+    assert!(one
+        .to_string()
+        .starts_with(&format!("wl1v{}:", HASH_VERSION)));
+    assert!(two
+        .to_string()
+        .starts_with(&format!("wl2v{}:", HASH_VERSION)));
+
+    let small = Invariant {
+        dimension: 1,
+        scheme: 1,
+        hash: 0xab,
+    };
+    assert_eq!(small.to_string(), "wl1:00000000000000ab");
+    assert_eq!(format!("{:?}", small), "Invariant(wl1:00000000000000ab)");
+    let later = Invariant { scheme: 2, ..small };
+    assert_eq!(later.to_string(), "wl1v2:00000000000000ab");
+    assert_eq!(later.to_string().parse::<Invariant>().unwrap(), later);
+    for invalid in [
+        "",
+        "wl1",
+        "1:00000000000000ab",
+        "wl1:ab",
+        "wlx:00000000000000ab",
+        "wl1:0000000000000zab",
+        "wl1:+0000000000000ab",
+        "wl1v:00000000000000ab",
+        "wl1vx:00000000000000ab",
+    ] {
+        assert!(
+            matches!(invalid.parse::<Invariant>(), Err(WlError::Parse { .. })),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn equality() {
+    let hash = wl_isomorphism::invariant(tailed_triangle());
+    let invariant = Invariant::of(tailed_triangle());
+    assert_eq!(invariant, hash);
+    assert_eq!(hash, invariant);
+    assert_eq!(u64::from(invariant), hash);
+    assert_ne!(invariant, hash.wrapping_add(1));
+    // The dimension is part of the identity, the same hash from another dimension is a different invariant
+    let other = Invariant {
+        dimension: 2,
+        ..invariant
+    };
+    assert_ne!(invariant, other);
+}
+
+#[test]
+fn matches() {
+    let one = Invariant::of(tailed_triangle());
+    assert_eq!(one.scheme, HASH_VERSION);
+    assert_eq!(one.matches(&one), MatchOutcome::Match);
+    assert!(one.matches(&one).is_match());
+    let other = Invariant {
+        hash: one.hash ^ 1 << 63,
+        ..one
+    };
+    assert_eq!(one.matches(&other), MatchOutcome::Mismatch);
+    assert!(!one.matches(&other).is_match());
+
+    // The same graph under 2-WL, and even the same hash with another dimension, cannot be compared
+    let two = Invariant::of_2wl(tailed_triangle());
+    assert_eq!(one.matches(&two), MatchOutcome::IncomparableDimension);
+    assert_eq!(two.matches(&one), MatchOutcome::IncomparableDimension);
+    let relabelled = Invariant {
+        dimension: 2,
+        ..one
+    };
+    assert_eq!(
+        one.matches(&relabelled),
+        MatchOutcome::IncomparableDimension
+    );
+
+    // Across scheme versions, even with an equal hash
+    let stale = Invariant {
+        scheme: HASH_VERSION + 1,
+        ..one
+    };
+    assert_eq!(one.matches(&stale), MatchOutcome::IncomparableScheme);
+    assert_eq!(stale.matches(&one), MatchOutcome::IncomparableScheme);
+    assert!(!stale.matches(&one).is_match());
+}
+
+#[test]
+fn stored_before_diagonal() {
+    // The 2-WL labels changed when pairs of a node with itself got their own initial label, so 2-WL invariants written by the
+    // first scheme are stale
+    let stored: Invariant = "wl2:24477376aa20543f".parse().unwrap();
+    assert_eq!(stored.scheme, 1);
+    let current = Invariant::of_2wl(tailed_triangle());
+    assert!(current.scheme > 1);
+    assert_eq!(stored.matches(&current), MatchOutcome::IncomparableScheme);
+    // The scheme versions all invariants, so 1-WL ones from the first scheme are stale as well, even though their hashes did not change
+    let stored: Invariant = "wl1:e2c055fbd397c9b4".parse().unwrap();
+    let current = Invariant::of(tailed_triangle());
+    assert_eq!(stored.hash, current.hash);
+    assert_eq!(stored.matches(&current), MatchOutcome::IncomparableScheme);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn persistence() {
+    let invariant = Invariant::of(tailed_triangle());
+    let json = serde_json::to_string(&invariant).unwrap();
+    assert_eq!(json, format!("\"{}\"", invariant));
+    assert_eq!(serde_json::from_str::<Invariant>(&json).unwrap(), invariant);
+    assert!(serde_json::from_str::<Invariant>("\"wl1\"").is_err());
+}
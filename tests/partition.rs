@@ -0,0 +1,245 @@
+use petgraph::graph::{DiGraph, Graph, NodeIndex, UnGraph};
+use petgraph::EdgeType;
+use wl_isomorphism::generators::{barabasi_albert, gnp, random_regular};
+use wl_isomorphism::{invariant, invariant_pt};
+
+// Relabel the nodes by a pseudo-random permutation, and list the edges in reverse
+fn permuted<Ty: EdgeType>(graph: &Graph<(), (), Ty>, id: u64) -> Graph<(), (), Ty> {
+    let mut order: Vec<u32> = (0..graph.node_count() as u32).collect();
+    order.sort_unstable_by_key(|&node| twox_hash::XxHash64::oneshot(id, &node.to_le_bytes()));
+    let mut permuted = Graph::with_capacity(graph.node_count(), graph.edge_count());
+    for _ in 0..graph.node_count() {
+        permuted.add_node(());
+    }
+    for edge in graph.raw_edges().iter().rev() {
+        permuted.add_edge(
+            NodeIndex::new(order[edge.source().index()] as usize),
+            NodeIndex::new(order[edge.target().index()] as usize),
+            (),
+        );
+    }
+    permuted
+}
+
+fn path(n: u32) -> UnGraph<(), ()> {
+    UnGraph::from_edges((1..n).map(|i| (i - 1, i)))
+}
+
+// Add a relabelled copy of every graph after all of them
+fn with_copies<Ty: EdgeType>(mut graphs: Vec<Graph<(), (), Ty>>) -> Vec<Graph<(), (), Ty>> {
+    let copies: Vec<_> = graphs
+        .iter()
+        .enumerate()
+        .map(|(id, graph)| permuted(graph, id as u64))
+        .collect();
+    graphs.extend(copies);
+    graphs
+}
+
+fn corpus() -> Vec<UnGraph<(), ()>> {
+    let mut graphs = vec![
+        // A hexagon and two triangles: WL cannot tell them apart
+        UnGraph::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]),
+        UnGraph::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]),
+        // The same degrees, but different neighbourhoods
+        UnGraph::from_edges([(0, 1), (1, 2), (2, 3), (2, 4), (0, 5), (5, 6)]),
+        UnGraph::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (2, 6)]),
+        UnGraph::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]),
+        UnGraph::from_edges([(0, 1), (1, 1), (1, 2)]),
+        UnGraph::from_edges([(0, 1), (0, 1), (1, 2)]),
+    ];
+    graphs.extend([path(2), path(3), path(30), path(31)]);
+    for seed in 0..20 {
+        graphs.push(gnp(12, 0.3, seed));
+        graphs.push(barabasi_albert(15, 2, seed));
+    }
+    for seed in 0..4 {
+        graphs.push(random_regular(10, 3, seed));
+    }
+    with_copies(graphs)
+}
+
+// invariant_pt gives two graphs the same hash exactly when invariant does, and the second half of the graphs are relabelled copies
+// of the first half, which get the same hashes from both
+fn assert_agrees<Ty: EdgeType>(graphs: &[Graph<(), (), Ty>]) {
+    let wl: Vec<u64> = graphs.iter().map(|g| invariant(g.clone())).collect();
+    let pt: Vec<u64> = graphs.iter().map(|g| invariant_pt(g.clone())).collect();
+    let half = graphs.len() / 2;
+    for i in 0..half {
+        assert_eq!(wl[i], wl[half + i], "graph {} and its copy", i);
+        assert_eq!(pt[i], pt[half + i], "graph {} and its copy", i);
+    }
+    for i in 0..graphs.len() {
+        for j in 0..i {
+            assert_eq!(pt[i] == pt[j], wl[i] == wl[j], "graphs {} and {}", i, j);
+        }
+    }
+}
+
+#[test]
+fn agrees_with_invariant() {
+    assert_agrees(&corpus());
+}
+
+#[test]
+fn agrees_with_invariant_directed() {
+    let mut graphs: Vec<DiGraph<(), ()>> = corpus()
+        .into_iter()
+        .map(|graph| graph.into_edge_type())
+        .collect();
+    graphs.truncate(graphs.len() / 2);
+    // The edge orientation matters
+    graphs.push(DiGraph::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]));
+    graphs.push(DiGraph::from_edges([(0, 1), (1, 2), (2, 0), (3, 2)]));
+    graphs.push(DiGraph::from_edges([(0, 1), (2, 1), (2, 3)]));
+    graphs.push(DiGraph::from_edges([(1, 0), (1, 2), (3, 2)]));
+    for seed in 0..20 {
+        let graph: DiGraph<(), ()> = gnp(8, 0.3, seed).into_edge_type();
+        // Turn some edges around, so that the graph is not symmetric
+        let edges: Vec<_> = graph
+            .raw_edges()
+            .iter()
+            .enumerate()
+            .map(|(i, edge)| match i % 3 {
+                0 => (edge.target(), edge.source()),
+                _ => (edge.source(), edge.target()),
+            })
+            .collect();
+        let mut oriented = DiGraph::with_capacity(8, edges.len());
+        for _ in 0..8 {
+            oriented.add_node(());
+        }
+        oriented.extend_with_edges(edges);
+        graphs.push(oriented);
+    }
+    assert_agrees(&with_copies(graphs));
+}
+
+#[test]
+fn multigraphs_and_self_loops() {
+    let undirected = vec![
+        UnGraph::from_edges([(0, 0), (0, 1)]),
+        UnGraph::from_edges([(0, 0), (0, 0), (0, 1)]),
+        UnGraph::from_edges([(0, 1), (0, 1), (1, 1)]),
+        UnGraph::from_edges([(0, 1), (0, 1), (0, 1)]),
+        UnGraph::from_edges([(0, 1), (1, 2), (2, 0), (0, 0)]),
+        UnGraph::from_edges([(0, 1), (1, 2), (2, 0), (0, 1)]),
+        UnGraph::from_edges([(0, 1), (0, 1), (2, 3), (2, 3), (1, 2)]),
+        UnGraph::from_edges([(0, 0), (1, 1), (2, 2), (0, 1), (1, 2)]),
+    ];
+    let directed: Vec<DiGraph<(), ()>> = vec![
+        DiGraph::from_edges([(0, 0), (0, 1)]),
+        DiGraph::from_edges([(0, 0), (1, 0)]),
+        DiGraph::from_edges([(0, 1), (0, 1), (1, 0)]),
+        DiGraph::from_edges([(0, 1), (1, 0), (1, 0)]),
+        DiGraph::from_edges([(0, 1), (1, 0), (1, 2), (2, 1)]),
+        DiGraph::from_edges([(0, 1), (0, 1), (1, 2), (1, 2)]),
+        DiGraph::from_edges([(0, 1), (1, 2), (2, 0), (1, 1)]),
+        DiGraph::from_edges([(0, 1), (1, 2), (2, 0), (1, 1), (1, 1)]),
+    ];
+    assert_agrees(&with_copies(undirected));
+    assert_agrees(&with_copies(directed));
+}
+
+#[test]
+fn suite_pairs() {
+    // The pairs of graphs that the other tests compare with invariant, which must get the same verdict here
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4)]);
+    let leaf_near = UnGraph::from_edges((0..11).map(|node| (node, node + 1)).chain([(3, 12)]));
+    let leaf_far = UnGraph::from_edges((0..11).map(|node| (node, node + 1)).chain([(4, 12)]));
+    let k33 = UnGraph::from_edges([
+        (0, 3),
+        (0, 4),
+        (0, 5),
+        (1, 3),
+        (1, 4),
+        (1, 5),
+        (2, 3),
+        (2, 4),
+        (2, 5),
+    ]);
+    let prism = UnGraph::from_edges([
+        (0, 1),
+        (1, 2),
+        (2, 0),
+        (3, 4),
+        (4, 5),
+        (5, 3),
+        (0, 3),
+        (1, 4),
+        (2, 5),
+    ]);
+    let hexagon = UnGraph::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+    let two_triangles = UnGraph::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+    let tailed_triangle = UnGraph::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+    let pairs: Vec<(UnGraph<(), ()>, UnGraph<(), ()>)> = vec![
+        (
+            path.clone(),
+            UnGraph::from_edges([(1, 0), (2, 1), (2, 3), (4, 3)]),
+        ),
+        (path, UnGraph::from_edges([(0, 1), (0, 2), (0, 3), (0, 4)])),
+        (
+            UnGraph::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (1, 5)]),
+            UnGraph::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (2, 5)]),
+        ),
+        (leaf_near, leaf_far),
+        (
+            UnGraph::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)]),
+            two_triangles.clone(),
+        ),
+        (hexagon.clone(), two_triangles),
+        (
+            hexagon,
+            UnGraph::from_edges([(3, 1), (1, 5), (5, 0), (0, 2), (2, 4), (4, 3)]),
+        ),
+        (k33, prism),
+        (
+            tailed_triangle.clone(),
+            UnGraph::from_edges([(3, 1), (1, 2), (2, 3), (3, 0)]),
+        ),
+        (
+            tailed_triangle,
+            UnGraph::from_edges([(0, 1), (1, 2), (2, 3), (3, 0)]),
+        ),
+    ];
+    for (i, (g, h)) in pairs.into_iter().enumerate() {
+        assert_eq!(
+            invariant_pt(g.clone()) == invariant_pt(h.clone()),
+            invariant(g) == invariant(h),
+            "pair {}",
+            i
+        );
+    }
+}
+
+#[test]
+fn early_stop() {
+    // The degree classes of both graphs are already stable, and only in the second graph are the nodes of degree 3 adjacent to
+    // leaves. invariant stops at the first iteration that splits no class and hashes the degrees alone, so it gives both graphs the
+    // same hash; the verdicts only differ on graphs like these, which none of the other tests compare
+    let first = UnGraph::<(), ()>::from_edges([(0, 4), (1, 2), (1, 3), (1, 6), (2, 6), (3, 6)]);
+    let second = UnGraph::<(), ()>::from_edges([(0, 3), (1, 2), (1, 3), (2, 6), (3, 6), (5, 6)]);
+    assert_ne!(invariant_pt(first), invariant_pt(second));
+
+    let first = DiGraph::<(), ()>::from_edges([(2, 5), (2, 1), (1, 3), (4, 3)]);
+    let mut second = DiGraph::<(), ()>::from_edges([(4, 3), (2, 3), (4, 0), (1, 2)]);
+    second.add_node(());
+    assert_eq!(first.node_count(), second.node_count());
+    assert_ne!(invariant_pt(first), invariant_pt(second));
+}
+
+#[test]
+fn empty_graph() {
+    assert_eq!(
+        invariant_pt(UnGraph::<(), ()>::default()),
+        invariant_pt(DiGraph::<(), ()>::default())
+    );
+}
+
+#[test]
+fn long_path() {
+    // Far too many iterations for the hash-based refinement to finish quickly
+    let n = 100_000;
+    assert_eq!(invariant_pt(path(n)), invariant_pt(permuted(&path(n), 7)));
+    assert_ne!(invariant_pt(path(n)), invariant_pt(path(n + 1)));
+}
@@ -0,0 +1,56 @@
+use wl_isomorphism::{compare, ungraph_from_edgelist, CompareOptions, Comparison};
+
+// Pairs of graphs in tests/fixtures, in the NetworkX edgelist format: a random G(100, 0.05) graph without isolated nodes, a random
+// tree on 100 nodes and a CFI pair. The second graph of an isomorphic pair is the first with its nodes and edges shuffled
+fn pair(
+    name: &str,
+) -> (
+    petgraph::graph::UnGraph<(), ()>,
+    petgraph::graph::UnGraph<(), ()>,
+) {
+    (
+        ungraph_from_edgelist(format!("tests/fixtures/{}1.edgelist", name)).unwrap(),
+        ungraph_from_edgelist(format!("tests/fixtures/{}2.edgelist", name)).unwrap(),
+    )
+}
+
+#[test]
+fn isomorphic_pairs() {
+    for name in ["random_iso", "rantree"] {
+        let (g, h) = pair(name);
+        assert_eq!(g.node_count(), 100);
+        assert_eq!(
+            wl_isomorphism::invariant(g.clone()),
+            wl_isomorphism::invariant(h.clone()),
+            "{}",
+            name
+        );
+        assert_eq!(
+            wl_isomorphism::invariant_2wl(g.clone()),
+            wl_isomorphism::invariant_2wl(h.clone()),
+            "{}",
+            name
+        );
+        let opts = CompareOptions {
+            escalate_2wl: false,
+            exact_max_nodes: 100,
+        };
+        assert_eq!(compare(g, h, opts), Comparison::Equal, "{}", name);
+    }
+}
+
+#[test]
+fn cfi_pair() {
+    // The Cai-Fürer-Immerman construction over K4, once with an edge twisted: the graphs are not isomorphic, but look alike to WL
+    let (g, h) = pair("cfi");
+    assert_eq!((g.node_count(), g.edge_count()), (40, 60));
+    assert_eq!(
+        wl_isomorphism::invariant(g.clone()),
+        wl_isomorphism::invariant(h.clone())
+    );
+    let opts = CompareOptions {
+        escalate_2wl: false,
+        exact_max_nodes: 40,
+    };
+    assert_eq!(compare(g, h, opts), Comparison::WlEqualButNotIsomorphic);
+}
@@ -0,0 +1,134 @@
+use petgraph::graph::UnGraph;
+use wl_isomorphism::generators::{gnp, random_regular};
+use wl_isomorphism::{apply_node_permutation, invariant_rwpe, invariant_spectral_init};
+
+fn k33() -> UnGraph<(), ()> {
+    UnGraph::from_edges([
+        (0, 3),
+        (0, 4),
+        (0, 5),
+        (1, 3),
+        (1, 4),
+        (1, 5),
+        (2, 3),
+        (2, 4),
+        (2, 5),
+    ])
+}
+
+fn prism() -> UnGraph<(), ()> {
+    UnGraph::from_edges([
+        (0, 1),
+        (1, 2),
+        (2, 0),
+        (3, 4),
+        (4, 5),
+        (5, 3),
+        (0, 3),
+        (1, 4),
+        (2, 5),
+    ])
+}
+
+#[test]
+fn cubic_graphs_on_six_nodes() {
+    // Both are 3-regular, so plain WL cannot tell them apart
+    assert_eq!(
+        wl_isomorphism::invariant(k33()),
+        wl_isomorphism::invariant(prism())
+    );
+    assert_ne!(
+        invariant_spectral_init(k33()),
+        invariant_spectral_init(prism())
+    );
+}
+
+#[test]
+fn simple_fiedler_value() {
+    // On a path the Fiedler value is not repeated, so the eigenspace is spanned by a single vector
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 6)]);
+    let reversed = UnGraph::<(), ()>::from_edges([(6, 5), (5, 4), (4, 3), (3, 2), (2, 1), (1, 0)]);
+    let shuffled = UnGraph::<(), ()>::from_edges([(3, 0), (0, 6), (6, 1), (1, 4), (4, 2), (2, 5)]);
+    let hash = invariant_spectral_init(path);
+    assert_eq!(hash, invariant_spectral_init(reversed));
+    assert_eq!(hash, invariant_spectral_init(shuffled));
+
+    let mut single = UnGraph::<(), ()>::default();
+    single.add_node(());
+    invariant_spectral_init(single);
+}
+
+#[test]
+fn permuted_copies() {
+    // A small shuffle, so that the test needs no random number generator
+    fn shuffle<T>(items: &mut [T], mut state: u64) {
+        for i in (1..items.len()).rev() {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            items.swap(i, (state % (i as u64 + 1)) as usize);
+        }
+    }
+    // Random graphs of all densities, so also disconnected ones and ones with repeated eigenvalues
+    for seed in 1..=500 {
+        let n = 2 + seed as usize % 19;
+        // Every other graph is regular, which plain WL cannot tell apart at all
+        let graph = if seed % 2 == 0 {
+            gnp(n, (seed % 9 + 1) as f64 / 10.0, seed)
+        } else {
+            random_regular(2 * (n / 2) + 2, 3, seed)
+        };
+        let n = graph.node_count();
+        let mut perm: Vec<usize> = (0..n).collect();
+        shuffle(&mut perm, seed);
+        assert_eq!(
+            invariant_spectral_init(graph.clone()),
+            invariant_spectral_init(apply_node_permutation(graph, &perm)),
+            "seed {seed}"
+        );
+    }
+}
+
+#[test]
+fn return_probabilities() {
+    // Every node of a hexagon looks like every node of two triangles to plain WL, but only in a triangle can a walk of three steps
+    // return to its start
+    let hexagon = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+    let triangles = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+    assert_ne!(
+        invariant_rwpe(hexagon.clone(), 3, 8, 42),
+        invariant_rwpe(triangles.clone(), 3, 8, 42)
+    );
+    // After two steps both return with probability 1/2
+    assert_eq!(
+        invariant_rwpe(hexagon, 2, 8, 42),
+        invariant_rwpe(triangles, 2, 8, 42)
+    );
+    // A walk on the prism returns through its triangle with probability 2/27, which only finer bins see
+    assert_eq!(
+        invariant_rwpe(k33(), 3, 8, 42),
+        invariant_rwpe(prism(), 3, 8, 42)
+    );
+    assert_ne!(
+        invariant_rwpe(k33(), 3, 16, 42),
+        invariant_rwpe(prism(), 3, 16, 42)
+    );
+
+    // Isomorphic graphs get equal hashes, including when the probabilities fall on bin boundaries
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 6)]);
+    let shuffled = UnGraph::<(), ()>::from_edges([(3, 0), (0, 6), (6, 1), (1, 4), (4, 2), (2, 5)]);
+    for walk_length in 0..6 {
+        assert_eq!(
+            invariant_rwpe(path.clone(), walk_length, 4, 42),
+            invariant_rwpe(shuffled.clone(), walk_length, 4, 42)
+        );
+    }
+}
+
+#[test]
+fn empty_graph() {
+    let empty = UnGraph::<(), ()>::default();
+    let hash = wl_isomorphism::invariant(empty.clone());
+    assert_eq!(invariant_spectral_init(empty.clone()), hash);
+    assert_eq!(invariant_rwpe(empty, 3, 8, 42), hash);
+}
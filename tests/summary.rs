@@ -0,0 +1,117 @@
+use petgraph::graph::{DiGraph, UnGraph};
+use wl_isomorphism::{summarise, GraphWarning};
+
+#[test]
+fn doc_example() {
+    let graph = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0), (2, 3)]);
+    let summary = summarise(graph.clone());
+    assert_eq!(summary.nodes, 4);
+    assert_eq!(summary.edges, 4);
+    assert!((summary.density - 4.0 / 6.0).abs() < 1e-12);
+    assert_eq!(summary.max_degree, 3);
+    // The tail, the node it hangs from, and the other two triangle nodes
+    assert_eq!(summary.wl_classes, 3);
+    assert_eq!(summary.invariant, wl_isomorphism::invariant(graph));
+
+    let line = summary.to_string();
+    for field in [
+        "4 nodes",
+        "4 edges",
+        "density 0.667",
+        "max degree 3",
+        "3 WL classes",
+    ] {
+        assert!(line.contains(field), "{}", line);
+    }
+    assert!(line.contains(&summary.invariant.to_string()), "{}", line);
+}
+
+#[test]
+fn directed_and_single_node() {
+    let summary = summarise(DiGraph::<(), ()>::from_edges([(0, 1), (1, 2)]));
+    assert!((summary.density - 2.0 / 6.0).abs() < 1e-12);
+    assert_eq!(summary.max_degree, 2);
+    assert_eq!(summary.wl_classes, 3);
+
+    let mut graph = UnGraph::<(), ()>::default();
+    graph.add_node(());
+    let summary = summarise(graph);
+    assert_eq!(
+        (summary.nodes, summary.density, summary.wl_classes),
+        (1, 0.0, 1)
+    );
+}
+
+#[test]
+fn empty_graph() {
+    let summary = summarise(UnGraph::<(), ()>::default());
+    assert_eq!(
+        (
+            summary.nodes,
+            summary.edges,
+            summary.density,
+            summary.max_degree,
+            summary.wl_classes
+        ),
+        (0, 0, 0.0, 0, 0)
+    );
+    assert_eq!(
+        summary.invariant,
+        wl_isomorphism::invariant(UnGraph::<(), ()>::default())
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn persistence() {
+    let summary = summarise(UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]));
+    let json = serde_json::to_string(&summary).unwrap();
+    let loaded: wl_isomorphism::GraphSummary = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded, summary);
+}
+
+#[test]
+fn validation() {
+    // Node 0 is left out, edges 1 and 3 repeat edge 0, and edge 2 is a self-loop
+    let graph = UnGraph::<(), ()>::from_edges([(1, 2), (2, 1), (3, 3), (1, 2), (2, 3)]);
+    let (hash, warnings) = wl_isomorphism::invariant_validated(graph.clone());
+    assert_eq!(hash, wl_isomorphism::invariant(graph));
+    assert_eq!(
+        warnings,
+        vec![
+            GraphWarning::IsolatedNode { node: 0 },
+            GraphWarning::SelfLoop { node: 3, edge: 2 },
+            GraphWarning::ParallelEdges {
+                source: 1,
+                target: 2,
+                edges: vec![0, 1, 3]
+            },
+        ]
+    );
+
+    // In a directed graph, edges back are not parallel but reciprocal
+    let graph = DiGraph::<(), ()>::from_edges([(0, 1), (2, 1), (1, 0), (1, 2), (1, 0)]);
+    assert_eq!(
+        wl_isomorphism::validate(&graph),
+        vec![
+            GraphWarning::ParallelEdges {
+                source: 1,
+                target: 0,
+                edges: vec![2, 4]
+            },
+            GraphWarning::ReciprocalEdges {
+                source: 0,
+                target: 1,
+                forward: 0,
+                backward: 2
+            },
+            GraphWarning::ReciprocalEdges {
+                source: 1,
+                target: 2,
+                forward: 3,
+                backward: 1
+            },
+        ]
+    );
+    assert!(wl_isomorphism::validate(&UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)])).is_empty());
+}
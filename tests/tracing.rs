@@ -0,0 +1,65 @@
+#![cfg(feature = "tracing")]
+
+use petgraph::graph::UnGraph;
+use std::sync::{Arc, Mutex};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+// A span name, with the name of its parent
+type Record = (String, Option<String>);
+
+// Records every new span with the name of its parent, and every event with the name of the span it happened in
+#[derive(Clone, Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<Record>>>,
+    events: Arc<Mutex<Vec<Option<String>>>>,
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Recorder {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let parent = ctx
+            .span(id)
+            .and_then(|span| span.parent())
+            .map(|parent| parent.name().to_string());
+        self.spans
+            .lock()
+            .unwrap()
+            .push((attrs.metadata().name().to_string(), parent));
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let span = ctx.event_span(event).map(|span| span.name().to_string());
+        self.events.lock().unwrap().push(span);
+    }
+}
+
+#[test]
+fn span_hierarchy() {
+    let recorder = Recorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+    let path = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 6)]);
+    tracing::subscriber::with_default(subscriber, || {
+        wl_isomorphism::invariant(path);
+    });
+
+    let spans = recorder.spans.lock().unwrap();
+    assert_eq!(spans[0], ("wl_run".to_string(), None));
+    // A path of 7 nodes splits twice after the degrees, and one more iteration confirms it is stable
+    assert_eq!(spans.len(), 4);
+    for span in &spans[1..] {
+        assert_eq!(
+            span,
+            &(
+                "calculate_new_labels".to_string(),
+                Some("wl_run".to_string())
+            )
+        );
+    }
+    // Every iteration that was kept is reported within the run
+    let events = recorder.events.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|span| span.as_deref() == Some("wl_run")));
+}
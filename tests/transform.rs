@@ -0,0 +1,170 @@
+use petgraph::graph::{DiGraph, NodeIndex, UnGraph};
+use wl_isomorphism::{
+    apply_node_permutation, invariant, to_symmetric_digraph, to_undirected_skeleton,
+    ReciprocalPolicy,
+};
+
+#[test]
+fn contract_triangle() {
+    let triangle = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0)]);
+    let double_edge = UnGraph::<u64, ()>::from_edges([(0, 1), (0, 1)]);
+    let expected = wl_isomorphism::invariant(double_edge);
+    for (u, v) in [(0, 1), (1, 2), (2, 0)] {
+        let (u, v) = (NodeIndex::new(u), NodeIndex::new(v));
+        let contracted = wl_isomorphism::contract_edge(triangle.clone(), u, v);
+        assert_eq!(contracted.node_count(), 2);
+        assert_eq!(contracted.edge_count(), 2);
+        assert_eq!(
+            wl_isomorphism::invariant_contracted(triangle.clone(), u, v),
+            expected
+        );
+    }
+}
+
+#[test]
+fn contract_without_edge() {
+    // The ends of a path of four nodes are not adjacent, so merging them identifies them into a triangle
+    let path = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3)]);
+    let (first, last) = (NodeIndex::new(0), NodeIndex::new(3));
+    assert!(!path.contains_edge(first, last));
+    let merged = wl_isomorphism::contract_edge(path.clone(), first, last);
+    assert_eq!((merged.node_count(), merged.edge_count()), (3, 3));
+    let triangle = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0)]);
+    assert_eq!(invariant(merged), invariant(triangle.clone()));
+    assert_eq!(
+        wl_isomorphism::invariant_contracted(path, first, last),
+        invariant(triangle)
+    );
+
+    // Nodes in different components are merged too, into a cut vertex
+    let two_edges = UnGraph::<u64, ()>::from_edges([(0, 1), (2, 3)]);
+    let merged = wl_isomorphism::contract_edge(two_edges, NodeIndex::new(1), NodeIndex::new(2));
+    assert_eq!(
+        invariant(merged),
+        invariant(UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2)]))
+    );
+}
+
+#[test]
+fn contract_keeps_weights() {
+    let mut g = UnGraph::<&str, u32>::new_undirected();
+    let a = g.add_node("a");
+    let b = g.add_node("b");
+    let c = g.add_node("c");
+    let d = g.add_node("d");
+    g.extend_with_edges([(a, b, 1), (b, c, 2), (c, d, 3)]);
+    let contracted = wl_isomorphism::contract_edge(g, b, c);
+    let mut weights: Vec<_> = contracted.node_weights().copied().collect();
+    weights.sort_unstable();
+    assert_eq!(weights, ["a", "b", "d"]);
+    let mut edges: Vec<_> = contracted.edge_weights().copied().collect();
+    edges.sort_unstable();
+    assert_eq!(edges, [1, 3]);
+}
+
+#[test]
+fn components() {
+    let two_triangles =
+        UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)]);
+    let hexagon = UnGraph::<u64, ()>::from_edges([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0)]);
+    // The whole graphs are 2-regular, so WL cannot tell them apart
+    assert_eq!(
+        wl_isomorphism::invariant(two_triangles.clone()),
+        wl_isomorphism::invariant(hexagon.clone())
+    );
+    assert!(!wl_isomorphism::components_possibly_isomorphic(
+        two_triangles.clone(),
+        hexagon
+    ));
+
+    // The same two triangles with interleaved node numbers, plus an isolated node in both
+    let mut interleaved =
+        UnGraph::<u64, ()>::from_edges([(0, 2), (2, 4), (4, 0), (1, 3), (3, 5), (5, 1)]);
+    interleaved.add_node(0);
+    let mut two_triangles = two_triangles;
+    two_triangles.add_node(0);
+    assert!(wl_isomorphism::components_possibly_isomorphic(
+        two_triangles,
+        interleaved
+    ));
+}
+
+#[test]
+fn direction_conversions() {
+    let reverse = |graph: &DiGraph<(), u32>| {
+        let mut reversed = graph.clone();
+        reversed.reverse();
+        reversed
+    };
+    // A two-way edge, a one-way edge, two parallel one-way edges and a self-loop
+    let g = DiGraph::<(), u32>::from_edges([
+        (0, 1, 1),
+        (1, 0, 2),
+        (1, 2, 3),
+        (2, 3, 4),
+        (2, 3, 5),
+        (3, 3, 6),
+    ]);
+    for policy in [
+        ReciprocalPolicy::KeepBoth,
+        ReciprocalPolicy::KeepFirst,
+        ReciprocalPolicy::Merge(|a, b| a + b),
+    ] {
+        let skeleton = to_undirected_skeleton(g.clone(), policy);
+        assert_eq!(
+            invariant(skeleton.clone()),
+            invariant(to_undirected_skeleton(reverse(&g), policy))
+        );
+        let weights: Vec<u32> = skeleton.edge_weights().copied().collect();
+        match policy {
+            ReciprocalPolicy::KeepBoth => assert_eq!(weights, [1, 2, 3, 4, 5, 6]),
+            ReciprocalPolicy::KeepFirst => assert_eq!(weights, [1, 3, 4, 5, 6]),
+            ReciprocalPolicy::Merge(_) => assert_eq!(weights, [3, 3, 4, 5, 6]),
+        }
+    }
+    // Reciprocal edges pair up one to one
+    let g = DiGraph::<(), u32>::from_edges([(0, 1, 1), (0, 1, 2), (1, 0, 3)]);
+    let skeleton = to_undirected_skeleton(g, ReciprocalPolicy::Merge(|a, b| a * 10 + b));
+    assert_eq!(
+        skeleton.edge_weights().copied().collect::<Vec<_>>(),
+        [13, 2]
+    );
+
+    // Writing the undirected edges the other way around makes no difference, and the skeleton of the symmetric digraph is the graph again
+    let g = UnGraph::<(), u32>::from_edges([(0, 1, 1), (1, 2, 2), (2, 0, 3), (2, 3, 4), (3, 3, 5)]);
+    let flipped =
+        UnGraph::<(), u32>::from_edges([(1, 0, 1), (2, 1, 2), (0, 2, 3), (3, 2, 4), (3, 3, 5)]);
+    let symmetric = to_symmetric_digraph(g.clone());
+    assert_eq!(symmetric.edge_count(), 9);
+    assert_eq!(
+        invariant(symmetric.clone()),
+        invariant(to_symmetric_digraph(flipped))
+    );
+    let round_trip = to_undirected_skeleton(symmetric, ReciprocalPolicy::KeepFirst);
+    assert_eq!(
+        round_trip.edge_weights().collect::<Vec<_>>(),
+        g.edge_weights().collect::<Vec<_>>()
+    );
+    assert_eq!(invariant(round_trip), invariant(g));
+}
+
+#[test]
+fn node_permutation() {
+    let path = UnGraph::<u32, u32>::from_edges([(0, 1, 10), (1, 2, 11), (2, 3, 12), (3, 4, 13)])
+        .map(|node, _| node.index() as u32 * 100, |_, &weight| weight);
+    let perm = [2, 4, 0, 1, 3];
+    let permuted = apply_node_permutation(path.clone(), &perm);
+    let edges: Vec<_> = permuted
+        .raw_edges()
+        .iter()
+        .map(|edge| (edge.source().index(), edge.target().index(), edge.weight))
+        .collect();
+    assert_eq!(edges, [(2, 4, 10), (4, 0, 11), (0, 1, 12), (1, 3, 13)]);
+    let weights: Vec<u32> = permuted.node_weights().copied().collect();
+    assert_eq!(weights, [200, 300, 0, 400, 100]);
+    assert_eq!(invariant(path.clone()), invariant(permuted));
+    assert_eq!(
+        wl_isomorphism::invariant_full_attr(path.clone()),
+        wl_isomorphism::invariant_full_attr(apply_node_permutation(path, &perm))
+    );
+}